@@ -0,0 +1,52 @@
+use b3::{bit_store::BitStore, bit_vec::BitVec};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ux::u1;
+
+const NUM_BITS: usize = 1 << 20;
+
+fn make_vec<S: BitStore>() -> BitVec<S> {
+    let bytes: Vec<u8> = (0..NUM_BITS / 8).map(|i| (i * 31) as u8).collect();
+    let mut vec = BitVec::from_bytes(&bytes, NUM_BITS);
+    // Make sure searches have to scan the whole vector
+    for i in 0..NUM_BITS - 1 {
+        vec.set(i, u1::new(0));
+    }
+    vec
+}
+
+fn bench_backend<S: BitStore>(c: &mut Criterion, name: &str) {
+    let vec = make_vec::<S>();
+    let other = make_vec::<S>();
+
+    c.bench_with_input(BenchmarkId::new("count_ones", name), &vec, |b, vec| {
+        b.iter(|| black_box(vec.count_ones()))
+    });
+    c.bench_with_input(BenchmarkId::new("first_one", name), &vec, |b, vec| {
+        b.iter(|| black_box(vec.first_one()))
+    });
+    c.bench_with_input(BenchmarkId::new("xor_assign", name), &vec, |b, vec| {
+        let mut vec = BitVec::<S>::from_bytes(&vec.to_bytes(), vec.len());
+        b.iter(|| vec ^= black_box(&other))
+    });
+    c.bench_function(&format!("push/{name}"), |b| {
+        b.iter(|| {
+            let mut vec = BitVec::<S>::default();
+            vec.reserve(NUM_BITS);
+            for i in 0..NUM_BITS {
+                vec.push(u1::new((i & 1) as u8));
+            }
+            black_box(vec)
+        })
+    });
+    c.bench_with_input(BenchmarkId::new("to_bytes", name), &vec, |b, vec| {
+        b.iter(|| black_box(vec.to_bytes()))
+    });
+}
+
+fn bit_vec_backends(c: &mut Criterion) {
+    bench_backend::<u8>(c, "u8");
+    bench_backend::<u64>(c, "u64");
+}
+
+criterion_group!(benches, bit_vec_backends);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use std::ops::RangeBounds;
+use core::ops::RangeBounds;
 
 use crate::{
     error::B3Result,
@@ -12,6 +12,20 @@ pub trait BitBuffer {
     fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>>;
 }
 
+/// [`BitBufferSplit`] is a trait leveraged by [`BitCursor::split_to`] for taking parts of a
+/// buffer.  A part is usually a [`BitSlice`] borrowing the buffer, but a part of a
+/// [`BitSlice<'a>`] is another [`BitSlice<'a>`], which doesn't need to borrow the buffer it was
+/// taken from.
+///
+/// [`BitCursor::split_to`]: crate::bit_cursor::BitCursor::split_to
+pub trait BitBufferSplit: BitBuffer {
+    type Part<'b>: BitBuffer
+    where
+        Self: 'b;
+
+    fn get_part<T: RangeBounds<usize>>(&self, range: T) -> B3Result<Self::Part<'_>>;
+}
+
 /// [`BitBufferMut`] is a trait leveraged by [`BitCursor`] for writing data.
 pub trait BitBufferMut: BitBuffer {
     fn get_slice_mut<T: RangeBounds<usize>>(&mut self, range: T) -> B3Result<BitSliceMut<'_>>;
@@ -0,0 +1,271 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{
+    bit_buffer::{BitBuffer, BitBufferSplit},
+    bit_vec::BitVec,
+    error::{B3Error, B3Result},
+    slice::BitSlice,
+};
+
+/// An immutable, reference-counted buffer of bits.
+///
+/// The bytes are held in an [`Arc`], so cloning a [`BitBytes`], taking a
+/// [`slice`](BitBytes::slice) of one or splitting one with [`split_to`](BitBytes::split_to) and
+/// [`split_off`](BitBytes::split_off) are all O(1) and don't copy any data.  Unlike a
+/// [`BitSlice`], a [`BitBytes`] doesn't borrow from anything, so it can be stored or sent to
+/// another thread freely.
+///
+/// # Example
+/// ```
+/// use b3::{bit_bytes::BitBytes, bit_cursor::BitCursor, bit_read_exts::BitReadExts};
+/// use ux::u4;
+///
+/// let mut packet = BitBytes::from_vec(vec![0b1011_0010, 0xFF]);
+/// let header = packet.split_to(4).unwrap();
+/// assert_eq!(header.len(), 4);
+/// assert_eq!(packet.len(), 12);
+///
+/// let mut cursor = BitCursor::new(header);
+/// assert_eq!(cursor.read_u4().unwrap(), u4::new(0b1011));
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitBytes {
+    buf: Arc<[u8]>,
+    start_bit_index: usize,
+    end_bit_index: usize,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl BitBytes {
+    /// Create a BitBytes from the given buffer.  The length will be the length of the vector *
+    /// 8.
+    pub fn from_vec(data: Vec<u8>) -> BitBytes {
+        let end_bit_index = data.len() * 8;
+        BitBytes {
+            buf: data.into(),
+            start_bit_index: 0,
+            end_bit_index,
+        }
+    }
+
+    /// The length of this buffer, in bits.
+    pub fn len(&self) -> usize {
+        self.end_bit_index - self.start_bit_index
+    }
+
+    /// Get a [`BitSlice`] of this buffer corresponding to the given range.
+    pub fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+        let (start, end) = self.bit_range(range)?;
+        Ok(BitSlice::new(&self.buf, start, end))
+    }
+
+    /// Get a new [`BitBytes`] containing the given range of this one, which shares the same
+    /// underlying buffer.
+    pub fn slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitBytes> {
+        let (start, end) = self.bit_range(range)?;
+        Ok(BitBytes {
+            buf: Arc::clone(&self.buf),
+            start_bit_index: start,
+            end_bit_index: end,
+        })
+    }
+
+    /// Split this buffer in two at `at`: afterwards this buffer contains the bits in [at, len),
+    /// and the returned one contains those in [0, at).  Returns an error (leaving this buffer
+    /// unchanged) if `at` is greater than the length of the buffer.
+    pub fn split_to(&mut self, at: usize) -> B3Result<BitBytes> {
+        let front = self.slice(..at)?;
+        self.start_bit_index = front.end_bit_index;
+        Ok(front)
+    }
+
+    /// Split this buffer in two at `at`: afterwards this buffer contains the bits in [0, at),
+    /// and the returned one contains those in [at, len).  Returns an error (leaving this buffer
+    /// unchanged) if `at` is greater than the length of the buffer.
+    pub fn split_off(&mut self, at: usize) -> B3Result<BitBytes> {
+        let back = self.slice(at..)?;
+        self.end_bit_index = back.start_bit_index;
+        Ok(back)
+    }
+
+    /// Shorten this buffer to `len` bits.  Has no effect if `len` is greater than the current
+    /// length.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.end_bit_index = self.start_bit_index + len;
+        }
+    }
+
+    /// View the whole buffer as a [`BitSlice`].
+    pub fn as_bit_slice(&self) -> BitSlice<'_> {
+        BitSlice::new(&self.buf, self.start_bit_index, self.end_bit_index)
+    }
+
+    /// Resolve `range` (relative to this buffer) into start and end bit indices in `buf`.
+    fn bit_range<T: RangeBounds<usize>>(&self, range: T) -> B3Result<(usize, usize)> {
+        let out_of_range = |slice_start, slice_end| B3Error::SliceOutOfRange {
+            len: self.len(),
+            slice_start,
+            slice_end,
+        };
+        let start = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.checked_add(1).ok_or(out_of_range(s, s))?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&e) => e.checked_add(1).ok_or(out_of_range(start, e))?,
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            return Err(out_of_range(start, end));
+        }
+        Ok((self.start_bit_index + start, self.start_bit_index + end))
+    }
+}
+
+impl From<Vec<u8>> for BitBytes {
+    fn from(data: Vec<u8>) -> Self {
+        BitBytes::from_vec(data)
+    }
+}
+
+impl From<BitVec> for BitBytes {
+    fn from(vec: BitVec) -> Self {
+        BitBytes {
+            buf: vec.as_bytes().into(),
+            start_bit_index: 0,
+            end_bit_index: vec.len(),
+        }
+    }
+}
+
+impl From<BitSlice<'_>> for BitBytes {
+    fn from(slice: BitSlice<'_>) -> Self {
+        BitBytes {
+            buf: slice.bytes().collect(),
+            start_bit_index: 0,
+            end_bit_index: slice.len(),
+        }
+    }
+}
+
+impl PartialEq for BitBytes {
+    fn eq(&self, other: &Self) -> bool {
+        PartialEq::eq(&self.as_bit_slice(), &other.as_bit_slice())
+    }
+}
+
+impl Eq for BitBytes {}
+
+impl Hash for BitBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&self.as_bit_slice(), state)
+    }
+}
+
+impl PartialOrd for BitBytes {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BitBytes {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&self.as_bit_slice(), &other.as_bit_slice())
+    }
+}
+
+impl PartialEq<BitVec> for BitBytes {
+    fn eq(&self, other: &BitVec) -> bool {
+        PartialEq::eq(&self.as_bit_slice(), other)
+    }
+}
+
+impl PartialEq<BitBytes> for BitVec {
+    fn eq(&self, other: &BitBytes) -> bool {
+        PartialEq::eq(&other.as_bit_slice(), self)
+    }
+}
+
+impl BitBuffer for BitBytes {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+        self.get_slice(range)
+    }
+}
+
+impl BitBufferSplit for BitBytes {
+    type Part<'b> = BitSlice<'b>;
+
+    fn get_part<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+        self.get_slice(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{bitarray, bitvec};
+
+    #[test]
+    fn test_slice() {
+        let bytes = BitBytes::from_vec(vec![0b1011_0010, 0b1100_0000]);
+        assert_eq!(bytes.len(), 16);
+
+        let slice = bytes.slice(4..10).unwrap();
+        assert_eq!(slice.len(), 6);
+        assert_eq!(slice.get_slice(..).unwrap(), bitarray!(0, 0, 1, 0, 1, 1));
+        let slice = slice.slice(1..=2).unwrap();
+        assert_eq!(slice.get_slice(..).unwrap(), bitarray!(0, 1));
+        assert_eq!(slice.slice(2..).unwrap().len(), 0);
+
+        assert!(slice.slice(1..3).is_err());
+        assert!(bytes.get_slice(..17).is_err());
+        assert!(bytes.slice(..=usize::MAX).is_err());
+        assert!(bytes
+            .slice((Bound::Excluded(usize::MAX), Bound::Unbounded))
+            .is_err());
+    }
+
+    #[test]
+    fn test_split() {
+        let mut bytes = BitBytes::from(bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1, 1));
+        let mut front = bytes.split_to(3).unwrap();
+        assert_eq!(front, bitvec!(1, 0, 1));
+        assert_eq!(bytes, bitvec!(1, 0, 0, 1, 0, 1, 1));
+
+        let back = bytes.split_off(4).unwrap();
+        assert_eq!(bytes, bitvec!(1, 0, 0, 1));
+        assert_eq!(back, bitvec!(0, 1, 1));
+
+        assert!(front.split_to(4).is_err());
+        assert!(front.split_off(4).is_err());
+        assert_eq!(front.len(), 3);
+        assert_eq!(front.split_to(3).unwrap(), bitvec!(1, 0, 1));
+        assert_eq!(front.len(), 0);
+
+        bytes.truncate(2);
+        assert_eq!(bytes, bitvec!(1, 0));
+    }
+
+    #[test]
+    fn test_eq_across_offsets() {
+        let vec = bitvec!(1, 1, 0, 1, 0, 1);
+        let shifted = BitBytes::from(vec.get_slice(1..).unwrap());
+        let sliced = BitBytes::from(vec).slice(1..).unwrap();
+        assert_eq!(shifted, sliced);
+        assert_eq!(sliced, bitvec!(1, 0, 1, 0, 1));
+        assert_eq!(sliced.cmp(&BitBytes::from(bitvec!(1, 1))), Ordering::Less);
+    }
+}
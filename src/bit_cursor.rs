@@ -1,19 +1,20 @@
-use std::{
-    io::{Seek, SeekFrom},
-    ops::RangeBounds,
-};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ops::RangeBounds;
 
 use ux::u1;
 
 use crate::{
-    bit_buffer::{BitBuffer, BitBufferMut},
-    bit_read::BitRead,
-    bit_vec::BitVec,
+    bit_buffer::{BitBuffer, BitBufferMut, BitBufferSplit},
+    bit_read::{BitPeek, BitRead},
     bit_write::BitWrite,
-    error::B3Result,
+    error::{B3Error, B3Result},
+    io::{self, Seek, SeekFrom},
     slice::{BitSlice, BitSliceMut},
     util::get_start_end_bit_index_from_range,
 };
+#[cfg(feature = "alloc")]
+use crate::{bit_bytes::BitBytes, bit_vec::BitVec};
 
 #[derive(Debug)]
 pub struct BitCursor<T> {
@@ -33,6 +34,64 @@ impl<T> BitCursor<T> {
     pub fn position(&self) -> usize {
         self.pos
     }
+
+    /// Save the cursor's current position, so it can be restored later via
+    /// [`BitCursor::rewind_to`].
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { pos: self.pos }
+    }
+
+    /// Move the cursor back (or forward) to the position saved in `checkpoint`.
+    ///
+    /// Only the position is restored: any bits written since the checkpoint was taken are _not_
+    /// restored to their previous values.
+    pub fn rewind_to(&mut self, checkpoint: Checkpoint) {
+        self.pos = checkpoint.pos;
+    }
+
+    /// Run `f` on this cursor, restoring the cursor's position if it returns an error.
+    ///
+    /// As with [`BitCursor::rewind_to`], only the position is restored: any bits written by `f`
+    /// before it failed are _not_ restored to their previous values.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts, bitvec};
+    /// use ux::u1;
+    ///
+    /// let mut cursor = BitCursor::new(bitvec!(1, 0, 1));
+    /// let result = cursor.transaction(|c| {
+    ///     c.read_u1()?;
+    ///     c.read_u3()
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(cursor.position(), 0);
+    /// assert_eq!(cursor.read_u1().unwrap(), u1::new(1));
+    /// ```
+    pub fn transaction<R, E, F>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Self) -> Result<R, E>,
+    {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+        if result.is_err() {
+            self.rewind_to(checkpoint);
+        }
+        result
+    }
+}
+
+/// A saved [`BitCursor`] position, created by [`BitCursor::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checkpoint {
+    pos: usize,
+}
+
+impl Checkpoint {
+    /// The cursor position at which this checkpoint was taken.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
 }
 
 impl<T> BitCursor<T>
@@ -59,6 +118,21 @@ where
         })
     }
 
+    /// Move the position forward by `n` bits, returning the previous position.  Returns an error
+    /// (without moving) if fewer than `n` bits remain.
+    fn advance_by(&mut self, n: usize) -> B3Result<usize> {
+        if n > self.bits_remaining() {
+            return Err(B3Error::SliceOutOfRange {
+                len: self.inner.len(),
+                slice_start: self.pos,
+                slice_end: self.pos.saturating_add(n),
+            });
+        }
+        let start = self.pos;
+        self.pos += n;
+        Ok(start)
+    }
+
     pub fn bits_remaining(&self) -> usize {
         self.remaining_slice().len()
     }
@@ -68,6 +142,38 @@ where
     }
 }
 
+impl<T> BitCursor<T>
+where
+    T: BitBufferSplit,
+{
+    /// Get a sub cursor over the next `n` bits, and advance this cursor past them.  Returns an
+    /// error if fewer than `n` bits remain.
+    ///
+    /// The sub cursor is over a [`BitBufferSplit::Part`] of the buffer.  For most buffers that
+    /// borrows this cursor, but splitting a `BitCursor<BitSlice<'a>>` gives a
+    /// `BitCursor<BitSlice<'a>>` which only borrows the underlying bits, so several fields can be
+    /// split off and kept at once.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts, bitvec};
+    /// use ux::{u1, u3};
+    ///
+    /// let mut cursor = BitCursor::new(bitvec!(1, 0, 1, 1));
+    /// let mut field = cursor.split_to(3).unwrap();
+    /// assert_eq!(field.read_u3().unwrap(), u3::new(5));
+    /// assert_eq!(cursor.read_u1().unwrap(), u1::new(1));
+    /// ```
+    pub fn split_to(&mut self, n: usize) -> B3Result<BitCursor<T::Part<'_>>> {
+        let start = self.advance_by(n)?;
+        let part = self.inner.get_part(start..start + n)?;
+        Ok(BitCursor {
+            inner: part,
+            pos: 0,
+        })
+    }
+}
+
 impl<T> BitCursor<T>
 where
     T: BitBufferMut,
@@ -94,6 +200,7 @@ where
     }
 }
 
+#[cfg(feature = "alloc")]
 impl BitCursor<BitVec> {
     pub fn from_vec(vec: Vec<u8>) -> BitCursor<BitVec> {
         BitCursor {
@@ -103,30 +210,51 @@ impl BitCursor<BitVec> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl BitCursor<BitBytes> {
+    /// Like [`BitCursor::split_to`], but the returned cursor shares ownership of the underlying
+    /// buffer instead of borrowing this cursor, so it can outlive it (or be sent to another
+    /// thread).
+    pub fn split_to_owned(&mut self, n: usize) -> B3Result<BitCursor<BitBytes>> {
+        let start = self.advance_by(n)?;
+        let bytes = self.inner.slice(start..start + n)?;
+        Ok(BitCursor {
+            inner: bytes,
+            pos: 0,
+        })
+    }
+}
+
 impl<T> BitWrite for BitCursor<T>
 where
     T: BitBufferMut,
 {
-    fn write(&mut self, buf: &[u1]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
         let n = self.remaining_slice().len().min(buf.len());
         BitWrite::write(&mut self.remaining_slice_mut(), buf)?;
         self.pos += n;
         Ok(n)
     }
 
-    fn write_all(&mut self, buf: &[u1]) -> std::io::Result<()> {
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
         let n = self.remaining_slice().len().min(buf.len());
         BitWrite::write_all(&mut self.remaining_slice_mut(), buf)?;
         self.pos += n;
         Ok(())
     }
+
+    fn write_from_bytes(&mut self, buf: &[u8], bit_offset: usize, nbits: usize) -> io::Result<()> {
+        BitWrite::write_from_bytes(&mut self.remaining_slice_mut(), buf, bit_offset, nbits)?;
+        self.pos += nbits;
+        Ok(())
+    }
 }
 
 impl<T> Seek for BitCursor<T>
 where
     T: BitBuffer,
 {
-    fn seek(&mut self, style: std::io::SeekFrom) -> std::io::Result<u64> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
         let (base_pos, offset) = match style {
             SeekFrom::Start(n) => {
                 self.pos = n as usize;
@@ -140,19 +268,28 @@ where
                 self.pos = n as usize;
                 Ok(self.pos as u64)
             }
-            None => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
                 "invalid seek to a negative or overflowing position",
             )),
         }
     }
 }
 
+impl<T> BitPeek for BitCursor<T>
+where
+    T: BitBuffer,
+{
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        BitRead::read(&mut self.remaining_slice(), buf)
+    }
+}
+
 impl<T> BitRead for BitCursor<T>
 where
     T: BitBuffer,
 {
-    fn read(&mut self, buf: &mut [u1]) -> std::io::Result<usize> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
         // Read buf.len() bits from pos to pos + buf.len() into buf
         let n = self.remaining_slice().len().min(buf.len());
         BitRead::read(&mut self.remaining_slice(), buf)?;
@@ -160,20 +297,32 @@ where
         Ok(n)
     }
 
-    fn read_exact(&mut self, buf: &mut [u1]) -> std::io::Result<()> {
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
         let n = buf.len();
         BitRead::read_exact(&mut self.remaining_slice(), buf)?;
         self.pos += n;
 
         Ok(())
     }
+
+    fn read_into_bytes(
+        &mut self,
+        buf: &mut [u8],
+        bit_offset: usize,
+        nbits: usize,
+    ) -> io::Result<()> {
+        BitRead::read_into_bytes(&mut self.remaining_slice(), buf, bit_offset, nbits)?;
+        self.pos += nbits;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use ux::{u1, u3};
+    use proptest::{collection::vec, prelude::*};
+    use ux::{u1, u3, u6};
 
     use crate::{bit_read_exts::BitReadExts, bitarray, bitvec};
 
@@ -243,4 +392,165 @@ mod tests {
         assert_eq!(cursor.bits_remaining(), 16);
         assert_eq!(cursor.read_u1().unwrap(), u1::new(1));
     }
+
+    #[test]
+    fn test_checkpoint() {
+        let vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1, 1);
+        let mut cursor = BitCursor::new(vec);
+        assert_eq!(cursor.read_u1().unwrap(), u1::new(1));
+
+        let checkpoint = cursor.checkpoint();
+        assert_eq!(checkpoint.position(), 1);
+        assert_eq!(cursor.read_u3().unwrap(), u3::new(3));
+        cursor.rewind_to(checkpoint);
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(cursor.read_u3().unwrap(), u3::new(3));
+
+        // A successful transaction keeps its progress...
+        let value = cursor.transaction(|c| c.read_u3());
+        assert_eq!(value.unwrap(), u3::new(1));
+        assert_eq!(cursor.position(), 7);
+        // ...while a failed one is rolled back
+        let result = cursor.transaction(|c| {
+            c.read_u1()?;
+            c.read_u3()
+        });
+        assert!(result.is_err());
+        assert_eq!(cursor.position(), 7);
+    }
+
+    #[test]
+    fn test_transaction_does_not_restore_writes() {
+        let mut cursor = BitCursor::new(bitvec!(0; 4));
+        let result: io::Result<()> = cursor.transaction(|c| {
+            c.write_all(&bitarray!(1, 1))?;
+            Err(io::Error::new(io::ErrorKind::InvalidData, "failed"))
+        });
+        assert!(result.is_err());
+        assert_eq!(cursor.position(), 0);
+        assert_eq!(cursor.into_inner(), bitvec!(1, 1, 0, 0));
+    }
+
+    #[test]
+    fn test_split_to() {
+        let vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1, 1);
+        let mut cursor = BitCursor::new(vec);
+        cursor.seek(SeekFrom::Current(1)).unwrap();
+
+        let mut field = cursor.split_to(3).unwrap();
+        assert_eq!(field.bits_remaining(), 3);
+        assert_eq!(field.read_u3().unwrap(), u3::new(3));
+        assert!(field.read_u1().is_err());
+        assert_eq!(cursor.position(), 4);
+
+        assert_eq!(cursor.split_to(0).unwrap().bits_remaining(), 0);
+        assert!(cursor.split_to(7).is_err());
+        assert_eq!(cursor.position(), 4);
+        assert_eq!(cursor.split_to(6).unwrap().bits_remaining(), 6);
+        assert_eq!(cursor.bits_remaining(), 0);
+        assert!(cursor.split_to(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_split_to_keeps_slice_lifetime() {
+        let vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1, 1);
+        // Splitting a cursor over a BitSlice doesn't borrow the cursor, so all the fields can be
+        // held at once, even after the cursor is gone
+        let (mut first, mut second) = {
+            let mut cursor = BitCursor::new(vec.get_slice(..).unwrap());
+            let first = cursor.split_to(3).unwrap();
+            let second = cursor.split_to(6).unwrap();
+            (first, second)
+        };
+        assert_eq!(first.read_u3().unwrap(), u3::new(5));
+        assert_eq!(second.read_u6().unwrap(), u6::new(0b10_0101));
+    }
+
+    #[test]
+    fn test_split_to_owned() {
+        let mut cursor = BitCursor::new(BitBytes::from_vec(vec![0b1011_0010, 0b1100_0000]));
+        let mut header = cursor.split_to_owned(4).unwrap();
+        let mut payload = cursor.split_to_owned(6).unwrap();
+        drop(cursor);
+
+        assert_eq!(header.read_u4().unwrap(), ux::u4::new(0b1011));
+        let handle = std::thread::spawn(move || payload.read_u6().unwrap());
+        assert_eq!(handle.join().unwrap(), ux::u6::new(0b00_1011));
+    }
+
+    /// Wraps a reader or writer, hiding its read_into_bytes/write_from_bytes overrides so the
+    /// default implementations are used.
+    struct Unoptimized<T>(T);
+
+    impl<T: BitRead> BitRead for Unoptimized<T> {
+        fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+
+        fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+            self.0.read_exact(buf)
+        }
+    }
+
+    impl<T: BitWrite> BitWrite for Unoptimized<T> {
+        fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+            self.0.write_all(buf)
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bytes_round_trip(
+            data in vec(any::<u8>(), 1..24),
+            start in 0usize..8,
+            bit_offset in 0usize..16,
+            nbits in 0usize..150,
+        ) {
+            let source = BitVec::from_vec(data);
+            let nbits = nbits.min(source.len() - start);
+            let bits: Vec<u1> = source.iter().skip(start).take(nbits).collect();
+            let buf_len = (bit_offset + nbits).div_ceil(8) + 1;
+            let mut expected = BitVec::from_vec(vec![0xA5; buf_len]);
+            for (i, bit) in bits.iter().enumerate() {
+                expected.set(bit_offset + i, *bit);
+            }
+
+            let mut fast = vec![0xA5; buf_len];
+            let mut cursor = BitCursor::new(source.get_slice(..).unwrap());
+            cursor.seek(SeekFrom::Start(start as u64)).unwrap();
+            cursor.read_into_bytes(&mut fast, bit_offset, nbits).unwrap();
+            prop_assert_eq!(cursor.position(), start + nbits);
+            prop_assert_eq!(&BitVec::from_vec(fast.clone()), &expected);
+
+            let mut default = vec![0xA5; buf_len];
+            let mut cursor = Unoptimized(BitCursor::new(source.get_slice(..).unwrap()));
+            cursor.0.seek(SeekFrom::Start(start as u64)).unwrap();
+            cursor.read_into_bytes(&mut default, bit_offset, nbits).unwrap();
+            prop_assert_eq!(cursor.0.position(), start + nbits);
+            prop_assert_eq!(&default, &fast);
+            let too_many = cursor.0.bits_remaining() + 1;
+            let mut big = vec![0; too_many.div_ceil(8)];
+            prop_assert!(cursor.read_into_bytes(&mut big, 0, too_many).is_err());
+            prop_assert!(cursor.0.read_into_bytes(&mut big, 0, too_many).is_err());
+
+            // Writing the bits back from the buffer reproduces them
+            for optimized in [true, false] {
+                let mut cursor = BitCursor::new(bitvec!(0; start + nbits + 3));
+                cursor.seek(SeekFrom::Start(start as u64)).unwrap();
+                if optimized {
+                    cursor.write_from_bytes(&fast, bit_offset, nbits).unwrap();
+                } else {
+                    Unoptimized(&mut cursor).write_from_bytes(&fast, bit_offset, nbits).unwrap();
+                }
+                prop_assert_eq!(cursor.position(), start + nbits);
+                let written = cursor.into_inner();
+                prop_assert_eq!(written.get_slice(start..start + nbits).unwrap(), &bits[..]);
+                prop_assert_eq!(written.count_ones(), bits.iter().filter(|b| **b == u1::new(1)).count());
+            }
+        }
+    }
 }
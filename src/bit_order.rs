@@ -0,0 +1,266 @@
+//! Adaptors for bytes which are packed LSB first.
+//!
+//! Readers and writers over byte buffers (such as [`BitCursor`](crate::bit_cursor::BitCursor))
+//! treat the most significant bit of each byte as the first bit.  Formats such as DEFLATE pack
+//! their bits the other way round, starting from the least significant bit of each byte.  These
+//! adaptors sit between such bytes and the code which reads or writes the bit stream, so that
+//! the stream's bits are seen in the order they were packed.
+//!
+//! # Example
+//! ```
+//! use b3::{
+//!     bit_cursor::BitCursor, bit_order::LsbFirstReader, bit_read_exts::BitReadExts,
+//!     bit_vec::BitVec,
+//! };
+//! use ux::{u1, u3};
+//!
+//! let mut reader = LsbFirstReader::new(BitCursor::new(BitVec::from_vec(vec![0b0000_0011])));
+//! assert_eq!(reader.read_u1().unwrap(), u1::new(1));
+//! assert_eq!(reader.read_u3().unwrap(), u3::new(0b100));
+//! ```
+
+use ux::u1;
+
+use crate::{
+    bit_read::{BitPeek, BitRead},
+    bit_write::BitWrite,
+    io,
+};
+
+/// A reader which reads whole bytes from an inner reader, and returns the bits of each byte
+/// starting from the least significant.  Reads fail if the inner reader ends part way through a
+/// byte.
+#[derive(Debug)]
+pub struct LsbFirstReader<R> {
+    inner: R,
+    byte: [u1; 8],
+    pos: usize,
+}
+
+impl<R> LsbFirstReader<R> {
+    pub fn new(inner: R) -> Self {
+        LsbFirstReader {
+            inner,
+            byte: [u1::new(0); 8],
+            pos: 8,
+        }
+    }
+
+    /// Discard the rest of the current byte, if part of it has been read.
+    pub fn align_to_byte(&mut self) {
+        self.pos = 8;
+    }
+
+    /// Whether the next bit is the first bit of a byte.
+    pub fn is_byte_aligned(&self) -> bool {
+        self.pos == 8
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Return the inner reader.  The rest of the current byte, if part of it has been read, is
+    /// lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+fn partial_byte() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "stream ended part way through a byte",
+    )
+}
+
+impl<R: BitRead> LsbFirstReader<R> {
+    /// Read the next byte, returning false at the end of the inner reader
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut filled = 0;
+        while filled < 8 {
+            match self.inner.read(&mut self.byte[filled..])? {
+                0 if filled == 0 => return Ok(false),
+                0 => return Err(partial_byte()),
+                n => filled += n,
+            }
+        }
+        self.byte.reverse();
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+impl<R: BitRead> BitRead for LsbFirstReader<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        for (i, dst) in buf.iter_mut().enumerate() {
+            if self.pos == 8 && !self.fill()? {
+                return Ok(i);
+            }
+            *dst = self.byte[self.pos];
+            self.pos += 1;
+        }
+        Ok(buf.len())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        if self.read(buf)? < buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BitPeek> BitPeek for LsbFirstReader<R> {
+    /// Peeks at most 64 bits past the current byte.
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let buffered = (8 - self.pos).min(buf.len());
+        buf[..buffered].copy_from_slice(&self.byte[self.pos..self.pos + buffered]);
+        let wanted = (buf.len() - buffered).div_ceil(8).min(8) * 8;
+        let mut upcoming = [u1::new(0); 64];
+        let peeked = self.inner.peek(&mut upcoming[..wanted])? / 8 * 8;
+        let mut n = buffered;
+        for byte in upcoming[..peeked].chunks_mut(8) {
+            byte.reverse();
+            let count = byte.len().min(buf.len() - n);
+            buf[n..n + count].copy_from_slice(&byte[..count]);
+            n += count;
+        }
+        Ok(n)
+    }
+}
+
+/// A writer which packs the bits written to it into bytes starting from the least significant
+/// bit, and writes the bytes to an inner writer.
+///
+/// Bits are written to the inner writer a whole byte at a time, so use
+/// [`LsbFirstWriter::finish`] to pad and write the last byte.
+#[derive(Debug)]
+pub struct LsbFirstWriter<W> {
+    inner: W,
+    byte: [u1; 8],
+    len: usize,
+}
+
+impl<W> LsbFirstWriter<W> {
+    pub fn new(inner: W) -> Self {
+        LsbFirstWriter {
+            inner,
+            byte: [u1::new(0); 8],
+            len: 0,
+        }
+    }
+
+    /// The number of bits written which don't yet make up a whole byte
+    pub fn pending_bits(&self) -> usize {
+        self.len
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+}
+
+impl<W: BitWrite> LsbFirstWriter<W> {
+    fn flush_byte(&mut self) -> io::Result<()> {
+        let mut byte = self.byte;
+        byte.reverse();
+        self.inner.write_all(&byte)?;
+        self.byte = [u1::new(0); 8];
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Pad the current byte (if part of it has been written) with 0s and write it.
+    pub fn align_to_byte(&mut self) -> io::Result<()> {
+        if self.len != 0 {
+            self.flush_byte()?;
+        }
+        Ok(())
+    }
+
+    /// Pad and write the last byte, and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.align_to_byte()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: BitWrite> BitWrite for LsbFirstWriter<W> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        for &bit in buf {
+            self.byte[self.len] = bit;
+            self.len += 1;
+            if self.len == 8 {
+                self.flush_byte()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bit_cursor::BitCursor, bit_vec::BitVec, bitarray};
+
+    #[test]
+    fn test_round_trip() {
+        let bits = bitarray!(1, 1, 0, 1, 0, 0, 1, 0, 0, 0, 1);
+        let mut writer = LsbFirstWriter::new(BitVec::new());
+        writer.write_all(&bits).unwrap();
+        assert_eq!(writer.pending_bits(), 3);
+        let bytes = writer.finish().unwrap().to_bytes();
+        assert_eq!(bytes, vec![0x4B, 0x04]);
+
+        let mut reader = LsbFirstReader::new(BitCursor::new(BitVec::from_vec(bytes)));
+        let mut buf = [u1::new(0); 16];
+        assert_eq!(reader.read(&mut buf).unwrap(), 16);
+        assert_eq!(buf[..11], bits);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut reader = LsbFirstReader::new(BitCursor::new(BitVec::from_vec(vec![0x4B, 0x04])));
+        let mut buf = [u1::new(0); 3];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, bitarray!(1, 1, 0));
+
+        let mut peeked = [u1::new(0); 10];
+        assert_eq!(reader.peek(&mut peeked).unwrap(), 10);
+        assert_eq!(peeked, bitarray!(1, 0, 0, 1, 0, 0, 0, 1, 0, 0));
+        let mut peeked = [u1::new(0); 16];
+        assert_eq!(reader.peek(&mut peeked).unwrap(), 13);
+
+        reader.align_to_byte();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, bitarray!(0, 0, 1));
+    }
+
+    #[test]
+    fn test_partial_byte() {
+        let mut reader = LsbFirstReader::new(BitCursor::new(BitVec::from_bytes(&[0xFF; 2], 12)));
+        let mut buf = [u1::new(0); 12];
+        assert_eq!(
+            reader.read_exact(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
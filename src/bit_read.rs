@@ -1,9 +1,322 @@
 use ux::u1;
 
+use crate::{
+    io,
+    util::{assert_bits_fit, set_bit},
+};
+
 pub trait BitRead {
     /// Pull some bytes from this source into the specified buffer, returning how many bytes were read.
-    fn read(&mut self, buf: &mut [u1]) -> std::io::Result<usize>;
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize>;
 
     /// Read the exact number of bytes required to fill buf.
-    fn read_exact(&mut self, buf: &mut [u1]) -> std::io::Result<()>;
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()>;
+
+    /// Read exactly `nbits` bits into `buf`, packed MSB-first starting at bit `bit_offset` (where
+    /// bit 0 is the most significant bit of `buf[0]`).  The other bits in `buf` are left
+    /// unchanged.  Panics if `buf` is too small to hold `nbits` bits at `bit_offset`.
+    ///
+    /// As with [`BitRead::read_exact`], if this returns an error then an unspecified number of
+    /// bits may have been read.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_read::BitRead, bitvec};
+    ///
+    /// let mut cursor = BitCursor::new(bitvec!(1, 0, 1, 1, 0, 1));
+    /// let mut buf = [0u8; 1];
+    /// cursor.read_into_bytes(&mut buf, 2, 6).unwrap();
+    /// assert_eq!(buf, [0b0010_1101]);
+    /// ```
+    fn read_into_bytes(
+        &mut self,
+        buf: &mut [u8],
+        bit_offset: usize,
+        nbits: usize,
+    ) -> io::Result<()> {
+        assert_bits_fit(buf.len(), bit_offset, nbits);
+        let mut bits = [u1::new(0); 64];
+        let mut done = 0;
+        while done < nbits {
+            let n = (nbits - done).min(bits.len());
+            self.read_exact(&mut bits[..n])?;
+            for (i, bit) in bits[..n].iter().enumerate() {
+                let pos = bit_offset + done + i;
+                set_bit(&mut buf[pos / 8], pos % 8, *bit);
+            }
+            done += n;
+        }
+        Ok(())
+    }
+
+    /// Create an adaptor which reads at most `limit` bits from this reader.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_read::BitRead, bit_read_exts::BitReadExts, bitvec};
+    /// use ux::u2;
+    ///
+    /// let mut take = BitCursor::new(bitvec!(1, 0, 1, 1)).take(2);
+    /// assert_eq!(take.read_u2().unwrap(), u2::new(2));
+    /// assert!(take.read_u1().is_err());
+    /// ```
+    fn take(self, limit: usize) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take { inner: self, limit }
+    }
+
+    /// Create an adaptor which reads all of the bits from this reader, followed by all of the
+    /// bits from `next`.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_read::BitRead, bit_read_exts::BitReadExts, bitvec};
+    /// use ux::u4;
+    ///
+    /// let first = BitCursor::new(bitvec!(1, 0));
+    /// let second = BitCursor::new(bitvec!(1, 1));
+    /// let mut chain = first.chain(second);
+    /// assert_eq!(chain.read_u4().unwrap(), u4::new(0b1011));
+    /// ```
+    fn chain<R: BitRead>(self, next: R) -> Chain<Self, R>
+    where
+        Self: Sized,
+    {
+        Chain {
+            first: self,
+            second: next,
+            done_first: false,
+        }
+    }
+
+    /// Borrow this reader, so an adaptor can be used on it without consuming it.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+impl<R: BitRead + ?Sized> BitRead for &mut R {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        (**self).read_exact(buf)
+    }
+
+    fn read_into_bytes(
+        &mut self,
+        buf: &mut [u8],
+        bit_offset: usize,
+        nbits: usize,
+    ) -> io::Result<()> {
+        (**self).read_into_bytes(buf, bit_offset, nbits)
+    }
+}
+
+/// A reader which can look at upcoming bits without consuming them, e.g. to decode variable
+/// length codes with a table lookup.
+pub trait BitPeek: BitRead {
+    /// Copy upcoming bits into `buf` without consuming them, returning how many bits were copied.
+    /// As with [`BitRead::read`], this may be fewer than `buf.len()` even if more bits remain.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_read::BitPeek, bit_read_exts::BitReadExts, bitvec};
+    /// use ux::{u1, u3};
+    ///
+    /// let mut cursor = BitCursor::new(bitvec!(1, 0, 1));
+    /// let mut buf = [u1::new(0); 8];
+    /// assert_eq!(cursor.peek(&mut buf).unwrap(), 3);
+    /// assert_eq!(cursor.read_u3().unwrap(), u3::new(0b101));
+    /// ```
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize>;
+}
+
+impl<R: BitPeek + ?Sized> BitPeek for &mut R {
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        (**self).peek(buf)
+    }
+}
+
+/// A reader which reads at most a limited number of bits from an inner reader, created by
+/// [`BitRead::take`].
+#[derive(Debug)]
+pub struct Take<R> {
+    inner: R,
+    limit: usize,
+}
+
+impl<R> Take<R> {
+    /// The number of bits which can still be read before this reader returns EOF.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Set the number of bits which can be read before this reader returns EOF.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead> BitRead for Take<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let n = buf.len().min(self.limit);
+        let n = self.inner.read(&mut buf[..n])?;
+        self.limit -= n;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        if buf.len() > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        self.inner.read_exact(buf)?;
+        self.limit -= buf.len();
+        Ok(())
+    }
+
+    fn read_into_bytes(
+        &mut self,
+        buf: &mut [u8],
+        bit_offset: usize,
+        nbits: usize,
+    ) -> io::Result<()> {
+        if nbits > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        self.inner.read_into_bytes(buf, bit_offset, nbits)?;
+        self.limit -= nbits;
+        Ok(())
+    }
+}
+
+impl<R: BitPeek> BitPeek for Take<R> {
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let n = buf.len().min(self.limit);
+        self.inner.peek(&mut buf[..n])
+    }
+}
+
+/// A reader which reads all of the bits from one reader and then another, created by
+/// [`BitRead::chain`].
+#[derive(Debug)]
+pub struct Chain<R1, R2> {
+    first: R1,
+    second: R2,
+    done_first: bool,
+}
+
+impl<R1, R2> Chain<R1, R2> {
+    pub fn get_ref(&self) -> (&R1, &R2) {
+        (&self.first, &self.second)
+    }
+
+    pub fn get_mut(&mut self) -> (&mut R1, &mut R2) {
+        (&mut self.first, &mut self.second)
+    }
+
+    pub fn into_inner(self) -> (R1, R2) {
+        (self.first, self.second)
+    }
+}
+
+impl<R1: BitRead, R2: BitRead> BitRead for Chain<R1, R2> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        if !self.done_first {
+            match self.first.read(buf)? {
+                0 if !buf.is_empty() => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        // Take as much as possible from the first reader, and the rest from the second
+        let mut filled = 0;
+        while !self.done_first && filled < buf.len() {
+            match self.first.read(&mut buf[filled..])? {
+                0 => self.done_first = true,
+                n => filled += n,
+            }
+        }
+        self.second.read_exact(&mut buf[filled..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ux::{u1, u3};
+
+    use super::*;
+    use crate::{bit_cursor::BitCursor, bit_read_exts::BitReadExts, bitarray, bitvec};
+
+    #[test]
+    fn test_take() {
+        let mut cursor = BitCursor::new(bitvec!(1, 0, 1, 1, 0, 0, 1));
+        let mut take = cursor.by_ref().take(5);
+        let mut buf = [u1::new(0); 4];
+        assert_eq!(take.read(&mut buf).unwrap(), 4);
+        assert_eq!(buf, bitarray!(1, 0, 1, 1));
+        assert_eq!(take.limit(), 1);
+        assert!(take.read_exact(&mut buf[..2]).is_err());
+        assert_eq!(take.read(&mut buf).unwrap(), 1);
+        assert_eq!(take.read(&mut buf).unwrap(), 0);
+
+        // The underlying reader was only advanced by the bits read through the adaptor
+        assert_eq!(cursor.position(), 5);
+        let mut take = cursor.by_ref().take(1);
+        assert_eq!(take.peek(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], u1::new(0));
+        assert_eq!(cursor.read_u1().unwrap(), u1::new(0));
+    }
+
+    #[test]
+    fn test_chain() {
+        let first = BitCursor::new(bitvec!(1, 0));
+        let second = BitCursor::new(bitvec!(1, 1, 0, 0, 1));
+        let mut chain = first.chain(second);
+        assert_eq!(chain.read_u3().unwrap(), u3::new(0b101));
+
+        let mut buf = [u1::new(0); 8];
+        assert_eq!(chain.read(&mut buf).unwrap(), 4);
+        assert_eq!(buf[..4], bitarray!(1, 0, 0, 1));
+        assert_eq!(chain.read(&mut buf).unwrap(), 0);
+        assert!(chain.read_u1().is_err());
+    }
+
+    #[test]
+    fn test_chain_of_takes() {
+        let mut cursor = BitCursor::new(bitvec!(1, 1, 0, 0, 1, 0, 1, 0));
+        let first = cursor.by_ref().take(2);
+        let mut chain = first.chain(BitCursor::new(bitvec!(0, 1)));
+        let mut buf = [u1::new(0); 4];
+        chain.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, bitarray!(1, 1, 0, 1));
+        assert_eq!(cursor.position(), 2);
+    }
 }
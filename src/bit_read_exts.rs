@@ -1,11 +1,25 @@
+use paste::paste;
 use ux::*;
 
-use crate::{bit_read::BitRead, bit_traits::BitTraits, byte_order::ByteOrder};
-
-fn bit_read_exts_helper<T: BitTraits, const N: usize, U: BitRead>(buf: &mut U) -> std::io::Result<T>
-where
-    U: ?Sized,
-{
+#[cfg(feature = "alloc")]
+use crate::text::{self, Charset, Framing};
+use crate::{
+    bit_read::BitRead,
+    bit_traits::BitTraits,
+    byte_order::{BigEndian, ByteOrder, Endianness, LittleEndian, MiddleEndian, WordSwapped},
+    encoding::{
+        check_width, decode_bcd, decode_gray, decode_ones_complement, decode_sign_magnitude,
+    },
+    error::B3Result,
+    fixed::{Fixed, QFormat},
+    float::f16_bits_to_f32,
+    io,
+    util::read_bits_msb_first,
+};
+
+fn bit_read_exts_helper<T: BitTraits, const N: usize, U: BitRead + ?Sized>(
+    buf: &mut U,
+) -> io::Result<T> {
     let mut read_buf = [u1::default(); N];
     buf.read_exact(&mut read_buf)?;
     let mut val = T::default();
@@ -16,194 +30,541 @@ where
     Ok(val)
 }
 
+/// Define `read_<name>_with` methods, which take the byte order as an [`Endianness`] at runtime
+/// rather than as a type parameter.
+macro_rules! impl_read_with {
+    ($($name:ident -> $type:ty),* $(,)?) => {
+        paste! {
+            $(
+                #[doc = concat!("Like `read_", stringify!($name), "`, with the byte order chosen at runtime")]
+                fn [<read_ $name _with>](&mut self, endianness: Endianness) -> io::Result<$type> {
+                    match endianness {
+                        Endianness::Big => self.[<read_ $name>]::<BigEndian>(),
+                        Endianness::Little => self.[<read_ $name>]::<LittleEndian>(),
+                        Endianness::WordSwapped => self.[<read_ $name>]::<WordSwapped>(),
+                        Endianness::MiddleEndian => self.[<read_ $name>]::<MiddleEndian>(),
+                    }
+                }
+            )*
+        }
+    };
+}
+
 /// A trait which extends BitRead to add explicit read methods for all uX types.
 pub trait BitReadExts: BitRead {
-    fn read_bool(&mut self) -> std::io::Result<bool> {
+    fn read_bool(&mut self) -> io::Result<bool> {
         self.read_u1().map(|v| v.into())
     }
 
-    fn read_u1(&mut self) -> std::io::Result<u1> {
+    fn read_u1(&mut self) -> io::Result<u1> {
         bit_read_exts_helper::<u1, 1, Self>(self)
     }
 
-    fn read_u2(&mut self) -> std::io::Result<u2> {
+    fn read_u2(&mut self) -> io::Result<u2> {
         bit_read_exts_helper::<u2, 2, Self>(self)
     }
 
-    fn read_u3(&mut self) -> std::io::Result<u3> {
+    fn read_u3(&mut self) -> io::Result<u3> {
         bit_read_exts_helper::<u3, 3, Self>(self)
     }
 
-    fn read_u4(&mut self) -> std::io::Result<u4> {
+    fn read_u4(&mut self) -> io::Result<u4> {
         bit_read_exts_helper::<u4, 4, Self>(self)
     }
 
-    fn read_u5(&mut self) -> std::io::Result<u5> {
+    fn read_u5(&mut self) -> io::Result<u5> {
         bit_read_exts_helper::<u5, 5, Self>(self)
     }
 
-    fn read_u6(&mut self) -> std::io::Result<u6> {
+    fn read_u6(&mut self) -> io::Result<u6> {
         bit_read_exts_helper::<u6, 6, Self>(self)
     }
 
-    fn read_u7(&mut self) -> std::io::Result<u7> {
+    fn read_u7(&mut self) -> io::Result<u7> {
         bit_read_exts_helper::<u7, 7, Self>(self)
     }
 
-    fn read_u8(&mut self) -> std::io::Result<u8> {
+    fn read_u8(&mut self) -> io::Result<u8> {
         bit_read_exts_helper::<u8, 8, Self>(self)
     }
 
-    fn read_u9<T: ByteOrder>(&mut self) -> std::io::Result<u9> {
+    fn read_u9<T: ByteOrder>(&mut self) -> io::Result<u9> {
         let mut buf = [u1::new(0); 9];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u9(&buf))
     }
 
-    fn read_u10<T: ByteOrder>(&mut self) -> std::io::Result<u10> {
+    fn read_u10<T: ByteOrder>(&mut self) -> io::Result<u10> {
         let mut buf = [u1::new(0); 10];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u10(&buf))
     }
 
-    fn read_u11<T: ByteOrder>(&mut self) -> std::io::Result<u11> {
+    fn read_u11<T: ByteOrder>(&mut self) -> io::Result<u11> {
         let mut buf = [u1::new(0); 11];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u11(&buf))
     }
 
-    fn read_u12<T: ByteOrder>(&mut self) -> std::io::Result<u12> {
+    fn read_u12<T: ByteOrder>(&mut self) -> io::Result<u12> {
         let mut buf = [u1::new(0); 12];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u12(&buf))
     }
 
-    fn read_u13<T: ByteOrder>(&mut self) -> std::io::Result<u13> {
+    fn read_u13<T: ByteOrder>(&mut self) -> io::Result<u13> {
         let mut buf = [u1::new(0); 13];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u13(&buf))
     }
 
-    fn read_u14<T: ByteOrder>(&mut self) -> std::io::Result<u14> {
+    fn read_u14<T: ByteOrder>(&mut self) -> io::Result<u14> {
         let mut buf = [u1::new(0); 14];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u14(&buf))
     }
 
-    fn read_u15<T: ByteOrder>(&mut self) -> std::io::Result<u15> {
+    fn read_u15<T: ByteOrder>(&mut self) -> io::Result<u15> {
         let mut buf = [u1::new(0); 15];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u15(&buf))
     }
 
-    fn read_u16<T: ByteOrder>(&mut self) -> std::io::Result<u16> {
+    fn read_u16<T: ByteOrder>(&mut self) -> io::Result<u16> {
         let mut buf = [u1::new(0); 16];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u16(&buf))
     }
 
-    fn read_u17<T: ByteOrder>(&mut self) -> std::io::Result<u17> {
+    fn read_u17<T: ByteOrder>(&mut self) -> io::Result<u17> {
         let mut buf = [u1::new(0); 17];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u17(&buf))
     }
 
-    fn read_u18<T: ByteOrder>(&mut self) -> std::io::Result<u18> {
+    fn read_u18<T: ByteOrder>(&mut self) -> io::Result<u18> {
         let mut buf = [u1::new(0); 18];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u18(&buf))
     }
 
-    fn read_u19<T: ByteOrder>(&mut self) -> std::io::Result<u19> {
+    fn read_u19<T: ByteOrder>(&mut self) -> io::Result<u19> {
         let mut buf = [u1::new(0); 19];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u19(&buf))
     }
 
-    fn read_u20<T: ByteOrder>(&mut self) -> std::io::Result<u20> {
+    fn read_u20<T: ByteOrder>(&mut self) -> io::Result<u20> {
         let mut buf = [u1::new(0); 20];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u20(&buf))
     }
 
-    fn read_u21<T: ByteOrder>(&mut self) -> std::io::Result<u21> {
+    fn read_u21<T: ByteOrder>(&mut self) -> io::Result<u21> {
         let mut buf = [u1::new(0); 21];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u21(&buf))
     }
 
-    fn read_u22<T: ByteOrder>(&mut self) -> std::io::Result<u22> {
+    fn read_u22<T: ByteOrder>(&mut self) -> io::Result<u22> {
         let mut buf = [u1::new(0); 22];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u22(&buf))
     }
 
-    fn read_u23<T: ByteOrder>(&mut self) -> std::io::Result<u23> {
+    fn read_u23<T: ByteOrder>(&mut self) -> io::Result<u23> {
         let mut buf = [u1::new(0); 23];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u23(&buf))
     }
 
-    fn read_u24<T: ByteOrder>(&mut self) -> std::io::Result<u24> {
+    fn read_u24<T: ByteOrder>(&mut self) -> io::Result<u24> {
         let mut buf = [u1::new(0); 24];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u24(&buf))
     }
 
-    fn read_u25<T: ByteOrder>(&mut self) -> std::io::Result<u25> {
+    fn read_u25<T: ByteOrder>(&mut self) -> io::Result<u25> {
         let mut buf = [u1::new(0); 25];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u25(&buf))
     }
 
-    fn read_u26<T: ByteOrder>(&mut self) -> std::io::Result<u26> {
+    fn read_u26<T: ByteOrder>(&mut self) -> io::Result<u26> {
         let mut buf = [u1::new(0); 26];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u26(&buf))
     }
 
-    fn read_u27<T: ByteOrder>(&mut self) -> std::io::Result<u27> {
+    fn read_u27<T: ByteOrder>(&mut self) -> io::Result<u27> {
         let mut buf = [u1::new(0); 27];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u27(&buf))
     }
 
-    fn read_u28<T: ByteOrder>(&mut self) -> std::io::Result<u28> {
+    fn read_u28<T: ByteOrder>(&mut self) -> io::Result<u28> {
         let mut buf = [u1::new(0); 28];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u28(&buf))
     }
 
-    fn read_u29<T: ByteOrder>(&mut self) -> std::io::Result<u29> {
+    fn read_u29<T: ByteOrder>(&mut self) -> io::Result<u29> {
         let mut buf = [u1::new(0); 29];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u29(&buf))
     }
 
-    fn read_u30<T: ByteOrder>(&mut self) -> std::io::Result<u30> {
+    fn read_u30<T: ByteOrder>(&mut self) -> io::Result<u30> {
         let mut buf = [u1::new(0); 30];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u30(&buf))
     }
 
-    fn read_u31<T: ByteOrder>(&mut self) -> std::io::Result<u31> {
+    fn read_u31<T: ByteOrder>(&mut self) -> io::Result<u31> {
         let mut buf = [u1::new(0); 31];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u31(&buf))
     }
 
-    fn read_u32<T: ByteOrder>(&mut self) -> std::io::Result<u32> {
+    fn read_u32<T: ByteOrder>(&mut self) -> io::Result<u32> {
         let mut buf = [u1::new(0); 32];
         self.read_exact(&mut buf)?;
         Ok(<T>::read_u32(&buf))
     }
+
+    fn read_u33<T: ByteOrder>(&mut self) -> io::Result<u33> {
+        let mut buf = [u1::new(0); 33];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u33(&buf))
+    }
+
+    fn read_u34<T: ByteOrder>(&mut self) -> io::Result<u34> {
+        let mut buf = [u1::new(0); 34];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u34(&buf))
+    }
+
+    fn read_u35<T: ByteOrder>(&mut self) -> io::Result<u35> {
+        let mut buf = [u1::new(0); 35];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u35(&buf))
+    }
+
+    fn read_u36<T: ByteOrder>(&mut self) -> io::Result<u36> {
+        let mut buf = [u1::new(0); 36];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u36(&buf))
+    }
+
+    fn read_u37<T: ByteOrder>(&mut self) -> io::Result<u37> {
+        let mut buf = [u1::new(0); 37];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u37(&buf))
+    }
+
+    fn read_u38<T: ByteOrder>(&mut self) -> io::Result<u38> {
+        let mut buf = [u1::new(0); 38];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u38(&buf))
+    }
+
+    fn read_u39<T: ByteOrder>(&mut self) -> io::Result<u39> {
+        let mut buf = [u1::new(0); 39];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u39(&buf))
+    }
+
+    fn read_u40<T: ByteOrder>(&mut self) -> io::Result<u40> {
+        let mut buf = [u1::new(0); 40];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u40(&buf))
+    }
+
+    fn read_u41<T: ByteOrder>(&mut self) -> io::Result<u41> {
+        let mut buf = [u1::new(0); 41];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u41(&buf))
+    }
+
+    fn read_u42<T: ByteOrder>(&mut self) -> io::Result<u42> {
+        let mut buf = [u1::new(0); 42];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u42(&buf))
+    }
+
+    fn read_u43<T: ByteOrder>(&mut self) -> io::Result<u43> {
+        let mut buf = [u1::new(0); 43];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u43(&buf))
+    }
+
+    fn read_u44<T: ByteOrder>(&mut self) -> io::Result<u44> {
+        let mut buf = [u1::new(0); 44];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u44(&buf))
+    }
+
+    fn read_u45<T: ByteOrder>(&mut self) -> io::Result<u45> {
+        let mut buf = [u1::new(0); 45];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u45(&buf))
+    }
+
+    fn read_u46<T: ByteOrder>(&mut self) -> io::Result<u46> {
+        let mut buf = [u1::new(0); 46];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u46(&buf))
+    }
+
+    fn read_u47<T: ByteOrder>(&mut self) -> io::Result<u47> {
+        let mut buf = [u1::new(0); 47];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u47(&buf))
+    }
+
+    fn read_u48<T: ByteOrder>(&mut self) -> io::Result<u48> {
+        let mut buf = [u1::new(0); 48];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u48(&buf))
+    }
+
+    fn read_u49<T: ByteOrder>(&mut self) -> io::Result<u49> {
+        let mut buf = [u1::new(0); 49];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u49(&buf))
+    }
+
+    fn read_u50<T: ByteOrder>(&mut self) -> io::Result<u50> {
+        let mut buf = [u1::new(0); 50];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u50(&buf))
+    }
+
+    fn read_u51<T: ByteOrder>(&mut self) -> io::Result<u51> {
+        let mut buf = [u1::new(0); 51];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u51(&buf))
+    }
+
+    fn read_u52<T: ByteOrder>(&mut self) -> io::Result<u52> {
+        let mut buf = [u1::new(0); 52];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u52(&buf))
+    }
+
+    fn read_u53<T: ByteOrder>(&mut self) -> io::Result<u53> {
+        let mut buf = [u1::new(0); 53];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u53(&buf))
+    }
+
+    fn read_u54<T: ByteOrder>(&mut self) -> io::Result<u54> {
+        let mut buf = [u1::new(0); 54];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u54(&buf))
+    }
+
+    fn read_u55<T: ByteOrder>(&mut self) -> io::Result<u55> {
+        let mut buf = [u1::new(0); 55];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u55(&buf))
+    }
+
+    fn read_u56<T: ByteOrder>(&mut self) -> io::Result<u56> {
+        let mut buf = [u1::new(0); 56];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u56(&buf))
+    }
+
+    fn read_u57<T: ByteOrder>(&mut self) -> io::Result<u57> {
+        let mut buf = [u1::new(0); 57];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u57(&buf))
+    }
+
+    fn read_u58<T: ByteOrder>(&mut self) -> io::Result<u58> {
+        let mut buf = [u1::new(0); 58];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u58(&buf))
+    }
+
+    fn read_u59<T: ByteOrder>(&mut self) -> io::Result<u59> {
+        let mut buf = [u1::new(0); 59];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u59(&buf))
+    }
+
+    fn read_u60<T: ByteOrder>(&mut self) -> io::Result<u60> {
+        let mut buf = [u1::new(0); 60];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u60(&buf))
+    }
+
+    fn read_u61<T: ByteOrder>(&mut self) -> io::Result<u61> {
+        let mut buf = [u1::new(0); 61];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u61(&buf))
+    }
+
+    fn read_u62<T: ByteOrder>(&mut self) -> io::Result<u62> {
+        let mut buf = [u1::new(0); 62];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u62(&buf))
+    }
+
+    fn read_u63<T: ByteOrder>(&mut self) -> io::Result<u63> {
+        let mut buf = [u1::new(0); 63];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u63(&buf))
+    }
+
+    fn read_u64<T: ByteOrder>(&mut self) -> io::Result<u64> {
+        let mut buf = [u1::new(0); 64];
+        self.read_exact(&mut buf)?;
+        Ok(<T>::read_u64(&buf))
+    }
+
+    /// Read an IEEE 754 half precision float.  Rust has no stable f16 type, so it's returned as
+    /// the (exactly equal) f32.
+    fn read_f16<T: ByteOrder>(&mut self) -> io::Result<f32> {
+        self.read_u16::<T>().map(f16_bits_to_f32)
+    }
+
+    fn read_f32<T: ByteOrder>(&mut self) -> io::Result<f32> {
+        self.read_u32::<T>().map(f32::from_bits)
+    }
+
+    fn read_f64<T: ByteOrder>(&mut self) -> io::Result<f64> {
+        self.read_u64::<T>().map(f64::from_bits)
+    }
+
+    /// Read a fixed-point value in the given format.  Fixed-point values can be any width, so
+    /// their bits are always read MSB first.
+    fn read_fixed(&mut self, format: QFormat) -> io::Result<Fixed> {
+        let bits = read_bits_msb_first(self, format.width())?;
+        Ok(Fixed::from_bits(format, bits))
+    }
+
+    impl_read_with!(
+        u9 -> u9,
+        u10 -> u10,
+        u11 -> u11,
+        u12 -> u12,
+        u13 -> u13,
+        u14 -> u14,
+        u15 -> u15,
+        u16 -> u16,
+        u17 -> u17,
+        u18 -> u18,
+        u19 -> u19,
+        u20 -> u20,
+        u21 -> u21,
+        u22 -> u22,
+        u23 -> u23,
+        u24 -> u24,
+        u25 -> u25,
+        u26 -> u26,
+        u27 -> u27,
+        u28 -> u28,
+        u29 -> u29,
+        u30 -> u30,
+        u31 -> u31,
+        u32 -> u32,
+        u33 -> u33,
+        u34 -> u34,
+        u35 -> u35,
+        u36 -> u36,
+        u37 -> u37,
+        u38 -> u38,
+        u39 -> u39,
+        u40 -> u40,
+        u41 -> u41,
+        u42 -> u42,
+        u43 -> u43,
+        u44 -> u44,
+        u45 -> u45,
+        u46 -> u46,
+        u47 -> u47,
+        u48 -> u48,
+        u49 -> u49,
+        u50 -> u50,
+        u51 -> u51,
+        u52 -> u52,
+        u53 -> u53,
+        u54 -> u54,
+        u55 -> u55,
+        u56 -> u56,
+        u57 -> u57,
+        u58 -> u58,
+        u59 -> u59,
+        u60 -> u60,
+        u61 -> u61,
+        u62 -> u62,
+        u63 -> u63,
+        u64 -> u64,
+        f16 -> f32,
+        f32 -> f32,
+        f64 -> f64,
+    );
+
+    /// Read a `width` bit packed BCD value.  Digits greater than 9 are reported as
+    /// [`B3Error::InvalidCode`](crate::error::B3Error::InvalidCode).  See
+    /// [`decode_bcd`](crate::encoding::decode_bcd).
+    ///
+    /// This and the other encoded field methods fail with
+    /// [`B3Error::InvalidWidth`](crate::error::B3Error::InvalidWidth), without reading anything,
+    /// if `width` is 0 or greater than 64.
+    fn read_bcd(&mut self, width: usize) -> B3Result<u64> {
+        check_width(width)?;
+        decode_bcd(read_bits_msb_first(self, width)?, width)
+    }
+
+    /// Read a `width` bit Gray coded value.
+    fn read_gray(&mut self, width: usize) -> B3Result<u64> {
+        check_width(width)?;
+        Ok(decode_gray(read_bits_msb_first(self, width)?, width))
+    }
+
+    /// Read a `width` bit sign-magnitude value.
+    fn read_sign_magnitude(&mut self, width: usize) -> B3Result<i64> {
+        check_width(width)?;
+        Ok(decode_sign_magnitude(
+            read_bits_msb_first(self, width)?,
+            width,
+        ))
+    }
+
+    /// Read a `width` bit one's complement value.
+    fn read_ones_complement(&mut self, width: usize) -> B3Result<i64> {
+        check_width(width)?;
+        Ok(decode_ones_complement(
+            read_bits_msb_first(self, width)?,
+            width,
+        ))
+    }
+
+    /// Read a string of codes from the given charset, framed as described by `framing`.
+    #[cfg(feature = "alloc")]
+    fn read_string<C: Charset + ?Sized>(
+        &mut self,
+        charset: &C,
+        framing: Framing,
+    ) -> B3Result<alloc::string::String> {
+        text::read_string(self, charset, framing)
+    }
 }
 
 impl<T> BitReadExts for T where T: BitRead {}
 
 #[cfg(test)]
 mod tests {
-    use crate::{bit_cursor::BitCursor, bitvec};
+    use crate::{
+        bit_cursor::BitCursor,
+        bit_vec::BitVec,
+        bit_write::BitWrite,
+        bitarray, bitvec,
+        byte_order::{BigEndian, Endianness, LittleEndian, WordSwapped},
+    };
 
     use super::*;
 
@@ -215,4 +576,95 @@ mod tests {
         assert_eq!(cursor.read_u2().unwrap(), u2::new(3));
         assert_eq!(cursor.read_u4().unwrap(), u4::new(0b1100));
     }
+
+    #[test]
+    fn test_read_floats() {
+        let mut data = vec![];
+        data.extend_from_slice(&1.5f32.to_be_bytes());
+        data.extend_from_slice(&(-0.1f64).to_le_bytes());
+        data.extend_from_slice(&[0xc0, 0x00]);
+        // Put the floats at an odd bit offset
+        let mut cursor = BitCursor::new(bitvec!(0; 3 + data.len() * 8));
+        cursor.write_all(&bitarray!(1, 0, 1)).unwrap();
+        cursor.write_from_bytes(&data, 0, data.len() * 8).unwrap();
+
+        let mut cursor = BitCursor::new(cursor.into_inner());
+        assert_eq!(cursor.read_u3().unwrap(), u3::new(0b101));
+        assert_eq!(cursor.read_f32::<BigEndian>().unwrap(), 1.5);
+        assert_eq!(cursor.read_f64::<LittleEndian>().unwrap(), -0.1);
+        assert_eq!(cursor.read_f16::<BigEndian>().unwrap(), -2.0);
+    }
+
+    #[test]
+    fn test_read_fixed() {
+        // A signed Q2.3 (-1.375), then an unsigned Q0.3 (0.625)
+        let vec = bitvec!(1, 0, 1, 0, 1, 1, 0, 1);
+        let mut cursor = BitCursor::new(vec);
+        let value = cursor.read_fixed(QFormat::signed(2, 3)).unwrap();
+        assert_eq!(value.to_f64(), -1.375);
+        let value = cursor.read_fixed(QFormat::unsigned(0, 3)).unwrap();
+        assert_eq!(value.to_f64(), 0.625);
+        assert!(cursor.read_fixed(QFormat::unsigned(1, 0)).is_err());
+    }
+
+    #[test]
+    fn test_read_encodings() {
+        // BCD 59, Gray 0b110 (4), sign-magnitude -3, one's complement -3
+        let vec = bitvec!(0, 1, 0, 1, 1, 0, 0, 1, 1, 1, 0, 1, 1, 1, 1, 0, 0);
+        let mut cursor = BitCursor::new(vec);
+        assert_eq!(cursor.read_bcd(8).unwrap(), 59);
+        assert_eq!(cursor.read_gray(3).unwrap(), 4);
+        assert_eq!(cursor.read_sign_magnitude(3).unwrap(), -3);
+        assert_eq!(cursor.read_ones_complement(3).unwrap(), -3);
+        assert_eq!(
+            cursor.read_bcd(4),
+            Err(crate::error::B3Error::Io(io::ErrorKind::UnexpectedEof))
+        );
+
+        // Invalid widths are rejected without reading anything
+        let mut cursor = BitCursor::new(bitvec!(1; 72));
+        let invalid = |bits| crate::error::B3Error::InvalidWidth { bits, max: 64 };
+        for width in [0, 65] {
+            assert_eq!(cursor.read_bcd(width), Err(invalid(width)));
+            assert_eq!(cursor.read_gray(width), Err(invalid(width)));
+            assert_eq!(cursor.read_sign_magnitude(width), Err(invalid(width)));
+            assert_eq!(cursor.read_ones_complement(width), Err(invalid(width)));
+        }
+        assert_eq!(cursor.bits_remaining(), 72);
+    }
+
+    #[test]
+    fn test_read_with() {
+        // A TIFF-style header: the byte order marker decides how the rest is read
+        let headers = [
+            (*b"II", [0x03, 0x02, 0x01], Endianness::Little),
+            (*b"MM", [0x01, 0x02, 0x03], Endianness::Big),
+        ];
+        for (marker, value, endianness) in headers {
+            let mut data = marker.to_vec();
+            data.extend_from_slice(&value);
+            let vec = BitVec::from_bytes(&data, data.len() * 8);
+            let mut cursor = BitCursor::new(vec);
+            let order = match cursor.read_u16::<BigEndian>().unwrap() {
+                0x4949 => Endianness::Little,
+                _ => Endianness::Big,
+            };
+            assert_eq!(order, endianness);
+            assert_eq!(cursor.read_u24_with(order).unwrap(), u24::new(0x010203));
+        }
+    }
+
+    #[test]
+    fn test_read_word_swapped() {
+        // Holding registers [0xE979, 0x42F6] from a device which sends 123.456f32 in CDAB order
+        let data = [0xE9, 0x79, 0x42, 0xF6];
+        let vec = BitVec::from_bytes(&data, 32);
+        let mut cursor = BitCursor::new(vec);
+        assert_eq!(cursor.read_f32::<WordSwapped>().unwrap(), 123.456);
+        let mut cursor = BitCursor::new(BitVec::from_bytes(&data, 32));
+        assert_eq!(
+            cursor.read_u32_with(Endianness::WordSwapped).unwrap(),
+            0x42F6E979
+        );
+    }
 }
@@ -0,0 +1,318 @@
+//! [`serde`] support for the bit containers, enabled by the `serde` feature.
+//!
+//! Human-readable formats (e.g. JSON) represent a container as a string of '0' and '1'
+//! characters.  Binary formats (e.g. bincode) represent it as a tuple of the length in bits
+//! followed by the packed bytes, where the first bit is the most significant bit of the first
+//! byte.  When deserializing, the length must be consistent with the number of bytes.
+//!
+//! [`BitVec`] and [`BitBytes`] can be serialized and deserialized; [`BitSlice`] and
+//! [`BitSliceMut`] can only be serialized (deserializing them produces a [`BitVec`]).  `[u1; N]`
+//! arrays (as created by [`bitarray!`](crate::bitarray)) can use the same representation via the
+//! [`bit_array`] module.
+
+use alloc::{string::String, vec::Vec};
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use ux::u1;
+
+use crate::{
+    bit_bytes::BitBytes,
+    bit_store::BitStore,
+    bit_vec::BitVec,
+    slice::{BitSlice, BitSliceMut},
+};
+
+/// Serialize the given bits in either the human-readable or binary format.
+fn serialize_bits<S, I>(bits: I, len: usize, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    I: Iterator<Item = u1>,
+{
+    if serializer.is_human_readable() {
+        let str: String = bits
+            .map(|bit| if bit == u1::new(1) { '1' } else { '0' })
+            .collect();
+        serializer.serialize_str(&str)
+    } else {
+        let mut vec = BitVec::with_capacity(len);
+        bits.for_each(|bit| vec.push(bit));
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&(len as u64))?;
+        tuple.serialize_element(&Bytes(vec.as_bytes()))?;
+        tuple.end()
+    }
+}
+
+/// Wrapper to serialize a byte slice via [`Serializer::serialize_bytes`] rather than as a
+/// sequence.
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Wrapper to deserialize a byte buffer via [`Deserializer::deserialize_byte_buf`].
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ByteBufVisitor;
+
+        impl<'de> Visitor<'de> for ByteBufVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte buffer")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(ByteBuf(v))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut buf = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    buf.push(byte);
+                }
+                Ok(ByteBuf(buf))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(ByteBufVisitor)
+    }
+}
+
+struct BitVecVisitor<S>(PhantomData<S>);
+
+impl<'de, S: BitStore> Visitor<'de> for BitVecVisitor<S> {
+    type Value = BitVec<S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string of bits or a (length, bytes) tuple")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let mut vec = BitVec::default();
+        vec.reserve(v.len());
+        for c in v.chars() {
+            match c {
+                '0' => vec.push(u1::new(0)),
+                '1' => vec.push(u1::new(1)),
+                _ => {
+                    return Err(de::Error::invalid_value(
+                        de::Unexpected::Char(c),
+                        &"'0' or '1'",
+                    ))
+                }
+            }
+        }
+        Ok(vec)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let len: u64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let bytes: ByteBuf = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let len = usize::try_from(len)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Unsigned(len), &self))?;
+        if len.div_ceil(8) != bytes.0.len() {
+            return Err(de::Error::custom(format_args!(
+                "bit length {} is inconsistent with byte length {}",
+                len,
+                bytes.0.len()
+            )));
+        }
+        Ok(BitVec::from_bytes(&bytes.0, len))
+    }
+}
+
+impl<T: BitStore> Serialize for BitVec<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serialize_bits(self.iter(), self.len(), serializer)
+        } else {
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&(self.len() as u64))?;
+            tuple.serialize_element(&Bytes(&self.to_bytes()))?;
+            tuple.end()
+        }
+    }
+}
+
+impl<'de, S: BitStore> Deserialize<'de> for BitVec<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BitVecVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_tuple(2, BitVecVisitor(PhantomData))
+        }
+    }
+}
+
+impl Serialize for BitSlice<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bits(self.iter(), self.len(), serializer)
+    }
+}
+
+impl Serialize for BitSliceMut<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // safety: this slice range will always be valid
+        Serialize::serialize(&self.get_slice(..).unwrap(), serializer)
+    }
+}
+
+impl Serialize for BitBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&self.as_bit_slice(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BitBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BitVec::deserialize(deserializer).map(BitBytes::from)
+    }
+}
+
+/// (De)serialize a `[u1; N]` using the same representation as [`BitVec`], for use with
+/// `#[serde(with = "b3::bit_serde::bit_array")]`.
+pub mod bit_array {
+    use alloc::format;
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use ux::u1;
+
+    use crate::bit_vec::BitVec;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &[u1; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize_bits(value.iter().copied(), N, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u1; N], D::Error> {
+        let vec: BitVec = BitVec::deserialize(deserializer)?;
+        if vec.len() != N {
+            return Err(de::Error::invalid_length(
+                vec.len(),
+                &format!("{} bits", N).as_str(),
+            ));
+        }
+        let mut arr = [u1::new(0); N];
+        arr.iter_mut()
+            .zip(vec.iter())
+            .for_each(|(dst, src)| *dst = src);
+        Ok(arr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{bitarray, bitvec};
+
+    use super::*;
+
+    #[test]
+    fn test_bit_vec_json() {
+        let vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1);
+        let json = serde_json::to_string(&vec).unwrap();
+        assert_eq!(json, "\"101100101\"");
+        assert_eq!(serde_json::from_str::<BitVec>(&json).unwrap(), vec);
+
+        assert!(serde_json::from_str::<BitVec>("\"1012\"").is_err());
+    }
+
+    #[test]
+    fn test_bit_vec_bincode() {
+        let vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1);
+        let bytes = bincode::serialize(&vec).unwrap();
+        // 8 byte length, 8 byte byte-length prefix, 2 bytes of data
+        assert_eq!(bytes.len(), 18);
+        assert_eq!(bincode::deserialize::<BitVec>(&bytes).unwrap(), vec);
+    }
+
+    #[test]
+    fn test_bit_vec_bincode_inconsistent_length() {
+        let bytes = bincode::serialize(&(17u64, Bytes(&[0xFF, 0xFF]))).unwrap();
+        assert!(bincode::deserialize::<BitVec>(&bytes).is_err());
+        let bytes = bincode::serialize(&(16u64, Bytes(&[0xFF, 0xFF]))).unwrap();
+        assert_eq!(
+            bincode::deserialize::<BitVec>(&bytes).unwrap(),
+            bitvec!(1; 16)
+        );
+    }
+
+    #[test]
+    fn test_word_bit_vec() {
+        let vec: BitVec<u64> = BitVec::from_bytes(&[0xAB; 9], 70);
+        let json = serde_json::to_string(&vec).unwrap();
+        assert_eq!(serde_json::from_str::<BitVec<u64>>(&json).unwrap(), vec);
+        let bytes = bincode::serialize(&vec).unwrap();
+        assert_eq!(bincode::deserialize::<BitVec<u64>>(&bytes).unwrap(), vec);
+        // The byte representation is the same regardless of the storage type
+        assert_eq!(bytes, bincode::serialize(&BitVec::<u8>::from(vec)).unwrap());
+    }
+
+    #[test]
+    fn test_bit_slice() {
+        let vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1);
+        let slice = vec.get_slice(3..).unwrap();
+        assert_eq!(serde_json::to_string(&slice).unwrap(), "\"100101\"");
+        let bytes = bincode::serialize(&slice).unwrap();
+        assert_eq!(
+            bincode::deserialize::<BitVec>(&bytes).unwrap(),
+            bitvec!(1, 0, 0, 1, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_bit_bytes() {
+        let bytes = BitBytes::from(bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1))
+            .slice(2..)
+            .unwrap();
+        let json = serde_json::to_string(&bytes).unwrap();
+        assert_eq!(json, "\"1100101\"");
+        assert_eq!(serde_json::from_str::<BitBytes>(&json).unwrap(), bytes);
+        let encoded = bincode::serialize(&bytes).unwrap();
+        assert_eq!(bincode::deserialize::<BitBytes>(&encoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_bit_array() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Header {
+            #[serde(with = "bit_array")]
+            flags: [u1; 3],
+        }
+
+        let header = Header {
+            flags: bitarray!(1, 0, 1),
+        };
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(json, r#"{"flags":"101"}"#);
+        assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+        assert!(serde_json::from_str::<Header>(r#"{"flags":"1010"}"#).is_err());
+
+        let bytes = bincode::serialize(&header).unwrap();
+        assert_eq!(bincode::deserialize::<Header>(&bytes).unwrap(), header);
+    }
+}
@@ -0,0 +1,154 @@
+use core::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
+};
+
+use ux::u1;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// [`BitStore`] is implemented for the unsigned integer types which can be used as the storage
+/// for a [`BitVec`](crate::bit_vec::BitVec).  Bits are always stored MSB-first: the first bit
+/// held in a storage element is its most significant bit.  Wider storage types allow bulk
+/// operations (popcount, search, bitwise ops) to process more bits per step.
+pub trait BitStore:
+    private::Sealed
+    + Copy
+    + Default
+    + Debug
+    + Eq
+    + Ord
+    + Hash
+    + BitAnd<Output = Self>
+    + BitAndAssign
+    + BitOr<Output = Self>
+    + BitOrAssign
+    + BitXor<Output = Self>
+    + BitXorAssign
+    + Not<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+{
+    /// The number of bits in this storage type.
+    const BITS: usize;
+    const ALL_ONES: Self;
+
+    /// Get the bit at `index` (where 0 is the most significant bit).
+    fn get_bit(self, index: usize) -> u1;
+
+    /// Set the bit at `index` (where 0 is the most significant bit) to `value`.
+    fn set_bit(&mut self, index: usize, value: u1);
+
+    /// A mask with the first (most significant) `n` bits set.  `n` must be in `1..=BITS`.
+    fn leading_mask(n: usize) -> Self;
+
+    fn count_ones(self) -> u32;
+
+    fn leading_zeros(self) -> u32;
+
+    /// Reverse the order of the bits in this element.
+    fn reverse_bits(self) -> Self;
+
+    /// Reverse the order of the bytes in this element.
+    fn swap_bytes(self) -> Self;
+
+    /// Get the byte at `index` within this element, where byte 0 holds the most significant
+    /// bits.
+    fn get_byte(self, index: usize) -> u8;
+
+    /// Set the byte at `index` within this element, where byte 0 holds the most significant
+    /// bits.
+    fn set_byte(&mut self, index: usize, value: u8);
+}
+
+macro_rules! impl_bit_store {
+    ($type:ty) => {
+        impl private::Sealed for $type {}
+
+        impl BitStore for $type {
+            const BITS: usize = <$type>::BITS as usize;
+            const ALL_ONES: Self = <$type>::MAX;
+
+            fn get_bit(self, index: usize) -> u1 {
+                debug_assert!(index < <Self as BitStore>::BITS);
+                u1::new(((self >> (<Self as BitStore>::BITS - 1 - index)) & 1) as u8)
+            }
+
+            fn set_bit(&mut self, index: usize, value: u1) {
+                debug_assert!(index < <Self as BitStore>::BITS);
+                let shift = <Self as BitStore>::BITS - 1 - index;
+                *self &= !(1 << shift);
+                *self |= (u8::from(value) as $type) << shift;
+            }
+
+            fn leading_mask(n: usize) -> Self {
+                debug_assert!(n > 0 && n <= <Self as BitStore>::BITS);
+                Self::ALL_ONES << (<Self as BitStore>::BITS - n)
+            }
+
+            fn count_ones(self) -> u32 {
+                <$type>::count_ones(self)
+            }
+
+            fn leading_zeros(self) -> u32 {
+                <$type>::leading_zeros(self)
+            }
+
+            fn reverse_bits(self) -> Self {
+                <$type>::reverse_bits(self)
+            }
+
+            fn swap_bytes(self) -> Self {
+                <$type>::swap_bytes(self)
+            }
+
+            fn get_byte(self, index: usize) -> u8 {
+                debug_assert!(index < <Self as BitStore>::BITS / 8);
+                (self >> (<Self as BitStore>::BITS - 8 * (index + 1))) as u8
+            }
+
+            fn set_byte(&mut self, index: usize, value: u8) {
+                debug_assert!(index < <Self as BitStore>::BITS / 8);
+                let shift = <Self as BitStore>::BITS - 8 * (index + 1);
+                *self &= !((0xFF as $type) << shift);
+                *self |= (value as $type) << shift;
+            }
+        }
+    };
+}
+
+impl_bit_store!(u8);
+impl_bit_store!(u16);
+impl_bit_store!(u32);
+impl_bit_store!(u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_set_bit() {
+        let mut word = 0u64;
+        word.set_bit(0, u1::new(1));
+        word.set_bit(63, u1::new(1));
+        assert_eq!(word, 0x8000_0000_0000_0001);
+        assert_eq!(word.get_bit(0), u1::new(1));
+        assert_eq!(word.get_bit(1), u1::new(0));
+        word.set_bit(0, u1::new(0));
+        assert_eq!(word, 1);
+    }
+
+    #[test]
+    fn test_get_set_byte() {
+        let mut word = 0x0102_0304_0506_0708u64;
+        assert_eq!(word.get_byte(0), 0x01);
+        assert_eq!(word.get_byte(7), 0x08);
+        word.set_byte(1, 0xFF);
+        assert_eq!(word, 0x01FF_0304_0506_0708);
+        assert_eq!(u64::leading_mask(4), 0xF000_0000_0000_0000);
+        assert_eq!(u8::leading_mask(8), 0xFF);
+    }
+}
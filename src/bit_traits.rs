@@ -1,4 +1,4 @@
-use std::ops::{BitAnd, BitOrAssign, ShlAssign, ShrAssign};
+use core::ops::{BitAnd, BitOrAssign, ShlAssign, ShrAssign};
 
 use ux::*;
 
@@ -50,6 +50,12 @@ impl BitTraits for u32 {
     const ONE: Self = 1;
 }
 
+impl BitTraits for u64 {
+    const BITS: usize = 64;
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+}
+
 impl_bit_traits_for_ux!(u1, 1);
 impl_bit_traits_for_ux!(u2, 2);
 impl_bit_traits_for_ux!(u3, 3);
@@ -79,3 +85,34 @@ impl_bit_traits_for_ux!(u28, 28);
 impl_bit_traits_for_ux!(u29, 29);
 impl_bit_traits_for_ux!(u30, 30);
 impl_bit_traits_for_ux!(u31, 31);
+impl_bit_traits_for_ux!(u33, 33);
+impl_bit_traits_for_ux!(u34, 34);
+impl_bit_traits_for_ux!(u35, 35);
+impl_bit_traits_for_ux!(u36, 36);
+impl_bit_traits_for_ux!(u37, 37);
+impl_bit_traits_for_ux!(u38, 38);
+impl_bit_traits_for_ux!(u39, 39);
+impl_bit_traits_for_ux!(u40, 40);
+impl_bit_traits_for_ux!(u41, 41);
+impl_bit_traits_for_ux!(u42, 42);
+impl_bit_traits_for_ux!(u43, 43);
+impl_bit_traits_for_ux!(u44, 44);
+impl_bit_traits_for_ux!(u45, 45);
+impl_bit_traits_for_ux!(u46, 46);
+impl_bit_traits_for_ux!(u47, 47);
+impl_bit_traits_for_ux!(u48, 48);
+impl_bit_traits_for_ux!(u49, 49);
+impl_bit_traits_for_ux!(u50, 50);
+impl_bit_traits_for_ux!(u51, 51);
+impl_bit_traits_for_ux!(u52, 52);
+impl_bit_traits_for_ux!(u53, 53);
+impl_bit_traits_for_ux!(u54, 54);
+impl_bit_traits_for_ux!(u55, 55);
+impl_bit_traits_for_ux!(u56, 56);
+impl_bit_traits_for_ux!(u57, 57);
+impl_bit_traits_for_ux!(u58, 58);
+impl_bit_traits_for_ux!(u59, 59);
+impl_bit_traits_for_ux!(u60, 60);
+impl_bit_traits_for_ux!(u61, 61);
+impl_bit_traits_for_ux!(u62, 62);
+impl_bit_traits_for_ux!(u63, 63);
@@ -1,24 +1,39 @@
-use std::ops::RangeBounds;
+use alloc::{vec, vec::Vec};
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::{BitAndAssign, BitOrAssign, BitXorAssign, Not, RangeBounds},
+};
 
 use ux::*;
 
 use crate::{
-    bit_buffer::{BitBuffer, BitBufferMut},
+    bit_buffer::{BitBuffer, BitBufferMut, BitBufferSplit},
+    bit_store::BitStore,
+    bit_write::BitWrite,
     error::{B3Error, B3Result},
+    io,
     slice::{BitSlice, BitSliceMut},
-    util::{get_bit, get_start_end_bit_index_from_range, set_bit},
+    util::{get_start_end_bit_index_from_range, hash_bits},
 };
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct BitVec {
-    buf: Vec<u8>,
+/// A Vector whose API is in bits, instead of bytes.
+///
+/// By default the bits are stored in a `Vec<u8>`.  Any [`BitStore`] type can be used as the
+/// storage instead: e.g. `BitVec<u64>` stores the bits in 64-bit words, which lets bulk
+/// operations (popcount, search, bitwise ops) process 64 bits per step.  Slicing (and therefore
+/// use with [`BitCursor`](crate::bit_cursor::BitCursor)) is only supported with the default
+/// `u8` storage; use [`BitVec::to_bytes`]/[`BitVec::from_bytes`] or the [`From`] impls to convert
+/// between the two.
+#[derive(Debug)]
+pub struct BitVec<S: BitStore = u8> {
+    buf: Vec<S>,
     /// The length of the data inside this BitVec, in bits
     len: usize,
 }
 
 // TODO: 'release' function that returns Vec and length? do we need the length?
 
-/// A Vector whose API is in bits, instead of bytes.
 #[allow(clippy::len_without_is_empty)]
 impl BitVec {
     /// Create a BitVec with an empty buffer
@@ -32,8 +47,8 @@ impl BitVec {
     /// Create a BitVec from the given buffer.
     ///
     /// * `data`: The backing buffer to be used for this BitVec.  It's assumed that the vector
-    /// is "full" of bits, i.e. the length of this BitVec will be set to the length of the
-    /// vector * 8.
+    ///   is "full" of bits, i.e. the length of this BitVec will be set to the length of the
+    ///   vector * 8.
     pub fn from_vec(data: Vec<u8>) -> BitVec {
         let len = data.len() * 8;
         BitVec { buf: data, len }
@@ -44,11 +59,252 @@ impl BitVec {
     /// * `capacity`: the initial capacity of the BitVec, in bits
     pub fn with_capacity(capacity: usize) -> BitVec {
         BitVec {
-            buf: Vec::with_capacity((capacity + 7) / 8),
+            buf: Vec::with_capacity(capacity.div_ceil(8)),
             len: 0,
         }
     }
 
+    /// Return the bytes backing this BitVec.  The first bit of the BitVec is the most significant
+    /// bit of the first byte.  If the length isn't a multiple of 8, the unused bits at the end of
+    /// the last byte are included as well.
+    /// # Example
+    /// ```
+    /// use b3::bitvec;
+    /// let vec = bitvec!(1, 0, 1, 0, 1, 0, 1, 0, 1);
+    /// assert_eq!(vec.as_bytes(), &[0b10101010, 0b10000000]);
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Get a slice of this BitVec representing the given range, where the left-most bit is index
+    /// 0.
+    ///
+    /// * `range`: the range
+    ///
+    /// # Example
+    /// ```
+    /// use b3::bitvec;
+    ///
+    /// let vec = bitvec!(0, 0, 0, 0, 1, 1, 1, 1);
+    /// let slice = vec.get_slice(3..).expect("valid slice");
+    /// assert_eq!(slice.len(), 5);
+    /// assert_eq!(slice, bitvec!(0, 1, 1, 1, 1));
+    /// ```
+    pub fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+        let (start_bit_index, end_bit_index) =
+            get_start_end_bit_index_from_range(&range, self.len());
+        let start_byte = start_bit_index / 8;
+        // Exclusive, so that an empty range needs no bytes
+        let end_byte = end_bit_index.div_ceil(8);
+        let bit_len = end_bit_index - start_bit_index;
+        // We now need to adjust the start_bit_index to be relative to the start_byte
+        let start_bit_index = start_bit_index - start_byte * 8;
+        if end_byte > self.buf.len() {
+            return Err(B3Error::SliceOutOfRange {
+                len: self.buf.len(),
+                slice_start: start_byte,
+                slice_end: end_byte,
+            });
+        }
+        Ok(BitSlice::new(
+            &self.buf[start_byte..end_byte],
+            start_bit_index,
+            start_bit_index + bit_len,
+        ))
+    }
+
+    /// Get a mutable slice of this BitVec representing the given range.
+    ///
+    /// * `range`: the range
+    ///
+    /// # Example
+    /// ```
+    /// use ux::u1;
+    /// use b3::{bitvec, bit_traits::BitTraits};
+    ///
+    /// let mut vec = bitvec!(0, 1, 1, 0);
+    /// let mut slice = vec.get_slice_mut(1..=2).expect("valid slice");
+    /// assert_eq!(slice.len(), 2);
+    /// slice.set(0, u1::ZERO);
+    /// slice.set(1, u1::ZERO);
+    /// assert_eq!(vec, bitvec!(0, 0, 0, 0));
+    /// ```
+    pub fn get_slice_mut<T: RangeBounds<usize>>(&mut self, range: T) -> B3Result<BitSliceMut<'_>> {
+        let (start_bit_index, end_bit_index) =
+            get_start_end_bit_index_from_range(&range, self.len());
+        let start_byte = start_bit_index / 8;
+        // Exclusive, so that an empty range needs no bytes
+        let end_byte = end_bit_index.div_ceil(8);
+        let bit_len = end_bit_index - start_bit_index;
+        // We now need to adjust the start_bit_index to be relative to the start_byte
+        let start_bit_index = start_bit_index - start_byte * 8;
+        if end_byte > self.buf.len() {
+            return Err(B3Error::SliceOutOfRange {
+                len: self.buf.len(),
+                slice_start: start_byte,
+                slice_end: end_byte,
+            });
+        }
+        Ok(BitSliceMut::new(
+            &mut self.buf[start_byte..end_byte],
+            start_bit_index,
+            start_bit_index + bit_len,
+        ))
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl<S: BitStore> BitVec<S> {
+    /// Create a BitVec from the given bytes, where the first bit is the most significant bit of
+    /// the first byte.  Panics if `bytes` doesn't contain at least `len` bits.
+    ///
+    /// * `bytes`: The bytes to copy the bits from.
+    /// * `len`: The length of the BitVec, in bits.
+    /// # Example
+    /// ```
+    /// use b3::{bitvec, bit_vec::BitVec};
+    /// let vec: BitVec<u64> = BitVec::from_bytes(&[0b10100000], 3);
+    /// assert_eq!(vec.len(), 3);
+    /// assert_eq!(vec.to_bytes(), vec![0b10100000]);
+    /// ```
+    pub fn from_bytes(bytes: &[u8], len: usize) -> BitVec<S> {
+        assert!(len <= bytes.len() * 8);
+        let bytes_per_elem = S::BITS / 8;
+        let mut buf = vec![S::default(); len.div_ceil(S::BITS)];
+        for (i, &byte) in bytes.iter().take(len.div_ceil(8)).enumerate() {
+            buf[i / bytes_per_elem].set_byte(i % bytes_per_elem, byte);
+        }
+        let mut vec = BitVec { buf, len };
+        vec.clear_unused_bits();
+        vec
+    }
+
+    /// Return the bits in this BitVec as bytes, where the first bit is the most significant bit
+    /// of the first byte.  If the length isn't a multiple of 8, the unused bits at the end of the
+    /// last byte are 0.
+    /// # Example
+    /// ```
+    /// use b3::bitvec;
+    /// let vec = bitvec!(1, 0, 1, 0, 1, 0, 1, 0, 1);
+    /// assert_eq!(vec.to_bytes(), vec![0b10101010, 0b10000000]);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes().collect()
+    }
+
+    /// An iterator over the bits in this BitVec packed into bytes, as returned by
+    /// [`BitVec::to_bytes`].
+    pub(crate) fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        let bytes_per_elem = S::BITS / 8;
+        let num_bytes = self.len.div_ceil(8);
+        (0..num_bytes).map(move |i| {
+            let byte = self.buf[i / bytes_per_elem].get_byte(i % bytes_per_elem);
+            if i == num_bytes - 1 {
+                byte & u8::leading_mask(self.len - i * 8)
+            } else {
+                byte
+            }
+        })
+    }
+
+    /// Reverse the order of the bits in this BitVec.
+    /// # Example
+    /// ```
+    /// use b3::bitvec;
+    /// let mut vec = bitvec!(1, 1, 0, 1, 0);
+    /// vec.reverse();
+    /// assert_eq!(vec, bitvec!(0, 1, 0, 1, 1));
+    /// ```
+    pub fn reverse(&mut self) {
+        self.buf.reverse();
+        self.buf
+            .iter_mut()
+            .for_each(|elem| *elem = elem.reverse_bits());
+        // The unused bits at the end of the last element are now at the start of the first, so
+        // shift everything back over them
+        let unused = self.buf.len() * S::BITS - self.len;
+        if unused > 0 {
+            for i in 0..self.buf.len() {
+                let next = self
+                    .buf
+                    .get(i + 1)
+                    .map_or(S::default(), |&elem| elem >> (S::BITS - unused));
+                self.buf[i] = (self.buf[i] << unused) | next;
+            }
+        }
+    }
+
+    /// Reverse the order of the bits within each byte, e.g. to convert between MSB-first and
+    /// LSB-first bit order.  Panics if the length isn't a multiple of 8.
+    /// # Example
+    /// ```
+    /// use b3::bit_vec::BitVec;
+    /// let mut vec = BitVec::from_vec(vec![0b1100_0001, 0b0000_1111]);
+    /// vec.reverse_bits_in_bytes();
+    /// assert_eq!(vec.as_bytes(), &[0b1000_0011, 0b1111_0000]);
+    /// ```
+    pub fn reverse_bits_in_bytes(&mut self) {
+        assert!(
+            self.len % 8 == 0,
+            "length ({}) is not a whole number of bytes",
+            self.len
+        );
+        // Reversing all the bits of an element also reverses its bytes, so swap them back
+        self.buf
+            .iter_mut()
+            .for_each(|elem| *elem = elem.reverse_bits().swap_bytes());
+    }
+
+    /// Reverse the order of the bytes within each `width` bit word.  Panics if `width` isn't a
+    /// non-zero multiple of 8, or if the length isn't a multiple of `width`.
+    /// # Example
+    /// ```
+    /// use b3::bit_vec::BitVec;
+    /// let mut vec = BitVec::from_vec(vec![0x12, 0x34, 0x56, 0x78]);
+    /// vec.swap_bytes_in_words(16);
+    /// assert_eq!(vec.as_bytes(), &[0x34, 0x12, 0x78, 0x56]);
+    /// ```
+    pub fn swap_bytes_in_words(&mut self, width: usize) {
+        assert!(
+            width > 0 && width % 8 == 0,
+            "word width ({}) is not a whole number of bytes",
+            width
+        );
+        assert!(
+            self.len % width == 0,
+            "length ({}) is not a whole number of {} bit words",
+            self.len,
+            width
+        );
+        if width == S::BITS {
+            self.buf
+                .iter_mut()
+                .for_each(|elem| *elem = elem.swap_bytes());
+            return;
+        }
+        let bytes_per_elem = S::BITS / 8;
+        let word_bytes = width / 8;
+        for word in (0..self.len / 8).step_by(word_bytes) {
+            for i in 0..word_bytes / 2 {
+                let (front, back) = (word + i, word + word_bytes - 1 - i);
+                let front_byte = self.buf[front / bytes_per_elem].get_byte(front % bytes_per_elem);
+                let back_byte = self.buf[back / bytes_per_elem].get_byte(back % bytes_per_elem);
+                self.buf[front / bytes_per_elem].set_byte(front % bytes_per_elem, back_byte);
+                self.buf[back / bytes_per_elem].set_byte(back % bytes_per_elem, front_byte);
+            }
+        }
+    }
+
+    /// Reserve capacity for at least `additional` more bits.
+    ///
+    /// * `additional`: The number of additional bits.
+    pub fn reserve(&mut self, additional: usize) {
+        let required_elems = (self.len + additional).div_ceil(S::BITS);
+        self.buf
+            .reserve(required_elems.saturating_sub(self.buf.len()));
+    }
+
     /// Push the given value onto the end of this BitVec.  The value will be converted to a u1.
     ///
     /// * `value`: The value to push.
@@ -66,12 +322,12 @@ impl BitVec {
     /// assert_eq!(vec.at(2), u1::ONE);
     /// ```
     pub fn push<T: Into<u1>>(&mut self, value: T) {
-        // 'allocate' another byte if needed
-        if self.len % 8 == 0 {
-            self.buf.push(0);
+        // 'allocate' another element if needed
+        if self.len % S::BITS == 0 {
+            self.buf.push(S::default());
         }
-        let last_byte = self.buf.last_mut().unwrap();
-        set_bit(last_byte, self.len % 8, value.into());
+        let last_elem = self.buf.last_mut().unwrap();
+        last_elem.set_bit(self.len % S::BITS, value.into());
         self.len += 1;
     }
 
@@ -89,11 +345,13 @@ impl BitVec {
         if self.len == 0 {
             return None;
         }
-        let last_byte = self.buf.last().unwrap();
-        let result = get_bit(*last_byte, (self.len - 1) % 8);
+        let last_elem = self.buf.last_mut().unwrap();
+        let result = last_elem.get_bit((self.len - 1) % S::BITS);
+        // Clear the popped bit so that unused bits are always 0
+        last_elem.set_bit((self.len - 1) % S::BITS, u1::new(0));
 
         self.len -= 1;
-        if self.len % 8 == 0 {
+        if self.len % S::BITS == 0 {
             self.buf.pop();
         }
 
@@ -114,9 +372,7 @@ impl BitVec {
     /// ```
     pub fn at(&self, index: usize) -> u1 {
         assert!(index < self.len());
-        let byte_pos = index / 8;
-        let bit_index = index % 8;
-        get_bit(self.buf[byte_pos], bit_index)
+        self.buf[index / S::BITS].get_bit(index % S::BITS)
     }
 
     /// Get an iterator to the bits in this BitVec
@@ -131,7 +387,7 @@ impl BitVec {
     /// assert_eq!(iter.next(), Some(u1::ONE));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter(&self) -> BitVecIterator<'_> {
+    pub fn iter(&self) -> BitVecIterator<'_, S> {
         BitVecIterator {
             vec: self,
             bit_pos: 0,
@@ -151,87 +407,248 @@ impl BitVec {
 
     /// Return the capacity of this BitVec in bits.
     pub fn capacity(&self) -> usize {
-        self.buf.capacity() * 8
+        self.buf.capacity() * S::BITS
     }
 
-    /// Get a slice of this BitVec representing the given range, where the left-most bit is index
-    /// 0.
-    ///
-    /// * `range`: the range
+    /// Shorten this BitVec to `len` bits.  Has no effect if `len` is greater than or equal to the
+    /// current length.
     ///
+    /// * `len`: The new length, in bits.
     /// # Example
     /// ```
     /// use b3::bitvec;
-    ///
-    /// let vec = bitvec!(0, 0, 0, 0, 1, 1, 1, 1);
-    /// let slice = vec.get_slice(3..).expect("valid slice");
-    /// assert_eq!(slice.len(), 5);
-    /// assert_eq!(slice, bitvec!(0, 1, 1, 1, 1));
+    /// let mut vec = bitvec!(1, 0, 1, 0, 1, 0, 1, 0, 1);
+    /// vec.truncate(3);
+    /// assert_eq!(vec, bitvec!(1, 0, 1));
     /// ```
-    pub fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
-        let (start_bit_index, end_bit_index) =
-            get_start_end_bit_index_from_range(&range, self.len());
-        let start_byte = start_bit_index / 8;
-        let end_byte = (end_bit_index - 1) / 8;
-        let bit_len = end_bit_index - start_bit_index;
-        // We now need to adjust the start_bit_index to be relative to the start_byte
-        let start_bit_index = start_bit_index - start_byte * 8;
-        if end_byte >= self.buf.len() {
-            return Err(B3Error::SliceOutOfRange {
-                len: self.buf.len(),
-                slice_start: start_byte,
-                slice_end: end_byte,
-            });
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
         }
-        Ok(BitSlice::new(
-            &self.buf[start_byte..=end_byte],
-            start_bit_index,
-            start_bit_index + bit_len,
-        ))
+        self.buf.truncate(len.div_ceil(S::BITS));
+        self.len = len;
+        self.clear_unused_bits();
     }
 
-    /// Get a mutable slice of this BitVec representing the given range.
-    ///
-    /// * `range`: the range
+    /// Set the bit at the given index.  Panics if index is out of range.
     ///
+    /// * `index`: The index
+    /// * `value`: The value to set
+    pub fn set(&mut self, index: usize, value: u1) {
+        assert!(index < self.len());
+        self.buf[index / S::BITS].set_bit(index % S::BITS, value);
+    }
+
+    /// Return the number of bits set to 1.
     /// # Example
     /// ```
-    /// use ux::u1;
-    /// use b3::{bitvec, bit_traits::BitTraits};
-    ///
-    /// let mut vec = bitvec!(0, 1, 1, 0);
-    /// let mut slice = vec.get_slice_mut(1..=2).expect("valid slice");
-    /// assert_eq!(slice.len(), 2);
-    /// slice.set(0, u1::ZERO);
-    /// slice.set(1, u1::ZERO);
-    /// assert_eq!(vec, bitvec!(0, 0, 0, 0));
+    /// use b3::bitvec;
+    /// let vec = bitvec!(1, 0, 1, 1);
+    /// assert_eq!(vec.count_ones(), 3);
     /// ```
-    pub fn get_slice_mut<T: RangeBounds<usize>>(&mut self, range: T) -> B3Result<BitSliceMut<'_>> {
-        let (start_bit_index, end_bit_index) =
-            get_start_end_bit_index_from_range(&range, self.len());
-        let start_byte = start_bit_index / 8;
-        let end_byte = (end_bit_index - 1) / 8;
-        let bit_len = end_bit_index - start_bit_index;
-        // We now need to adjust the start_bit_index to be relative to the start_byte
-        let start_bit_index = start_bit_index - start_byte * 8;
-        if end_byte >= self.buf.len() {
-            return Err(B3Error::SliceOutOfRange {
-                len: self.buf.len(),
-                slice_start: start_byte,
-                slice_end: end_byte,
-            });
+    pub fn count_ones(&self) -> usize {
+        self.masked_elems()
+            .map(|elem| elem.count_ones() as usize)
+            .sum()
+    }
+
+    /// Return the number of bits set to 0.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Return the index of the first bit set to 1, if there is one.
+    /// # Example
+    /// ```
+    /// use b3::bitvec;
+    /// let vec = bitvec!(0, 0, 1, 1);
+    /// assert_eq!(vec.first_one(), Some(2));
+    /// assert_eq!(bitvec!(0, 0).first_one(), None);
+    /// ```
+    pub fn first_one(&self) -> Option<usize> {
+        self.masked_elems()
+            .enumerate()
+            .find(|(_, elem)| *elem != S::default())
+            .map(|(i, elem)| i * S::BITS + elem.leading_zeros() as usize)
+    }
+
+    /// Return the index of the first bit set to 0, if there is one.
+    /// # Example
+    /// ```
+    /// use b3::bitvec;
+    /// let vec = bitvec!(1, 1, 0, 1);
+    /// assert_eq!(vec.first_zero(), Some(2));
+    /// assert_eq!(bitvec!(1, 1).first_zero(), None);
+    /// ```
+    pub fn first_zero(&self) -> Option<usize> {
+        let mask = self.last_elem_mask();
+        let last_index = self.buf.len().saturating_sub(1);
+        self.buf
+            .iter()
+            .enumerate()
+            .map(|(i, &elem)| if i == last_index { !elem & mask } else { !elem })
+            .enumerate()
+            .find(|(_, elem)| *elem != S::default())
+            .map(|(i, elem)| i * S::BITS + elem.leading_zeros() as usize)
+    }
+
+    /// The storage elements of this BitVec, with any unused bits in the last element zeroed.
+    fn masked_elems(&self) -> impl Iterator<Item = S> + '_ {
+        let mask = self.last_elem_mask();
+        let last_index = self.buf.len().saturating_sub(1);
+        self.buf
+            .iter()
+            .enumerate()
+            .map(move |(i, &elem)| if i == last_index { elem & mask } else { elem })
+    }
+
+    /// A mask of the bits in the last storage element which are part of this BitVec.
+    fn last_elem_mask(&self) -> S {
+        if self.len == 0 {
+            return S::default();
+        }
+        S::leading_mask(self.len - (self.len - 1) / S::BITS * S::BITS)
+    }
+
+    /// Zero out any unused bits at the end of the last storage element.
+    fn clear_unused_bits(&mut self) {
+        let mask = self.last_elem_mask();
+        if let Some(last_elem) = self.buf.last_mut() {
+            *last_elem &= mask;
         }
-        Ok(BitSliceMut::new(
-            &mut self.buf[start_byte..=end_byte],
-            start_bit_index,
-            start_bit_index + bit_len,
-        ))
     }
 }
 
-impl Default for BitVec {
+impl<S: BitStore> Default for BitVec<S> {
     fn default() -> Self {
-        Self::new()
+        BitVec {
+            buf: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<S: BitStore> PartialEq for BitVec<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.masked_elems().eq(other.masked_elems())
+    }
+}
+
+impl<S: BitStore> Eq for BitVec<S> {}
+
+impl<S: BitStore> Hash for BitVec<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_bits(self.len, self.bytes(), state);
+    }
+}
+
+impl<S: BitStore> PartialOrd for BitVec<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: BitStore> Ord for BitVec<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Storage elements are MSB-first, so comparing them numerically compares the bits
+        // lexicographically.  If one BitVec is a prefix of the other, the shorter one is less.
+        self.masked_elems()
+            .cmp(other.masked_elems())
+            .then(self.len.cmp(&other.len))
+    }
+}
+
+impl<S: BitStore> PartialEq<&[u1]> for BitVec<S> {
+    fn eq(&self, other: &&[u1]) -> bool {
+        self.len == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+impl<S: BitStore, const N: usize> PartialEq<[u1; N]> for BitVec<S> {
+    fn eq(&self, other: &[u1; N]) -> bool {
+        PartialEq::eq(self, &&other[..])
+    }
+}
+
+impl<S: BitStore> BitAndAssign<&BitVec<S>> for BitVec<S> {
+    /// Panics if the lengths of the two BitVecs differ.
+    fn bitand_assign(&mut self, rhs: &BitVec<S>) {
+        assert_eq!(self.len, rhs.len);
+        self.buf
+            .iter_mut()
+            .zip(rhs.buf.iter())
+            .for_each(|(l, &r)| *l &= r);
+    }
+}
+
+impl<S: BitStore> BitOrAssign<&BitVec<S>> for BitVec<S> {
+    /// Panics if the lengths of the two BitVecs differ.
+    fn bitor_assign(&mut self, rhs: &BitVec<S>) {
+        assert_eq!(self.len, rhs.len);
+        self.buf
+            .iter_mut()
+            .zip(rhs.buf.iter())
+            .for_each(|(l, &r)| *l |= r);
+        self.clear_unused_bits();
+    }
+}
+
+impl<S: BitStore> BitXorAssign<&BitVec<S>> for BitVec<S> {
+    /// Panics if the lengths of the two BitVecs differ.
+    fn bitxor_assign(&mut self, rhs: &BitVec<S>) {
+        assert_eq!(self.len, rhs.len);
+        self.buf
+            .iter_mut()
+            .zip(rhs.buf.iter())
+            .for_each(|(l, &r)| *l ^= r);
+        self.clear_unused_bits();
+    }
+}
+
+impl<S: BitStore> Not for BitVec<S> {
+    type Output = BitVec<S>;
+
+    fn not(mut self) -> Self::Output {
+        self.buf.iter_mut().for_each(|elem| *elem = !*elem);
+        self.clear_unused_bits();
+        self
+    }
+}
+
+impl From<BitVec<u64>> for BitVec {
+    fn from(value: BitVec<u64>) -> Self {
+        BitVec::from_bytes(&value.to_bytes(), value.len())
+    }
+}
+
+impl From<BitVec> for BitVec<u64> {
+    fn from(value: BitVec) -> Self {
+        BitVec::from_bytes(value.as_bytes(), value.len())
+    }
+}
+
+/// Writing to a BitVec appends to it, like `std::io::Write` for `Vec<u8>`: writes always
+/// succeed, and grow the BitVec by the bits written.  This lets anything which writes to a
+/// [`BitWrite`] (e.g. the [`ecc`](crate::ecc) encoders) produce a BitVec directly.  To overwrite
+/// the existing bits of a BitVec instead, write through a
+/// [`BitCursor`](crate::bit_cursor::BitCursor) over it.
+/// # Example
+/// ```
+/// use b3::{bit_write::BitWrite, bitarray, bitvec};
+/// let mut vec = bitvec!(1);
+/// vec.write_all(&bitarray!(0, 1)).unwrap();
+/// assert_eq!(vec, bitvec!(1, 0, 1));
+/// ```
+impl<S: BitStore> BitWrite for BitVec<S> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        self.reserve(buf.len());
+        buf.iter().for_each(|&bit| self.push(bit));
+        Ok(())
     }
 }
 
@@ -245,6 +662,14 @@ impl BitBuffer for BitVec {
     }
 }
 
+impl BitBufferSplit for BitVec {
+    type Part<'b> = BitSlice<'b>;
+
+    fn get_part<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+        self.get_slice(range)
+    }
+}
+
 impl BitBufferMut for BitVec {
     fn get_slice_mut<T: RangeBounds<usize>>(&mut self, range: T) -> B3Result<BitSliceMut<'_>> {
         self.get_slice_mut(range)
@@ -306,40 +731,13 @@ macro_rules! bitvec {
     };
 }
 
-/// Create a [u1; N] array.  This is mainly used for testing the byteorder functions, which expect
-/// specifically-sized u1 arrays as their arguments.
-#[macro_export]
-macro_rules! bitarray {
-    (0$(, $rest:tt)*) => {
-        bitarray!(@internal [::ux::u1::new(0)] $($rest),*)
-    };
-    (1$(, $rest:tt)*) => {
-        bitarray!(@internal [::ux::u1::new(1)] $($rest),*)
-    };
-    (@internal [$($done:expr$(,)?)+] 0$(, $rest:tt)*) => {
-        bitarray!(@internal [$($done)*, ::ux::u1::new(0)] $($rest),*)
-    };
-    (@internal [$($done:expr$(,)?)+] 1$(, $rest:tt)*) => {
-        bitarray!(@internal [$($done)*, ::ux::u1::new(1)] $($rest),*)
-    };
-    (@internal [$($done:expr$(,)?)+]) => {
-        [$($done, )*]
-    };
-    ($val:expr$(, $rest:tt)*) => {
-        compile_error!("Only 1s and 0s are valid when creating a bitarray")
-    };
-    (@internal [$($done:expr$(,)?)+] $val:expr$(, $rest:tt)*) => {
-        compile_error!("Only 1s and 0s are valid when creating a bitarray")
-    };
-}
-
 /// An iterator over the bits of a BitVec.
-pub struct BitVecIterator<'a> {
-    vec: &'a BitVec,
+pub struct BitVecIterator<'a, S: BitStore = u8> {
+    vec: &'a BitVec<S>,
     bit_pos: usize,
 }
 
-impl Iterator for BitVecIterator<'_> {
+impl<S: BitStore> Iterator for BitVecIterator<'_, S> {
     type Item = u1;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -355,6 +753,7 @@ impl Iterator for BitVecIterator<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bitarray;
 
     #[test]
     fn test_push() {
@@ -391,6 +790,76 @@ mod tests {
         assert_eq!(slice, bitvec!(0, 0, 0, 0, 0, 1, 1, 1));
     }
 
+    #[test]
+    fn test_get_empty_slice() {
+        let mut vec = BitVec::new();
+        assert_eq!(vec.get_slice(..).expect("valid slice").len(), 0);
+        assert_eq!(vec.get_slice_mut(..).expect("valid slice").len(), 0);
+
+        let mut vec = bitvec!(1, 0, 1, 1, 0, 0, 1, 0, 1);
+        for i in [0, 3, 8, 9] {
+            assert_eq!(vec.get_slice(i..i).expect("valid slice").len(), 0);
+            assert_eq!(vec.get_slice_mut(i..i).expect("valid slice").len(), 0);
+        }
+        assert_eq!(vec.get_slice(9..).expect("valid slice").len(), 0);
+        assert!(vec.get_slice(17..17).is_err());
+        assert!(vec.get_slice(0..17).is_err());
+    }
+
+    #[test]
+    fn test_word_storage() {
+        let mut vec = BitVec::<u64>::default();
+        for i in 0..130 {
+            vec.push(u1::new((i % 3 == 0) as u8));
+        }
+        assert_eq!(vec.len(), 130);
+        assert!((0..130).all(|i| vec.at(i) == u1::new((i % 3 == 0) as u8)));
+        assert_eq!(vec.count_ones(), 44);
+        assert_eq!(vec.pop(), Some(u1::new(1)));
+        assert_eq!(vec.pop(), Some(u1::new(0)));
+        assert_eq!(vec.len(), 128);
+        assert_eq!(vec.count_ones(), 43);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut vec: BitVec<u64> = BitVec::from_bytes(&[0; 20], 150);
+        assert_eq!(vec.first_one(), None);
+        assert_eq!(vec.first_zero(), Some(0));
+        vec.set(140, u1::new(1));
+        assert_eq!(vec.first_one(), Some(140));
+
+        let vec = !vec;
+        assert_eq!(vec.count_ones(), 149);
+        assert_eq!(vec.first_zero(), Some(140));
+        // Unused bits in the last word shouldn't be considered
+        let vec: BitVec<u64> = BitVec::from_bytes(&[0xFF; 8], 10);
+        assert_eq!(vec.first_zero(), None);
+    }
+
+    #[test]
+    fn test_bitwise_ops() {
+        let mut vec = bitvec!(1, 1, 0, 0);
+        vec &= &bitvec!(1, 0, 1, 0);
+        assert_eq!(vec, bitvec!(1, 0, 0, 0));
+        vec |= &bitvec!(0, 0, 1, 0);
+        assert_eq!(vec, bitvec!(1, 0, 1, 0));
+        vec ^= &bitvec!(1, 1, 1, 1);
+        assert_eq!(vec, bitvec!(0, 1, 0, 1));
+        assert_eq!(!vec, bitvec!(1, 0, 1, 0));
+    }
+
+    #[test]
+    fn test_storage_conversion() {
+        let bytes = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0xF0];
+        let vec = BitVec::from_vec(bytes.to_vec());
+        let words = BitVec::<u64>::from(vec);
+        assert_eq!(words.len(), 72);
+        assert!(words.iter().eq(BitVec::from_vec(bytes.to_vec()).iter()));
+        assert_eq!(words.to_bytes(), bytes);
+        assert_eq!(BitVec::from(words), BitVec::from_vec(bytes.to_vec()));
+    }
+
     #[test]
     fn test_get_slice_mut() {
         let mut vec = bitvec!(0);
@@ -399,4 +868,80 @@ mod tests {
         slice.set(0, u1::new(1));
         assert_eq!(slice.at(0), u1::new(1));
     }
+
+    #[test]
+    fn test_reversal() {
+        let mut vec = BitVec::from_vec(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]);
+        vec.swap_bytes_in_words(64);
+        assert_eq!(
+            vec.to_bytes(),
+            [0xEF, 0xCD, 0xAB, 0x89, 0x67, 0x45, 0x23, 0x01]
+        );
+        vec.swap_bytes_in_words(32);
+        assert_eq!(
+            vec.to_bytes(),
+            [0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67]
+        );
+        vec.reverse_bits_in_bytes();
+        vec.reverse();
+        assert_eq!(
+            vec.to_bytes(),
+            [0x67, 0x45, 0x23, 0x01, 0xEF, 0xCD, 0xAB, 0x89]
+        );
+
+        let mut vec = bitvec!(1, 0, 0, 1, 1, 1, 0, 1, 0, 1);
+        vec.reverse();
+        assert_eq!(vec, bitvec!(1, 0, 1, 0, 1, 1, 1, 0, 0, 1));
+        let mut vec = BitVec::new();
+        vec.reverse();
+        assert_eq!(vec.len(), 0);
+    }
+
+    #[test]
+    fn test_word_reversal() {
+        let bytes: Vec<u8> = (0..24u8).map(|i| i.wrapping_mul(37) ^ 0x5A).collect();
+        // The word storage should give the same results as byte storage, whether or not the
+        // length fills the last word
+        for len in [0, 8, 64, 72, 130, 192] {
+            let bytes_vec = |f: &dyn Fn(&mut BitVec)| {
+                let mut vec = BitVec::from_bytes(&bytes, len);
+                f(&mut vec);
+                vec.to_bytes()
+            };
+            let words_vec = |f: &dyn Fn(&mut BitVec<u64>)| {
+                let mut vec = BitVec::<u64>::from_bytes(&bytes, len);
+                f(&mut vec);
+                vec.to_bytes()
+            };
+            assert_eq!(words_vec(&|v| v.reverse()), bytes_vec(&|v| v.reverse()));
+            if len % 8 == 0 {
+                assert_eq!(
+                    words_vec(&|v| v.reverse_bits_in_bytes()),
+                    bytes_vec(&|v| v.reverse_bits_in_bytes())
+                );
+            }
+            for width in [16, 32, 64, 128] {
+                if len % width == 0 {
+                    assert_eq!(
+                        words_vec(&|v| v.swap_bytes_in_words(width)),
+                        bytes_vec(&|v| v.swap_bytes_in_words(width))
+                    );
+                }
+            }
+        }
+
+        let mut vec: BitVec<u64> = BitVec::from_bytes(&[0b1011_0000], 5);
+        vec.reverse();
+        assert_eq!(vec.to_bytes(), [0b0110_1000]);
+        // Unused bits in the last word are still clear
+        assert_eq!(vec.count_ones(), 3);
+    }
+
+    #[test]
+    fn test_write_appends() {
+        let mut vec = bitvec!(1);
+        vec.write_all(&bitarray!(0, 1)).unwrap();
+        assert_eq!(vec.write(&bitarray!(1, 1)).unwrap(), 2);
+        assert_eq!(vec, bitvec!(1, 0, 1, 1, 1));
+    }
 }
@@ -1,9 +1,172 @@
 use ux::u1;
 
+use crate::{
+    io,
+    util::{assert_bits_fit, get_bit},
+};
+
 pub trait BitWrite {
     /// Write a buffer into this writer, returning how many bytes were written.
-    fn write(&mut self, buf: &[u1]) -> std::io::Result<usize>;
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize>;
 
     /// Write the entirety buf into self.
-    fn write_all(&mut self, buf: &[u1]) -> std::io::Result<()>;
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()>;
+
+    /// Write all `nbits` bits from `buf`, which are packed MSB-first starting at bit `bit_offset`
+    /// (where bit 0 is the most significant bit of `buf[0]`).  Panics if `buf` is too small to
+    /// hold `nbits` bits at `bit_offset`.
+    ///
+    /// As with [`BitWrite::write_all`], if this returns an error then an unspecified number of
+    /// bits may have been written.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_write::BitWrite, bitvec};
+    ///
+    /// let mut cursor = BitCursor::new(bitvec!(0; 6));
+    /// cursor.write_from_bytes(&[0b0010_1101], 2, 6).unwrap();
+    /// assert_eq!(cursor.into_inner(), bitvec!(1, 0, 1, 1, 0, 1));
+    /// ```
+    fn write_from_bytes(&mut self, buf: &[u8], bit_offset: usize, nbits: usize) -> io::Result<()> {
+        assert_bits_fit(buf.len(), bit_offset, nbits);
+        let mut bits = [u1::new(0); 64];
+        let mut done = 0;
+        while done < nbits {
+            let n = (nbits - done).min(bits.len());
+            for (i, bit) in bits[..n].iter_mut().enumerate() {
+                let pos = bit_offset + done + i;
+                *bit = get_bit(buf[pos / 8], pos % 8);
+            }
+            self.write_all(&bits[..n])?;
+            done += n;
+        }
+        Ok(())
+    }
+
+    /// Create an adaptor which writes at most `limit` bits to this writer.
+    ///
+    /// # Example
+    /// ```
+    /// use b3::{bit_cursor::BitCursor, bit_write::BitWrite, bitarray, bitvec};
+    ///
+    /// let mut cursor = BitCursor::new(bitvec!(0; 8));
+    /// let mut limit = cursor.by_ref().limit(2);
+    /// assert!(limit.write_all(&bitarray!(1, 1, 1)).is_err());
+    /// assert_eq!(limit.write(&bitarray!(1, 1, 1)).unwrap(), 2);
+    /// assert_eq!(cursor.position(), 2);
+    /// ```
+    fn limit(self, limit: usize) -> Limit<Self>
+    where
+        Self: Sized,
+    {
+        Limit { inner: self, limit }
+    }
+
+    /// Borrow this writer, so an adaptor can be used on it without consuming it.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+impl<W: BitWrite + ?Sized> BitWrite for &mut W {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        (**self).write_all(buf)
+    }
+
+    fn write_from_bytes(&mut self, buf: &[u8], bit_offset: usize, nbits: usize) -> io::Result<()> {
+        (**self).write_from_bytes(buf, bit_offset, nbits)
+    }
+}
+
+/// A writer which writes at most a limited number of bits to an inner writer, created by
+/// [`BitWrite::limit`].
+#[derive(Debug)]
+pub struct Limit<W> {
+    inner: W,
+    limit: usize,
+}
+
+impl<W> Limit<W> {
+    /// The number of bits which can still be written before this writer is full.  (This isn't
+    /// called `limit` so it doesn't clash with [`BitWrite::limit`].)
+    pub fn remaining(&self) -> usize {
+        self.limit
+    }
+
+    /// Set the number of bits which can be written before this writer is full.
+    pub fn set_remaining(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: BitWrite> BitWrite for Limit<W> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        let n = buf.len().min(self.limit);
+        let n = self.inner.write(&buf[..n])?;
+        self.limit -= n;
+        Ok(n)
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        if buf.len() > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "failed to write whole buffer",
+            ));
+        }
+        self.inner.write_all(buf)?;
+        self.limit -= buf.len();
+        Ok(())
+    }
+
+    fn write_from_bytes(&mut self, buf: &[u8], bit_offset: usize, nbits: usize) -> io::Result<()> {
+        if nbits > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "failed to write whole buffer",
+            ));
+        }
+        self.inner.write_from_bytes(buf, bit_offset, nbits)?;
+        self.limit -= nbits;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ux::u3;
+
+    use super::*;
+    use crate::{bit_cursor::BitCursor, bit_write_exts::BitWriteExts, bitvec};
+
+    #[test]
+    fn test_limit() {
+        let mut cursor = BitCursor::new(bitvec!(0; 8));
+        let mut limit = cursor.by_ref().limit(5);
+        limit.write_u3(u3::new(0b101)).unwrap();
+        assert_eq!(limit.remaining(), 2);
+        assert!(limit.write_u3(u3::new(0b111)).is_err());
+        limit.write_u1(ux::u1::new(1)).unwrap();
+        assert_eq!(cursor.position(), 4);
+        assert_eq!(cursor.into_inner(), bitvec!(1, 0, 1, 1, 0, 0, 0, 0));
+    }
 }
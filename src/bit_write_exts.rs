@@ -1,11 +1,26 @@
+use paste::paste;
 use ux::*;
 
-use crate::{bit_traits::BitTraits, bit_write::BitWrite, byte_order::ByteOrder};
+#[cfg(feature = "alloc")]
+use crate::text::{self, Charset, Framing};
+use crate::{
+    bit_traits::BitTraits,
+    bit_write::BitWrite,
+    byte_order::{BigEndian, ByteOrder, Endianness, LittleEndian, MiddleEndian, WordSwapped},
+    encoding::{
+        check_width, encode_bcd, encode_gray, encode_ones_complement, encode_sign_magnitude,
+    },
+    error::B3Result,
+    fixed::Fixed,
+    float::f32_to_f16_bits,
+    io,
+    util::write_bits_msb_first,
+};
 
 fn bit_write_exts_helper<T: BitTraits, const N: usize, U: BitWrite + ?Sized>(
     buf: &mut U,
     mut value: T,
-) -> std::io::Result<()> {
+) -> io::Result<()> {
     let mut arr = [u1::default(); N];
     let index_offset = N - 1;
     for i in 0..N {
@@ -21,194 +36,543 @@ fn bit_write_exts_helper<T: BitTraits, const N: usize, U: BitWrite + ?Sized>(
     buf.write_all(&arr)
 }
 
+/// Define `write_<name>_with` methods, which take the byte order as an [`Endianness`] at
+/// runtime rather than as a type parameter.
+macro_rules! impl_write_with {
+    ($($name:ident: $type:ty),* $(,)?) => {
+        paste! {
+            $(
+                #[doc = concat!("Like `write_", stringify!($name), "`, with the byte order chosen at runtime")]
+                fn [<write_ $name _with>](&mut self, endianness: Endianness, value: $type) -> io::Result<()> {
+                    match endianness {
+                        Endianness::Big => self.[<write_ $name>]::<BigEndian>(value),
+                        Endianness::Little => self.[<write_ $name>]::<LittleEndian>(value),
+                        Endianness::WordSwapped => self.[<write_ $name>]::<WordSwapped>(value),
+                        Endianness::MiddleEndian => self.[<write_ $name>]::<MiddleEndian>(value),
+                    }
+                }
+            )*
+        }
+    };
+}
+
 /// A trait which extends BitWrite to add explicit write methods for all uX types.
 pub trait BitWriteExts: BitWrite {
-    fn write_bool(&mut self, value: bool) -> std::io::Result<()> {
+    fn write_bool(&mut self, value: bool) -> io::Result<()> {
         self.write_u1(value.into())
     }
 
-    fn write_u1(&mut self, value: u1) -> std::io::Result<()> {
+    fn write_u1(&mut self, value: u1) -> io::Result<()> {
         self.write_all(&[value])
     }
 
-    fn write_u2(&mut self, value: u2) -> std::io::Result<()> {
+    fn write_u2(&mut self, value: u2) -> io::Result<()> {
         bit_write_exts_helper::<u2, 2, Self>(self, value)
     }
 
-    fn write_u3(&mut self, value: u3) -> std::io::Result<()> {
+    fn write_u3(&mut self, value: u3) -> io::Result<()> {
         bit_write_exts_helper::<u3, 3, Self>(self, value)
     }
 
-    fn write_u4(&mut self, value: u4) -> std::io::Result<()> {
+    fn write_u4(&mut self, value: u4) -> io::Result<()> {
         bit_write_exts_helper::<u4, 4, Self>(self, value)
     }
 
-    fn write_u5(&mut self, value: u5) -> std::io::Result<()> {
+    fn write_u5(&mut self, value: u5) -> io::Result<()> {
         bit_write_exts_helper::<u5, 5, Self>(self, value)
     }
 
-    fn write_u6(&mut self, value: u6) -> std::io::Result<()> {
+    fn write_u6(&mut self, value: u6) -> io::Result<()> {
         bit_write_exts_helper::<u6, 6, Self>(self, value)
     }
 
-    fn write_u7(&mut self, value: u7) -> std::io::Result<()> {
+    fn write_u7(&mut self, value: u7) -> io::Result<()> {
         bit_write_exts_helper::<u7, 7, Self>(self, value)
     }
 
-    fn write_u8(&mut self, value: u8) -> std::io::Result<()> {
+    fn write_u8(&mut self, value: u8) -> io::Result<()> {
         bit_write_exts_helper::<u8, 8, Self>(self, value)
     }
 
-    fn write_u9<T: ByteOrder>(&mut self, value: u9) -> std::io::Result<()> {
+    fn write_u9<T: ByteOrder>(&mut self, value: u9) -> io::Result<()> {
         let mut arr = [u1::default(); 9];
         T::write_u9(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u10<T: ByteOrder>(&mut self, value: u10) -> std::io::Result<()> {
+    fn write_u10<T: ByteOrder>(&mut self, value: u10) -> io::Result<()> {
         let mut arr = [u1::default(); 10];
         T::write_u10(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u11<T: ByteOrder>(&mut self, value: u11) -> std::io::Result<()> {
+    fn write_u11<T: ByteOrder>(&mut self, value: u11) -> io::Result<()> {
         let mut arr = [u1::default(); 11];
         T::write_u11(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u12<T: ByteOrder>(&mut self, value: u12) -> std::io::Result<()> {
+    fn write_u12<T: ByteOrder>(&mut self, value: u12) -> io::Result<()> {
         let mut arr = [u1::default(); 12];
         T::write_u12(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u13<T: ByteOrder>(&mut self, value: u13) -> std::io::Result<()> {
+    fn write_u13<T: ByteOrder>(&mut self, value: u13) -> io::Result<()> {
         let mut arr = [u1::default(); 13];
         T::write_u13(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u14<T: ByteOrder>(&mut self, value: u14) -> std::io::Result<()> {
+    fn write_u14<T: ByteOrder>(&mut self, value: u14) -> io::Result<()> {
         let mut arr = [u1::default(); 14];
         T::write_u14(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u15<T: ByteOrder>(&mut self, value: u15) -> std::io::Result<()> {
+    fn write_u15<T: ByteOrder>(&mut self, value: u15) -> io::Result<()> {
         let mut arr = [u1::default(); 15];
         T::write_u15(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u16<T: ByteOrder>(&mut self, value: u16) -> std::io::Result<()> {
+    fn write_u16<T: ByteOrder>(&mut self, value: u16) -> io::Result<()> {
         let mut arr = [u1::default(); 16];
         T::write_u16(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u17<T: ByteOrder>(&mut self, value: u17) -> std::io::Result<()> {
+    fn write_u17<T: ByteOrder>(&mut self, value: u17) -> io::Result<()> {
         let mut arr = [u1::default(); 17];
         T::write_u17(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u18<T: ByteOrder>(&mut self, value: u18) -> std::io::Result<()> {
+    fn write_u18<T: ByteOrder>(&mut self, value: u18) -> io::Result<()> {
         let mut arr = [u1::default(); 18];
         T::write_u18(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u19<T: ByteOrder>(&mut self, value: u19) -> std::io::Result<()> {
+    fn write_u19<T: ByteOrder>(&mut self, value: u19) -> io::Result<()> {
         let mut arr = [u1::default(); 19];
         T::write_u19(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u20<T: ByteOrder>(&mut self, value: u20) -> std::io::Result<()> {
+    fn write_u20<T: ByteOrder>(&mut self, value: u20) -> io::Result<()> {
         let mut arr = [u1::default(); 20];
         T::write_u20(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u21<T: ByteOrder>(&mut self, value: u21) -> std::io::Result<()> {
+    fn write_u21<T: ByteOrder>(&mut self, value: u21) -> io::Result<()> {
         let mut arr = [u1::default(); 21];
         T::write_u21(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u22<T: ByteOrder>(&mut self, value: u22) -> std::io::Result<()> {
+    fn write_u22<T: ByteOrder>(&mut self, value: u22) -> io::Result<()> {
         let mut arr = [u1::default(); 22];
         T::write_u22(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u23<T: ByteOrder>(&mut self, value: u23) -> std::io::Result<()> {
+    fn write_u23<T: ByteOrder>(&mut self, value: u23) -> io::Result<()> {
         let mut arr = [u1::default(); 23];
         T::write_u23(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u24<T: ByteOrder>(&mut self, value: u24) -> std::io::Result<()> {
+    fn write_u24<T: ByteOrder>(&mut self, value: u24) -> io::Result<()> {
         let mut arr = [u1::default(); 24];
         T::write_u24(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u25<T: ByteOrder>(&mut self, value: u25) -> std::io::Result<()> {
+    fn write_u25<T: ByteOrder>(&mut self, value: u25) -> io::Result<()> {
         let mut arr = [u1::default(); 25];
         T::write_u25(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u26<T: ByteOrder>(&mut self, value: u26) -> std::io::Result<()> {
+    fn write_u26<T: ByteOrder>(&mut self, value: u26) -> io::Result<()> {
         let mut arr = [u1::default(); 26];
         T::write_u26(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u27<T: ByteOrder>(&mut self, value: u27) -> std::io::Result<()> {
+    fn write_u27<T: ByteOrder>(&mut self, value: u27) -> io::Result<()> {
         let mut arr = [u1::default(); 27];
         T::write_u27(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u28<T: ByteOrder>(&mut self, value: u28) -> std::io::Result<()> {
+    fn write_u28<T: ByteOrder>(&mut self, value: u28) -> io::Result<()> {
         let mut arr = [u1::default(); 28];
         T::write_u28(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u29<T: ByteOrder>(&mut self, value: u29) -> std::io::Result<()> {
+    fn write_u29<T: ByteOrder>(&mut self, value: u29) -> io::Result<()> {
         let mut arr = [u1::default(); 29];
         T::write_u29(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u30<T: ByteOrder>(&mut self, value: u30) -> std::io::Result<()> {
+    fn write_u30<T: ByteOrder>(&mut self, value: u30) -> io::Result<()> {
         let mut arr = [u1::default(); 30];
         T::write_u30(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u31<T: ByteOrder>(&mut self, value: u31) -> std::io::Result<()> {
+    fn write_u31<T: ByteOrder>(&mut self, value: u31) -> io::Result<()> {
         let mut arr = [u1::default(); 31];
         T::write_u31(&mut arr, value);
         self.write_all(&arr)
     }
 
-    fn write_u32<T: ByteOrder>(&mut self, value: u32) -> std::io::Result<()> {
+    fn write_u32<T: ByteOrder>(&mut self, value: u32) -> io::Result<()> {
         let mut arr = [u1::default(); 32];
         T::write_u32(&mut arr, value);
         self.write_all(&arr)
     }
+
+    fn write_u33<T: ByteOrder>(&mut self, value: u33) -> io::Result<()> {
+        let mut arr = [u1::default(); 33];
+        T::write_u33(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u34<T: ByteOrder>(&mut self, value: u34) -> io::Result<()> {
+        let mut arr = [u1::default(); 34];
+        T::write_u34(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u35<T: ByteOrder>(&mut self, value: u35) -> io::Result<()> {
+        let mut arr = [u1::default(); 35];
+        T::write_u35(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u36<T: ByteOrder>(&mut self, value: u36) -> io::Result<()> {
+        let mut arr = [u1::default(); 36];
+        T::write_u36(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u37<T: ByteOrder>(&mut self, value: u37) -> io::Result<()> {
+        let mut arr = [u1::default(); 37];
+        T::write_u37(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u38<T: ByteOrder>(&mut self, value: u38) -> io::Result<()> {
+        let mut arr = [u1::default(); 38];
+        T::write_u38(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u39<T: ByteOrder>(&mut self, value: u39) -> io::Result<()> {
+        let mut arr = [u1::default(); 39];
+        T::write_u39(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u40<T: ByteOrder>(&mut self, value: u40) -> io::Result<()> {
+        let mut arr = [u1::default(); 40];
+        T::write_u40(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u41<T: ByteOrder>(&mut self, value: u41) -> io::Result<()> {
+        let mut arr = [u1::default(); 41];
+        T::write_u41(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u42<T: ByteOrder>(&mut self, value: u42) -> io::Result<()> {
+        let mut arr = [u1::default(); 42];
+        T::write_u42(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u43<T: ByteOrder>(&mut self, value: u43) -> io::Result<()> {
+        let mut arr = [u1::default(); 43];
+        T::write_u43(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u44<T: ByteOrder>(&mut self, value: u44) -> io::Result<()> {
+        let mut arr = [u1::default(); 44];
+        T::write_u44(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u45<T: ByteOrder>(&mut self, value: u45) -> io::Result<()> {
+        let mut arr = [u1::default(); 45];
+        T::write_u45(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u46<T: ByteOrder>(&mut self, value: u46) -> io::Result<()> {
+        let mut arr = [u1::default(); 46];
+        T::write_u46(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u47<T: ByteOrder>(&mut self, value: u47) -> io::Result<()> {
+        let mut arr = [u1::default(); 47];
+        T::write_u47(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u48<T: ByteOrder>(&mut self, value: u48) -> io::Result<()> {
+        let mut arr = [u1::default(); 48];
+        T::write_u48(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u49<T: ByteOrder>(&mut self, value: u49) -> io::Result<()> {
+        let mut arr = [u1::default(); 49];
+        T::write_u49(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u50<T: ByteOrder>(&mut self, value: u50) -> io::Result<()> {
+        let mut arr = [u1::default(); 50];
+        T::write_u50(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u51<T: ByteOrder>(&mut self, value: u51) -> io::Result<()> {
+        let mut arr = [u1::default(); 51];
+        T::write_u51(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u52<T: ByteOrder>(&mut self, value: u52) -> io::Result<()> {
+        let mut arr = [u1::default(); 52];
+        T::write_u52(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u53<T: ByteOrder>(&mut self, value: u53) -> io::Result<()> {
+        let mut arr = [u1::default(); 53];
+        T::write_u53(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u54<T: ByteOrder>(&mut self, value: u54) -> io::Result<()> {
+        let mut arr = [u1::default(); 54];
+        T::write_u54(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u55<T: ByteOrder>(&mut self, value: u55) -> io::Result<()> {
+        let mut arr = [u1::default(); 55];
+        T::write_u55(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u56<T: ByteOrder>(&mut self, value: u56) -> io::Result<()> {
+        let mut arr = [u1::default(); 56];
+        T::write_u56(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u57<T: ByteOrder>(&mut self, value: u57) -> io::Result<()> {
+        let mut arr = [u1::default(); 57];
+        T::write_u57(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u58<T: ByteOrder>(&mut self, value: u58) -> io::Result<()> {
+        let mut arr = [u1::default(); 58];
+        T::write_u58(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u59<T: ByteOrder>(&mut self, value: u59) -> io::Result<()> {
+        let mut arr = [u1::default(); 59];
+        T::write_u59(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u60<T: ByteOrder>(&mut self, value: u60) -> io::Result<()> {
+        let mut arr = [u1::default(); 60];
+        T::write_u60(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u61<T: ByteOrder>(&mut self, value: u61) -> io::Result<()> {
+        let mut arr = [u1::default(); 61];
+        T::write_u61(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u62<T: ByteOrder>(&mut self, value: u62) -> io::Result<()> {
+        let mut arr = [u1::default(); 62];
+        T::write_u62(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u63<T: ByteOrder>(&mut self, value: u63) -> io::Result<()> {
+        let mut arr = [u1::default(); 63];
+        T::write_u63(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    fn write_u64<T: ByteOrder>(&mut self, value: u64) -> io::Result<()> {
+        let mut arr = [u1::default(); 64];
+        T::write_u64(&mut arr, value);
+        self.write_all(&arr)
+    }
+
+    /// Write an f32 as an IEEE 754 half precision float, rounding to the nearest half.  Values
+    /// outside of the half range become infinity.
+    fn write_f16<T: ByteOrder>(&mut self, value: f32) -> io::Result<()> {
+        self.write_u16::<T>(f32_to_f16_bits(value))
+    }
+
+    fn write_f32<T: ByteOrder>(&mut self, value: f32) -> io::Result<()> {
+        self.write_u32::<T>(value.to_bits())
+    }
+
+    fn write_f64<T: ByteOrder>(&mut self, value: f64) -> io::Result<()> {
+        self.write_u64::<T>(value.to_bits())
+    }
+
+    /// Write a fixed-point value, MSB first, using the width of its format.  Use
+    /// [`Fixed::from_f64`] to write an f64 in a given format.
+    fn write_fixed(&mut self, value: Fixed) -> io::Result<()> {
+        write_bits_msb_first(self, value.format().width(), value.to_bits())
+    }
+
+    impl_write_with!(
+        u9: u9,
+        u10: u10,
+        u11: u11,
+        u12: u12,
+        u13: u13,
+        u14: u14,
+        u15: u15,
+        u16: u16,
+        u17: u17,
+        u18: u18,
+        u19: u19,
+        u20: u20,
+        u21: u21,
+        u22: u22,
+        u23: u23,
+        u24: u24,
+        u25: u25,
+        u26: u26,
+        u27: u27,
+        u28: u28,
+        u29: u29,
+        u30: u30,
+        u31: u31,
+        u32: u32,
+        u33: u33,
+        u34: u34,
+        u35: u35,
+        u36: u36,
+        u37: u37,
+        u38: u38,
+        u39: u39,
+        u40: u40,
+        u41: u41,
+        u42: u42,
+        u43: u43,
+        u44: u44,
+        u45: u45,
+        u46: u46,
+        u47: u47,
+        u48: u48,
+        u49: u49,
+        u50: u50,
+        u51: u51,
+        u52: u52,
+        u53: u53,
+        u54: u54,
+        u55: u55,
+        u56: u56,
+        u57: u57,
+        u58: u58,
+        u59: u59,
+        u60: u60,
+        u61: u61,
+        u62: u62,
+        u63: u63,
+        u64: u64,
+        f16: f32,
+        f32: f32,
+        f64: f64,
+    );
+
+    /// Write `value` as a `width` bit packed BCD value, or fail with
+    /// [`B3Error::ValueOutOfRange`](crate::error::B3Error::ValueOutOfRange) if it has too many
+    /// digits.  See [`encode_bcd`](crate::encoding::encode_bcd).
+    ///
+    /// This and the other encoded field methods fail with
+    /// [`B3Error::InvalidWidth`](crate::error::B3Error::InvalidWidth), without writing anything,
+    /// if `width` is 0 or greater than 64.
+    fn write_bcd(&mut self, width: usize, value: u64) -> B3Result<()> {
+        check_width(width)?;
+        let bits = encode_bcd(value, width)?;
+        Ok(write_bits_msb_first(self, width, bits)?)
+    }
+
+    /// Write `value` as a `width` bit Gray code, or fail with
+    /// [`B3Error::ValueOutOfRange`](crate::error::B3Error::ValueOutOfRange) if it doesn't fit.
+    fn write_gray(&mut self, width: usize, value: u64) -> B3Result<()> {
+        check_width(width)?;
+        let bits = encode_gray(value, width)?;
+        Ok(write_bits_msb_first(self, width, bits)?)
+    }
+
+    /// Write `value` as a `width` bit sign-magnitude value, or fail with
+    /// [`B3Error::ValueOutOfRange`](crate::error::B3Error::ValueOutOfRange) if it doesn't fit.
+    fn write_sign_magnitude(&mut self, width: usize, value: i64) -> B3Result<()> {
+        check_width(width)?;
+        let bits = encode_sign_magnitude(value, width)?;
+        Ok(write_bits_msb_first(self, width, bits)?)
+    }
+
+    /// Write `value` as a `width` bit one's complement value, or fail with
+    /// [`B3Error::ValueOutOfRange`](crate::error::B3Error::ValueOutOfRange) if it doesn't fit.
+    fn write_ones_complement(&mut self, width: usize, value: i64) -> B3Result<()> {
+        check_width(width)?;
+        let bits = encode_ones_complement(value, width)?;
+        Ok(write_bits_msb_first(self, width, bits)?)
+    }
+
+    /// Write a string as codes from the given charset, framed as described by `framing`.
+    #[cfg(feature = "alloc")]
+    fn write_string<C: Charset + ?Sized>(
+        &mut self,
+        charset: &C,
+        framing: Framing,
+        s: &str,
+    ) -> B3Result<()> {
+        text::write_string(self, charset, framing, s)
+    }
 }
 
 impl<T> BitWriteExts for T where T: BitWrite {}
 
 #[cfg(test)]
 mod tests {
-    use crate::{bit_cursor::BitCursor, bitvec, byte_order::NetworkOrder};
+    use crate::{
+        bit_cursor::BitCursor,
+        bit_read_exts::BitReadExts,
+        bitvec,
+        byte_order::{Endianness, LittleEndian, NativeEndian, NetworkOrder},
+        error::B3Error,
+        fixed::QFormat,
+    };
 
     use super::*;
 
@@ -230,4 +594,86 @@ mod tests {
             bitvec!(1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0)
         );
     }
+
+    #[test]
+    fn test_write_floats() {
+        let mut cursor = BitCursor::new(bitvec!(0; 3 + 16 + 32 + 64));
+        cursor.write_u3(u3::new(0b101)).unwrap();
+        cursor.write_f16::<NetworkOrder>(-2.0).unwrap();
+        cursor.write_f32::<LittleEndian>(1.5).unwrap();
+        cursor.write_f64::<NetworkOrder>(-0.1).unwrap();
+
+        let mut cursor = BitCursor::new(cursor.into_inner());
+        cursor.read_u3().unwrap();
+        assert_eq!(cursor.read_u16::<NetworkOrder>().unwrap(), 0xc000);
+        assert_eq!(
+            cursor.read_u32::<NetworkOrder>().unwrap(),
+            u32::from_be_bytes(1.5f32.to_le_bytes())
+        );
+        assert_eq!(cursor.read_f64::<NetworkOrder>().unwrap(), -0.1);
+    }
+
+    #[test]
+    fn test_write_fixed() {
+        let mut cursor = BitCursor::new(bitvec!(0; 8));
+        let value = Fixed::from_f64(QFormat::signed(2, 3), -1.375).unwrap();
+        cursor.write_fixed(value).unwrap();
+        let value = Fixed::from_f64(QFormat::unsigned(0, 3), 0.625).unwrap();
+        cursor.write_fixed(value).unwrap();
+        assert_eq!(cursor.into_inner(), bitvec!(1, 0, 1, 0, 1, 1, 0, 1));
+    }
+
+    #[test]
+    fn test_write_encodings() {
+        let mut cursor = BitCursor::new(bitvec!(0; 17));
+        cursor.write_bcd(8, 59).unwrap();
+        cursor.write_gray(3, 4).unwrap();
+        cursor.write_sign_magnitude(3, -3).unwrap();
+        cursor.write_ones_complement(3, -3).unwrap();
+        assert_eq!(
+            cursor.into_inner(),
+            bitvec!(0, 1, 0, 1, 1, 0, 0, 1, 1, 1, 0, 1, 1, 1, 1, 0, 0)
+        );
+
+        // Invalid values are rejected without writing anything
+        let mut cursor = BitCursor::new(bitvec!(0; 8));
+        assert_eq!(
+            cursor.write_bcd(8, 100),
+            Err(B3Error::ValueOutOfRange {
+                value: 100,
+                bits: 8
+            })
+        );
+        for width in [0, 65] {
+            let invalid = Err(B3Error::InvalidWidth {
+                bits: width,
+                max: 64,
+            });
+            assert_eq!(cursor.write_bcd(width, 0), invalid);
+            assert_eq!(cursor.write_gray(width, 0), invalid);
+            assert_eq!(cursor.write_sign_magnitude(width, 0), invalid);
+            assert_eq!(cursor.write_ones_complement(width, 0), invalid);
+        }
+        assert_eq!(cursor.bits_remaining(), 8);
+    }
+
+    #[test]
+    fn test_write_with() {
+        let mut cursor = BitCursor::new(bitvec!(0; 48));
+        cursor
+            .write_u24_with(Endianness::Big, u24::new(0x010203))
+            .unwrap();
+        cursor
+            .write_u24_with(Endianness::NATIVE, u24::new(0x010203))
+            .unwrap();
+        let mut cursor = BitCursor::new(cursor.into_inner());
+        assert_eq!(
+            cursor.read_u24::<NetworkOrder>().unwrap(),
+            u24::new(0x010203)
+        );
+        assert_eq!(
+            cursor.read_u24::<NativeEndian>().unwrap(),
+            u24::new(0x010203)
+        );
+    }
 }
@@ -3,88 +3,93 @@ use ux::*;
 
 use crate::bit_traits::BitTraits;
 
-/// Generate a LittleEndian read operation from a buffer for the uX types
-macro_rules! impl_read_le {
-    ($type:ty, $size_bits:expr) => {
-        paste! {
-            fn [<read_ $type>](buf: &[u1; $size_bits]) -> $type {
-                let mut val = <$type>::default();
-                if $size_bits > 32 {
-                    unimplemented!("Only uX types up to u32 supported");
-                }
-                if $size_bits > 24 {
-                    for i in 24..std::cmp::min($size_bits, 32) {
-                        val <<= 1;
-                        val |= <ux::u1 as Into<$type>>::into(buf[i]);
-                    }
-                }
-                if $size_bits > 16 {
-                    for i in 16..std::cmp::min($size_bits, 24) {
-                        val <<= 1;
-                        val |= <ux::u1 as Into<$type>>::into(buf[i]);
-                    }
-                }
-                if $size_bits > 8 {
-                    for i in 8..std::cmp::min($size_bits, 16) {
-                        val <<= 1;
-                        val |= <ux::u1 as Into<$type>>::into(buf[i]);
-                    }
-                }
-                for i in 0..std::cmp::min($size_bits, 8) {
-                    val <<= 1;
-                    val |= <ux::u1 as Into<$type>>::into(buf[i]);
-                }
-                val
-            }
-        }
-    };
+/// Read a value from |buf|, where the bit of significance |s| (counting from the LSB) in an
+/// |n| bit value is at index |bit_index(n, s)|
+fn read_mapped<T: BitTraits, const N: usize>(
+    buf: &[u1; N],
+    bit_index: fn(usize, usize) -> usize,
+) -> T {
+    let mut val = T::default();
+    for s in (0..N).rev() {
+        val <<= 1;
+        val |= buf[bit_index(N, s)].into();
+    }
+    val
 }
 
-macro_rules! impl_write_le {
-    ($type:ty, $size_bits:expr) => {
-        paste! {
-            fn [<write_ $type>](buf: &mut [u1; $size_bits], value: $type) {
-                let mut mask = $type::ONE << std::cmp::min($size_bits - 1, 7);
-                for i in 0..std::cmp::min($size_bits, 8) {
-                    if value & mask != $type::ZERO {
-                        buf[i] = u1::new(1);
-                    }
-                    mask >>= 1;
-                }
-                if $size_bits > 8 {
-                    let mut mask = $type::ONE << std::cmp::min($size_bits - 1, 15);
-                    for i in 8..std::cmp::min($size_bits, 16) {
-                        if value & mask != $type::ZERO {
-                            buf[i] = u1::new(1);
-                        }
-                        mask >>= 1;
-                    }
-                }
-                if $size_bits > 16 {
-                    let mut mask = $type::ONE << std::cmp::min($size_bits - 1, 23);
-                    for i in 16..std::cmp::min($size_bits, 24) {
-                        if value & mask != $type::ZERO {
-                            buf[i] = u1::new(1);
-                        }
-                        mask >>= 1;
+/// Write |value| to |buf|, where the bit of significance |s| (counting from the LSB) in an |n|
+/// bit value goes to index |bit_index(n, s)|
+fn write_mapped<T: BitTraits, const N: usize>(
+    buf: &mut [u1; N],
+    mut value: T,
+    bit_index: fn(usize, usize) -> usize,
+) {
+    for s in 0..N {
+        buf[bit_index(N, s)] = if value & T::ONE == T::ONE {
+            u1::new(1)
+        } else {
+            u1::new(0)
+        };
+        value >>= 1;
+    }
+}
+
+fn be_bit_index(n: usize, s: usize) -> usize {
+    n - 1 - s
+}
+
+/// Bytes are least significant first, and each byte is MSB first.  If |n| isn't a multiple of
+/// 8, the last (most significant) byte is the short one.
+fn le_bit_index(n: usize, s: usize) -> usize {
+    let byte = s / 8;
+    let byte_bits = core::cmp::min(8, n - 8 * byte);
+    8 * byte + be_bit_index(byte_bits, s % 8)
+}
+
+/// 16 bit words are least significant first, and each word is big endian.  If |n| isn't a
+/// multiple of 16, the last (most significant) word is the short one.
+fn word_swapped_bit_index(n: usize, s: usize) -> usize {
+    let word = s / 16;
+    let word_bits = core::cmp::min(16, n - 16 * word);
+    16 * word + be_bit_index(word_bits, s % 16)
+}
+
+/// 16 bit words are most significant first, and each word is little endian.  If |n| isn't a
+/// multiple of 16, the first (most significant) word is the short one.
+fn middle_endian_bit_index(n: usize, s: usize) -> usize {
+    let word = s / 16;
+    let word_bits = core::cmp::min(16, n - 16 * word);
+    let word_offset = n - 16 * word - word_bits;
+    word_offset + le_bit_index(word_bits, s % 16)
+}
+
+/// Implement ByteOrder for |$order| using read_mapped and write_mapped with the given
+/// |$bit_index| function
+macro_rules! impl_byte_order_mapped {
+    ($order:ty, $bit_index:ident) => {
+        impl_byte_order_mapped!(
+            $order,
+            $bit_index,
+            u9, u10, u11, u12, u13, u14, u15, u16, u17, u18, u19, u20, u21, u22, u23, u24, u25, u26, u27, u28, u29, u30, u31, u32, u33, u34, u35, u36, u37, u38, u39, u40, u41, u42, u43, u44, u45, u46, u47, u48, u49, u50, u51, u52, u53, u54, u55, u56, u57, u58, u59, u60, u61, u62, u63, u64
+        );
+    };
+    ($order:ty, $bit_index:ident, $($type:ident),*) => {
+        impl ByteOrder for $order {
+            paste! {
+                $(
+                    fn [<read_ $type>](buf: &[u1; <$type as BitTraits>::BITS]) -> $type {
+                        read_mapped(buf, $bit_index)
                     }
-                }
-                if $size_bits > 24 {
-                    let mut mask = $type::ONE << std::cmp::min($size_bits - 1, 31);
-                    for i in 24..std::cmp::min($size_bits, 32) {
-                        if value & mask != $type::ZERO {
-                            buf[i] = u1::new(1);
-                        }
-                        mask >>= 1;
+
+                    fn [<write_ $type>](buf: &mut [u1; <$type as BitTraits>::BITS], value: $type) {
+                        write_mapped(buf, value, $bit_index)
                     }
-                }
+                )*
             }
         }
     };
 }
 
-// TODO: define write methods
-
 pub trait ByteOrder {
     fn read_u9(buf: &[u1; 9]) -> u9;
     fn read_u10(buf: &[u1; 10]) -> u10;
@@ -110,6 +115,38 @@ pub trait ByteOrder {
     fn read_u30(buf: &[u1; 30]) -> u30;
     fn read_u31(buf: &[u1; 31]) -> u31;
     fn read_u32(buf: &[u1; 32]) -> u32;
+    fn read_u33(buf: &[u1; 33]) -> u33;
+    fn read_u34(buf: &[u1; 34]) -> u34;
+    fn read_u35(buf: &[u1; 35]) -> u35;
+    fn read_u36(buf: &[u1; 36]) -> u36;
+    fn read_u37(buf: &[u1; 37]) -> u37;
+    fn read_u38(buf: &[u1; 38]) -> u38;
+    fn read_u39(buf: &[u1; 39]) -> u39;
+    fn read_u40(buf: &[u1; 40]) -> u40;
+    fn read_u41(buf: &[u1; 41]) -> u41;
+    fn read_u42(buf: &[u1; 42]) -> u42;
+    fn read_u43(buf: &[u1; 43]) -> u43;
+    fn read_u44(buf: &[u1; 44]) -> u44;
+    fn read_u45(buf: &[u1; 45]) -> u45;
+    fn read_u46(buf: &[u1; 46]) -> u46;
+    fn read_u47(buf: &[u1; 47]) -> u47;
+    fn read_u48(buf: &[u1; 48]) -> u48;
+    fn read_u49(buf: &[u1; 49]) -> u49;
+    fn read_u50(buf: &[u1; 50]) -> u50;
+    fn read_u51(buf: &[u1; 51]) -> u51;
+    fn read_u52(buf: &[u1; 52]) -> u52;
+    fn read_u53(buf: &[u1; 53]) -> u53;
+    fn read_u54(buf: &[u1; 54]) -> u54;
+    fn read_u55(buf: &[u1; 55]) -> u55;
+    fn read_u56(buf: &[u1; 56]) -> u56;
+    fn read_u57(buf: &[u1; 57]) -> u57;
+    fn read_u58(buf: &[u1; 58]) -> u58;
+    fn read_u59(buf: &[u1; 59]) -> u59;
+    fn read_u60(buf: &[u1; 60]) -> u60;
+    fn read_u61(buf: &[u1; 61]) -> u61;
+    fn read_u62(buf: &[u1; 62]) -> u62;
+    fn read_u63(buf: &[u1; 63]) -> u63;
+    fn read_u64(buf: &[u1; 64]) -> u64;
 
     fn write_u9(buf: &mut [u1; 9], value: u9);
     fn write_u10(buf: &mut [u1; 10], value: u10);
@@ -135,66 +172,89 @@ pub trait ByteOrder {
     fn write_u30(buf: &mut [u1; 30], value: u30);
     fn write_u31(buf: &mut [u1; 31], value: u31);
     fn write_u32(buf: &mut [u1; 32], value: u32);
+    fn write_u33(buf: &mut [u1; 33], value: u33);
+    fn write_u34(buf: &mut [u1; 34], value: u34);
+    fn write_u35(buf: &mut [u1; 35], value: u35);
+    fn write_u36(buf: &mut [u1; 36], value: u36);
+    fn write_u37(buf: &mut [u1; 37], value: u37);
+    fn write_u38(buf: &mut [u1; 38], value: u38);
+    fn write_u39(buf: &mut [u1; 39], value: u39);
+    fn write_u40(buf: &mut [u1; 40], value: u40);
+    fn write_u41(buf: &mut [u1; 41], value: u41);
+    fn write_u42(buf: &mut [u1; 42], value: u42);
+    fn write_u43(buf: &mut [u1; 43], value: u43);
+    fn write_u44(buf: &mut [u1; 44], value: u44);
+    fn write_u45(buf: &mut [u1; 45], value: u45);
+    fn write_u46(buf: &mut [u1; 46], value: u46);
+    fn write_u47(buf: &mut [u1; 47], value: u47);
+    fn write_u48(buf: &mut [u1; 48], value: u48);
+    fn write_u49(buf: &mut [u1; 49], value: u49);
+    fn write_u50(buf: &mut [u1; 50], value: u50);
+    fn write_u51(buf: &mut [u1; 51], value: u51);
+    fn write_u52(buf: &mut [u1; 52], value: u52);
+    fn write_u53(buf: &mut [u1; 53], value: u53);
+    fn write_u54(buf: &mut [u1; 54], value: u54);
+    fn write_u55(buf: &mut [u1; 55], value: u55);
+    fn write_u56(buf: &mut [u1; 56], value: u56);
+    fn write_u57(buf: &mut [u1; 57], value: u57);
+    fn write_u58(buf: &mut [u1; 58], value: u58);
+    fn write_u59(buf: &mut [u1; 59], value: u59);
+    fn write_u60(buf: &mut [u1; 60], value: u60);
+    fn write_u61(buf: &mut [u1; 61], value: u61);
+    fn write_u62(buf: &mut [u1; 62], value: u62);
+    fn write_u63(buf: &mut [u1; 63], value: u63);
+    fn write_u64(buf: &mut [u1; 64], value: u64);
 }
 
 pub struct BigEndian {}
 
 pub struct LittleEndian {}
 
+/// 16 bit words in little endian order, with big endian bytes in each word: 0x0A0B0C0D is laid
+/// out as 0C 0D 0A 0B.  This is the "CDAB" order used by many Modbus devices for 32 bit values
+/// spread over two registers.
+pub struct WordSwapped {}
+
+/// 16 bit words in big endian order, with little endian bytes in each word: 0x0A0B0C0D is laid
+/// out as 0B 0A 0D 0C.  This is the "BADC" order used by the PDP-11.
+pub struct MiddleEndian {}
+
+pub type PdpEndian = MiddleEndian;
+
 pub type NetworkOrder = BigEndian;
 
-impl ByteOrder for LittleEndian {
-    impl_read_le!(u9, 9);
-    impl_read_le!(u10, 10);
-    impl_read_le!(u11, 11);
-    impl_read_le!(u12, 12);
-    impl_read_le!(u13, 13);
-    impl_read_le!(u14, 14);
-    impl_read_le!(u15, 15);
-    impl_read_le!(u16, 16);
-    impl_read_le!(u17, 17);
-    impl_read_le!(u18, 18);
-    impl_read_le!(u19, 19);
-    impl_read_le!(u20, 20);
-    impl_read_le!(u21, 21);
-    impl_read_le!(u22, 22);
-    impl_read_le!(u23, 23);
-    impl_read_le!(u24, 24);
-    impl_read_le!(u25, 25);
-    impl_read_le!(u26, 26);
-    impl_read_le!(u27, 27);
-    impl_read_le!(u28, 28);
-    impl_read_le!(u29, 29);
-    impl_read_le!(u30, 30);
-    impl_read_le!(u31, 31);
-    impl_read_le!(u32, 32);
-
-    impl_write_le!(u9, 9);
-    impl_write_le!(u10, 10);
-    impl_write_le!(u11, 11);
-    impl_write_le!(u12, 12);
-    impl_write_le!(u13, 13);
-    impl_write_le!(u14, 14);
-    impl_write_le!(u15, 15);
-    impl_write_le!(u16, 16);
-    impl_write_le!(u17, 17);
-    impl_write_le!(u18, 18);
-    impl_write_le!(u19, 19);
-    impl_write_le!(u20, 20);
-    impl_write_le!(u21, 21);
-    impl_write_le!(u22, 22);
-    impl_write_le!(u23, 23);
-    impl_write_le!(u24, 24);
-    impl_write_le!(u25, 25);
-    impl_write_le!(u26, 26);
-    impl_write_le!(u27, 27);
-    impl_write_le!(u28, 28);
-    impl_write_le!(u29, 29);
-    impl_write_le!(u30, 30);
-    impl_write_le!(u31, 31);
-    impl_write_le!(u32, 32);
+/// The byte order of the target platform
+#[cfg(target_endian = "little")]
+pub type NativeEndian = LittleEndian;
+/// The byte order of the target platform
+#[cfg(target_endian = "big")]
+pub type NativeEndian = BigEndian;
+
+/// A byte order chosen at runtime, e.g. from a flag in a header.  The `_with` methods on
+/// [`BitReadExts`](crate::bit_read_exts::BitReadExts) and
+/// [`BitWriteExts`](crate::bit_write_exts::BitWriteExts) take one of these in place of a
+/// [`ByteOrder`] type parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Big,
+    Little,
+    WordSwapped,
+    MiddleEndian,
 }
 
+impl Endianness {
+    /// The byte order of the target platform
+    pub const NATIVE: Endianness = if cfg!(target_endian = "little") {
+        Endianness::Little
+    } else {
+        Endianness::Big
+    };
+}
+
+impl_byte_order_mapped!(LittleEndian, le_bit_index);
+impl_byte_order_mapped!(WordSwapped, word_swapped_bit_index);
+impl_byte_order_mapped!(MiddleEndian, middle_endian_bit_index);
+
 macro_rules! impl_read_be {
     ($type:ty, $size_bits:expr) => {
         paste! {
@@ -250,6 +310,38 @@ impl ByteOrder for BigEndian {
     impl_read_be!(u30, 30);
     impl_read_be!(u31, 31);
     impl_read_be!(u32, 32);
+    impl_read_be!(u33, 33);
+    impl_read_be!(u34, 34);
+    impl_read_be!(u35, 35);
+    impl_read_be!(u36, 36);
+    impl_read_be!(u37, 37);
+    impl_read_be!(u38, 38);
+    impl_read_be!(u39, 39);
+    impl_read_be!(u40, 40);
+    impl_read_be!(u41, 41);
+    impl_read_be!(u42, 42);
+    impl_read_be!(u43, 43);
+    impl_read_be!(u44, 44);
+    impl_read_be!(u45, 45);
+    impl_read_be!(u46, 46);
+    impl_read_be!(u47, 47);
+    impl_read_be!(u48, 48);
+    impl_read_be!(u49, 49);
+    impl_read_be!(u50, 50);
+    impl_read_be!(u51, 51);
+    impl_read_be!(u52, 52);
+    impl_read_be!(u53, 53);
+    impl_read_be!(u54, 54);
+    impl_read_be!(u55, 55);
+    impl_read_be!(u56, 56);
+    impl_read_be!(u57, 57);
+    impl_read_be!(u58, 58);
+    impl_read_be!(u59, 59);
+    impl_read_be!(u60, 60);
+    impl_read_be!(u61, 61);
+    impl_read_be!(u62, 62);
+    impl_read_be!(u63, 63);
+    impl_read_be!(u64, 64);
 
     impl_write_be!(u9, 9);
     impl_write_be!(u10, 10);
@@ -275,6 +367,38 @@ impl ByteOrder for BigEndian {
     impl_write_be!(u30, 30);
     impl_write_be!(u31, 31);
     impl_write_be!(u32, 32);
+    impl_write_be!(u33, 33);
+    impl_write_be!(u34, 34);
+    impl_write_be!(u35, 35);
+    impl_write_be!(u36, 36);
+    impl_write_be!(u37, 37);
+    impl_write_be!(u38, 38);
+    impl_write_be!(u39, 39);
+    impl_write_be!(u40, 40);
+    impl_write_be!(u41, 41);
+    impl_write_be!(u42, 42);
+    impl_write_be!(u43, 43);
+    impl_write_be!(u44, 44);
+    impl_write_be!(u45, 45);
+    impl_write_be!(u46, 46);
+    impl_write_be!(u47, 47);
+    impl_write_be!(u48, 48);
+    impl_write_be!(u49, 49);
+    impl_write_be!(u50, 50);
+    impl_write_be!(u51, 51);
+    impl_write_be!(u52, 52);
+    impl_write_be!(u53, 53);
+    impl_write_be!(u54, 54);
+    impl_write_be!(u55, 55);
+    impl_write_be!(u56, 56);
+    impl_write_be!(u57, 57);
+    impl_write_be!(u58, 58);
+    impl_write_be!(u59, 59);
+    impl_write_be!(u60, 60);
+    impl_write_be!(u61, 61);
+    impl_write_be!(u62, 62);
+    impl_write_be!(u63, 63);
+    impl_write_be!(u64, 64);
 }
 
 #[cfg(test)]
@@ -372,4 +496,82 @@ mod tests {
             )[..]
         );
     }
+
+    #[test]
+    fn test_u64() {
+        let value = 0x0123_4567_89AB_CDEFu64;
+        let mut be_buf = [u1::ZERO; 64];
+        BigEndian::write_u64(&mut be_buf, value);
+        assert_eq!(be_buf[..8], bitarray!(0, 0, 0, 0, 0, 0, 0, 1));
+        assert_eq!(BigEndian::read_u64(&be_buf), value);
+
+        let mut le_buf = [u1::ZERO; 64];
+        LittleEndian::write_u64(&mut le_buf, value);
+        assert_eq!(le_buf[..8], bitarray!(1, 1, 1, 0, 1, 1, 1, 1));
+        assert_eq!(LittleEndian::read_u64(&le_buf), value);
+    }
+
+    /// The first N bits of |bytes|, MSB first
+    fn bits<const N: usize>(bytes: &[u8]) -> [u1; N] {
+        core::array::from_fn(|i| u1::new((bytes[i / 8] >> (7 - i % 8)) & 1))
+    }
+
+    #[test]
+    fn test_word_swapped() {
+        // A Modbus register dump of 123.456f32 (0x42F6E979) in CDAB order: [0xE979, 0x42F6]
+        let buf = bits::<32>(&[0xE9, 0x79, 0x42, 0xF6]);
+        assert_eq!(WordSwapped::read_u32(&buf), 0x42F6E979);
+        let mut out = [u1::ZERO; 32];
+        WordSwapped::write_u32(&mut out, 0x42F6E979);
+        assert_eq!(out, buf);
+
+        // Four registers: [0x0708, 0x0506, 0x0304, 0x0102]
+        let buf = bits::<64>(&[0x07, 0x08, 0x05, 0x06, 0x03, 0x04, 0x01, 0x02]);
+        assert_eq!(WordSwapped::read_u64(&buf), 0x0102030405060708);
+
+        // The most significant word is the short one, and comes last
+        let buf = bits::<24>(&[0xCD, 0xEF, 0xAB]);
+        assert_eq!(WordSwapped::read_u24(&buf), u24::new(0xABCDEF));
+        let buf = bits::<17>(&[0x80, 0x01, 0x80]);
+        assert_eq!(WordSwapped::read_u17(&buf), u17::new(0x1_8001));
+    }
+
+    #[test]
+    fn test_middle_endian() {
+        // PDP-11 layout of 0x0A0B0C0D
+        let buf = bits::<32>(&[0x0B, 0x0A, 0x0D, 0x0C]);
+        assert_eq!(MiddleEndian::read_u32(&buf), 0x0A0B0C0D);
+        let mut out = [u1::ZERO; 32];
+        MiddleEndian::write_u32(&mut out, 0x0A0B0C0D);
+        assert_eq!(out, buf);
+
+        let buf = bits::<64>(&[0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x08, 0x07]);
+        assert_eq!(PdpEndian::read_u64(&buf), 0x0102030405060708);
+
+        // The most significant word is the short one, and comes first
+        let buf = bits::<24>(&[0xAB, 0xEF, 0xCD]);
+        assert_eq!(MiddleEndian::read_u24(&buf), u24::new(0xABCDEF));
+    }
+
+    #[test]
+    fn test_round_trip_wide() {
+        fn check<T: ByteOrder>() {
+            for value in [0, 1, 0x1234_5678_9ABC, u48::MAX.into()] {
+                let value = u48::new(value);
+                let mut buf = [u1::ZERO; 48];
+                T::write_u48(&mut buf, value);
+                assert_eq!(T::read_u48(&buf), value);
+            }
+            for value in [0, 0x7FFF_0000_1234_5678, u63::MAX.into()] {
+                let value = u63::new(value);
+                let mut buf = [u1::ZERO; 63];
+                T::write_u63(&mut buf, value);
+                assert_eq!(T::read_u63(&buf), value);
+            }
+        }
+        check::<BigEndian>();
+        check::<LittleEndian>();
+        check::<WordSwapped>();
+        check::<MiddleEndian>();
+    }
 }
@@ -0,0 +1,356 @@
+//! Cyclic redundancy checks over bit streams of any length.
+//!
+//! [`CrcParams`] describes a CRC using the parameters of the "Rocksoft" model (as catalogued
+//! by [reveng](https://reveng.sourceforge.io/crc-catalogue/)), and [`Crc`] computes it
+//! incrementally from bytes, [`BitSlice`]s or any [`BitRead`] source.
+//!
+//! Byte input is fed LSB first when the algorithm reflects its input (`refin`), and MSB first
+//! otherwise, so it matches the usual byte oriented implementations.  Bit input (from a
+//! [`BitSlice`] or [`BitRead`]) is fed in order, as the stream of bits the CRC covers, e.g. in
+//! the order the bits were sent on the wire.  `refin` doesn't apply to it.
+//!
+//! # Example
+//! ```
+//! use b3::{
+//!     bitvec,
+//!     crc::{Crc, CRC_15_CAN, CRC_32_ISO_HDLC},
+//! };
+//!
+//! assert_eq!(CRC_32_ISO_HDLC.checksum(b"123456789"), 0xCBF43926);
+//!
+//! // A CAN frame's CRC covers a bit count which isn't a whole number of bytes
+//! let frame = bitvec!(0, 1, 1, 0, 0, 1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 1, 0, 1);
+//! let mut crc = Crc::new(CRC_15_CAN);
+//! crc.update_bits(&frame.get_slice(..).unwrap());
+//! assert_eq!(crc.finalize(), 0x0FDD);
+//! ```
+
+use ux::u1;
+
+use crate::{bit_read::BitRead, io, slice::BitSlice};
+
+/// The parameters of a CRC algorithm.  `poly`, `init` and `xorout` are given in their
+/// unreflected form, as in the reveng catalogue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CrcParams {
+    /// The width of the CRC, in bits (1 to 64)
+    pub width: usize,
+    /// The generator polynomial, without its leading term
+    pub poly: u64,
+    /// The initial value of the register
+    pub init: u64,
+    /// Whether input bytes are fed LSB first
+    pub refin: bool,
+    /// Whether the register is reflected before `xorout` is applied
+    pub refout: bool,
+    /// The value XORed with the final register
+    pub xorout: u64,
+    /// The CRC of the ASCII string "123456789"
+    pub check: u64,
+}
+
+impl CrcParams {
+    /// Compute the CRC of `bytes`.
+    pub fn checksum(&self, bytes: &[u8]) -> u64 {
+        let mut crc = Crc::new(*self);
+        crc.update(bytes);
+        crc.finalize()
+    }
+
+    /// Compute the CRC of the bits in `bits`.
+    pub fn checksum_bits(&self, bits: &BitSlice<'_>) -> u64 {
+        let mut crc = Crc::new(*self);
+        crc.update_bits(bits);
+        crc.finalize()
+    }
+}
+
+/// A CRC computation in progress.
+#[derive(Clone, Debug)]
+pub struct Crc {
+    params: CrcParams,
+    /// `params.poly`, masked to the width of the CRC
+    poly: u64,
+    register: u64,
+}
+
+impl Crc {
+    /// Panics if the width of `params` isn't between 1 and 64.  Any bits of `poly` above the
+    /// width (e.g. a leading term written out explicitly) are ignored.
+    pub fn new(params: CrcParams) -> Self {
+        assert!(
+            params.width > 0 && params.width <= 64,
+            "CRC width must be 1 to 64 bits"
+        );
+        Crc {
+            params,
+            poly: params.poly & Crc::mask(params.width),
+            register: params.init & Crc::mask(params.width),
+        }
+    }
+
+    fn mask(width: usize) -> u64 {
+        u64::MAX >> (64 - width)
+    }
+
+    pub fn params(&self) -> &CrcParams {
+        &self.params
+    }
+
+    /// Start over, as if nothing had been fed in.
+    pub fn reset(&mut self) {
+        self.register = self.params.init & Crc::mask(self.params.width);
+    }
+
+    /// Feed a single bit.
+    pub fn update_bit(&mut self, bit: u1) {
+        let width = self.params.width;
+        let top = (self.register >> (width - 1)) & 1;
+        self.register = (self.register << 1) & Crc::mask(width);
+        if top ^ u64::from(bit) == 1 {
+            self.register ^= self.poly;
+        }
+    }
+
+    /// Feed bytes, LSB first if the algorithm reflects its input and MSB first otherwise.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let byte = if self.params.refin {
+                byte.reverse_bits()
+            } else {
+                byte
+            };
+            for i in (0..8).rev() {
+                self.update_bit(u1::new((byte >> i) & 1));
+            }
+        }
+    }
+
+    /// Feed all of the bits in `bits`, in order.
+    pub fn update_bits(&mut self, bits: &BitSlice<'_>) {
+        bits.iter().for_each(|bit| self.update_bit(bit));
+    }
+
+    /// Read exactly `nbits` bits from `reader` and feed them in order.
+    pub fn update_from<R: BitRead + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        nbits: usize,
+    ) -> io::Result<()> {
+        let mut buf = [u1::new(0); 64];
+        let mut remaining = nbits;
+        while remaining > 0 {
+            let chunk = &mut buf[..remaining.min(64)];
+            reader.read_exact(chunk)?;
+            chunk.iter().for_each(|&bit| self.update_bit(bit));
+            remaining -= chunk.len();
+        }
+        Ok(())
+    }
+
+    /// The CRC of everything fed in so far.  More data can still be fed in afterwards.
+    pub fn finalize(&self) -> u64 {
+        let width = self.params.width;
+        let register = if self.params.refout {
+            self.register.reverse_bits() >> (64 - width)
+        } else {
+            self.register
+        };
+        (register ^ self.params.xorout) & Crc::mask(width)
+    }
+}
+
+macro_rules! crc_params {
+    ($(#[$doc:meta])* $name:ident, $width:expr, $poly:expr, $init:expr, $refin:expr, $refout:expr, $xorout:expr, $check:expr) => {
+        $(#[$doc])*
+        pub const $name: CrcParams = CrcParams {
+            width: $width,
+            poly: $poly,
+            init: $init,
+            refin: $refin,
+            refout: $refout,
+            xorout: $xorout,
+            check: $check,
+        };
+    };
+}
+
+crc_params!(
+    /// The GSM control channel CRC
+    CRC_3_GSM, 3, 0x3, 0x0, false, false, 0x7, 0x4
+);
+crc_params!(
+    /// The CRC of USB token packets
+    CRC_5_USB, 5, 0x05, 0x1F, true, true, 0x1F, 0x19
+);
+crc_params!(
+    /// The SMBus packet error code, often just called "CRC-8"
+    CRC_8_SMBUS, 8, 0x07, 0x00, false, false, 0x00, 0xF4
+);
+crc_params!(
+    /// The Bluetooth header error check
+    CRC_8_BLUETOOTH, 8, 0xA7, 0x00, true, true, 0x00, 0x26
+);
+crc_params!(
+    /// The classic CAN frame CRC
+    CRC_15_CAN, 15, 0x4599, 0x0000, false, false, 0x0000, 0x059E
+);
+crc_params!(
+    /// Often called "CRC-16/CCITT-FALSE"
+    CRC_16_IBM_3740, 16, 0x1021, 0xFFFF, false, false, 0x0000, 0x29B1
+);
+crc_params!(
+    /// Often called "CRC-16/CCITT"
+    CRC_16_KERMIT, 16, 0x1021, 0x0000, true, true, 0x0000, 0x2189
+);
+crc_params!(
+    /// The HDLC frame check sequence, also called "CRC-16/X-25"
+    CRC_16_IBM_SDLC, 16, 0x1021, 0xFFFF, true, true, 0xFFFF, 0x906E
+);
+crc_params!(
+    /// Often just called "CRC-16"
+    CRC_16_ARC, 16, 0x8005, 0x0000, true, true, 0x0000, 0xBB3D
+);
+crc_params!(
+    /// The Modbus RTU CRC
+    CRC_16_MODBUS, 16, 0x8005, 0xFFFF, true, true, 0x0000, 0x4B37
+);
+crc_params!(
+    /// The CRC of USB data packets
+    CRC_16_USB, 16, 0x8005, 0xFFFF, true, true, 0xFFFF, 0xB4C8
+);
+crc_params!(
+    /// The CAN FD CRC for frames of up to 16 data bytes
+    CRC_17_CAN_FD, 17, 0x1685B, 0x00000, false, false, 0x00000, 0x04F03
+);
+crc_params!(
+    /// The CAN FD CRC for frames of more than 16 data bytes
+    CRC_21_CAN_FD, 21, 0x102899, 0x000000, false, false, 0x000000, 0x0ED841
+);
+crc_params!(
+    /// The Bluetooth Low Energy link layer CRC.  `init` is the advertising channel value; data
+    /// channels use a value from the connection request instead.
+    CRC_24_BLE, 24, 0x00065B, 0x555555, true, true, 0x000000, 0xC25A56
+);
+crc_params!(
+    /// The OpenPGP armor checksum
+    CRC_24_OPENPGP, 24, 0x864CFB, 0xB704CE, false, false, 0x000000, 0x21CF02
+);
+crc_params!(
+    /// The CRC used by Ethernet, zip, PNG and HDLC, usually just called "CRC-32"
+    CRC_32_ISO_HDLC, 32, 0x04C11DB7, 0xFFFFFFFF, true, true, 0xFFFFFFFF, 0xCBF43926
+);
+crc_params!(
+    /// CRC-32C (Castagnoli), used by iSCSI, SCTP and ext4
+    CRC_32_ISCSI, 32, 0x1EDC6F41, 0xFFFFFFFF, true, true, 0xFFFFFFFF, 0xE3069283
+);
+crc_params!(
+    /// The CRC used by bzip2
+    CRC_32_BZIP2, 32, 0x04C11DB7, 0xFFFFFFFF, false, false, 0xFFFFFFFF, 0xFC891918
+);
+crc_params!(
+    /// The CRC used by xz
+    CRC_64_XZ, 64, 0x42F0E1EBA9EA3693, 0xFFFFFFFFFFFFFFFF, true, true, 0xFFFFFFFFFFFFFFFF,
+    0x995DC9BBDF1939FA
+);
+crc_params!(
+    /// The CRC from ECMA-182
+    CRC_64_ECMA_182, 64, 0x42F0E1EBA9EA3693, 0x0000000000000000, false, false,
+    0x0000000000000000, 0x6C40DF5F0B497347
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bit_cursor::BitCursor, bit_vec::BitVec, bitvec};
+
+    const ALL: [CrcParams; 20] = [
+        CRC_3_GSM,
+        CRC_5_USB,
+        CRC_8_SMBUS,
+        CRC_8_BLUETOOTH,
+        CRC_15_CAN,
+        CRC_16_IBM_3740,
+        CRC_16_KERMIT,
+        CRC_16_IBM_SDLC,
+        CRC_16_ARC,
+        CRC_16_MODBUS,
+        CRC_16_USB,
+        CRC_17_CAN_FD,
+        CRC_21_CAN_FD,
+        CRC_24_BLE,
+        CRC_24_OPENPGP,
+        CRC_32_ISO_HDLC,
+        CRC_32_ISCSI,
+        CRC_32_BZIP2,
+        CRC_64_XZ,
+        CRC_64_ECMA_182,
+    ];
+
+    #[test]
+    fn test_check_values() {
+        for params in ALL {
+            assert_eq!(params.checksum(b"123456789"), params.check, "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn test_bit_sources() {
+        for params in ALL {
+            // Bit sources are fed in order, so reflected algorithms need the bits in each byte
+            // reversed to match the byte oriented check value
+            let mut vec = BitVec::from_vec(b"123456789".to_vec());
+            if params.refin {
+                vec.reverse_bits_in_bytes();
+            }
+            let slice = vec.get_slice(..).unwrap();
+            assert_eq!(params.checksum_bits(&slice), params.check, "{:?}", params);
+
+            let mut crc = Crc::new(params);
+            let mut cursor = BitCursor::new(vec);
+            crc.update_from(&mut cursor, 3).unwrap();
+            crc.update_from(&mut cursor, 69).unwrap();
+            assert_eq!(crc.finalize(), params.check, "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn test_partial_bytes() {
+        // CAN's CRC has no init or xorout, so appending it to the 19 bits it covers gives a
+        // stream whose CRC is 0
+        let mut frame = bitvec!(0, 1, 1, 0, 0, 1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 1, 0, 1);
+        let crc = CRC_15_CAN.checksum_bits(&frame.get_slice(..).unwrap());
+        assert_eq!(crc, 0x0FDD);
+        for i in (0..15).rev() {
+            frame.push(u1::new(((crc >> i) & 1) as u8));
+        }
+        assert_eq!(CRC_15_CAN.checksum_bits(&frame.get_slice(..).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_poly_leading_term() {
+        // A polynomial with its leading term written out gives the same CRC, and the register
+        // never grows past the width
+        let params = CrcParams {
+            poly: 0x1_04C1_1DB7,
+            ..CRC_32_ISO_HDLC
+        };
+        assert_eq!(params.checksum(b"123456789"), 0xCBF43926);
+        let params = CrcParams {
+            poly: 0xFFFF_FFFF_FFFF_FF07,
+            ..CRC_8_SMBUS
+        };
+        assert_eq!(params.checksum(b"123456789"), 0xF4);
+    }
+
+    #[test]
+    fn test_incremental() {
+        let mut crc = Crc::new(CRC_32_ISO_HDLC);
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finalize(), 0xCBF43926);
+        crc.reset();
+        crc.update(b"123456789");
+        assert_eq!(crc.finalize(), 0xCBF43926);
+    }
+}
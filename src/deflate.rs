@@ -0,0 +1,754 @@
+//! DEFLATE (RFC 1951) compressed data.
+//!
+//! [`inflate`] decompresses a stream of stored, fixed Huffman and dynamic Huffman blocks, and
+//! [`deflate`] compresses data into a single fixed Huffman block.  Both work on the bit stream in
+//! the order DEFLATE packs it: bytes are packed LSB first, so read them through a
+//! [`LsbFirstReader`] and write them through a [`LsbFirstWriter`], as [`inflate_bytes`] and
+//! [`deflate_bytes`] do.
+//!
+//! To look at how a stream is put together rather than just what it decompresses to, an
+//! [`Inflater`] reads it a [`Block`] at a time, giving each block's type and position in the
+//! stream, and the literals and matches it's made of.
+//!
+//! # Example
+//! ```
+//! use b3::{
+//!     bit_cursor::BitCursor, bit_order::LsbFirstReader, bit_vec::BitVec,
+//!     deflate::{self, BlockType, Inflater, Token},
+//! };
+//!
+//! let compressed = deflate::deflate_bytes(b"abcabcabc").unwrap();
+//! assert_eq!(deflate::inflate_bytes(&compressed).unwrap(), b"abcabcabc");
+//!
+//! let reader = LsbFirstReader::new(BitCursor::new(BitVec::from_vec(compressed)));
+//! let mut inflater = Inflater::new(reader);
+//! let block = inflater.next_block().unwrap().unwrap();
+//! assert_eq!(block.block_type, BlockType::Fixed);
+//! assert_eq!(block.tokens[3], Token::Match { length: 6, distance: 3 });
+//! assert!(inflater.next_block().unwrap().is_none());
+//! ```
+
+use alloc::{vec, vec::Vec};
+use ux::u1;
+
+use crate::{
+    bit_cursor::BitCursor,
+    bit_order::{LsbFirstReader, LsbFirstWriter},
+    bit_read::{BitPeek, BitRead},
+    bit_vec::BitVec,
+    bit_write::BitWrite,
+    error::{B3Error, B3Result},
+    huffman::HuffmanTable,
+    io,
+};
+
+/// The furthest back a match can refer to
+pub const WINDOW_SIZE: usize = 32768;
+/// The shortest match which can be encoded
+pub const MIN_MATCH: usize = 3;
+/// The longest match which can be encoded
+pub const MAX_MATCH: usize = 258;
+
+const END_OF_BLOCK: u16 = 256;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order in which a dynamic block header gives the code length code's lengths
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// How a block is encoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlockType {
+    /// Uncompressed bytes
+    Stored,
+    /// Huffman codes fixed by the specification
+    Fixed,
+    /// Huffman codes given in the block's header
+    Dynamic,
+}
+
+/// An item of compressed data: a literal byte, or a copy of earlier output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Token {
+    Literal(u8),
+    /// Copy `length` bytes, starting `distance` bytes back from the end of the output
+    Match {
+        length: u16,
+        distance: u16,
+    },
+}
+
+impl Token {
+    /// The number of bytes of output the token makes
+    pub fn output_len(&self) -> usize {
+        match *self {
+            Token::Literal(_) => 1,
+            Token::Match { length, .. } => length.into(),
+        }
+    }
+}
+
+/// A block of a DEFLATE stream, as read by [`Inflater::next_block`].  The bytes of a stored block
+/// are given as literals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub block_type: BlockType,
+    /// Whether this is the last block of the stream
+    pub is_final: bool,
+    /// The position of the block's header in the stream, in bits
+    pub start: usize,
+    /// The position just past the end of the block, in bits
+    pub end: usize,
+    pub tokens: Vec<Token>,
+}
+
+/// Counts the bits read from the inner reader, so that stored blocks can be aligned to bytes
+#[derive(Debug)]
+struct Counter<R> {
+    inner: R,
+    position: usize,
+}
+
+impl<R: BitRead> BitRead for Counter<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        self.inner.read_exact(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+}
+
+impl<R: BitPeek> BitPeek for Counter<R> {
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        // Peeked bits haven't been consumed, so they aren't counted
+        self.inner.peek(buf)
+    }
+}
+
+/// Reads the next Huffman coded symbol
+type Decode<R> = fn(&HuffmanTable, &mut Counter<R>) -> B3Result<u16>;
+
+fn fixed_tables() -> (HuffmanTable, HuffmanTable) {
+    let mut lengths = vec![8; 144];
+    lengths.extend([9; 112]);
+    lengths.extend([7; 24]);
+    lengths.extend([8; 8]);
+    // Distance codes 30 and 31 have codes, but never appear in valid data
+    let literals = HuffmanTable::from_lengths(&lengths).expect("the fixed codes are valid");
+    let distances = HuffmanTable::from_lengths(&[5; 32]).expect("the fixed codes are valid");
+    (literals, distances)
+}
+
+/// A DEFLATE decoder which reads a stream from an inner reader a block at a time.
+///
+/// The stream must start at the first bit of a byte, since stored blocks are aligned to bytes.
+/// Malformed data is reported as [`B3Error::InvalidStream`], with the position of the offending
+/// bits in the stream.
+#[derive(Debug)]
+pub struct Inflater<R> {
+    reader: Counter<R>,
+    decode: Decode<R>,
+    output_len: usize,
+    done: bool,
+}
+
+impl<R> Inflater<R> {
+    /// Create an inflater which decodes Huffman codes by peeking at the upcoming bits and looking
+    /// them up in a table (see [`HuffmanTable::decode`]).
+    pub fn new(inner: R) -> Self
+    where
+        R: BitPeek,
+    {
+        Inflater::with_decode(inner, HuffmanTable::decode)
+    }
+
+    /// Create an inflater for a reader which can't peek, which decodes Huffman codes a bit at a
+    /// time (see [`HuffmanTable::decode_bitwise`]).
+    pub fn bitwise(inner: R) -> Self
+    where
+        R: BitRead,
+    {
+        Inflater::with_decode(inner, HuffmanTable::decode_bitwise)
+    }
+
+    fn with_decode(inner: R, decode: Decode<R>) -> Self {
+        Inflater {
+            reader: Counter { inner, position: 0 },
+            decode,
+            output_len: 0,
+            done: false,
+        }
+    }
+
+    /// The number of bits which have been read from the inner reader.
+    pub fn position(&self) -> usize {
+        self.reader.position
+    }
+
+    /// The number of bytes of output the blocks read so far make.
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    /// Whether the final block has been read.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.inner
+    }
+}
+
+impl<R: BitRead> Inflater<R> {
+    /// Read the next block, or return None once the final block has been read.
+    pub fn next_block(&mut self) -> B3Result<Option<Block>> {
+        if self.done {
+            return Ok(None);
+        }
+        let start = self.position();
+        let is_final = self.read_value(1)? == 1;
+        let (block_type, tokens) = match self.read_value(2)? {
+            0 => (BlockType::Stored, self.read_stored()?),
+            1 => {
+                let (literals, distances) = fixed_tables();
+                let tokens = self.read_compressed(&literals, &distances)?;
+                (BlockType::Fixed, tokens)
+            }
+            2 => {
+                let (literals, distances) = self.read_dynamic_tables()?;
+                let tokens = self.read_compressed(&literals, &distances)?;
+                (BlockType::Dynamic, tokens)
+            }
+            _ => return Err(invalid(start + 1, "reserved block type")),
+        };
+        self.done = is_final;
+        Ok(Some(Block {
+            block_type,
+            is_final,
+            start,
+            end: self.position(),
+            tokens,
+        }))
+    }
+
+    /// Read a `nbits` bit value, which DEFLATE packs starting from its least significant bit
+    fn read_value(&mut self, nbits: usize) -> B3Result<u32> {
+        let mut bits = [u1::new(0); 16];
+        self.reader.read_exact(&mut bits[..nbits])?;
+        Ok(bits[..nbits]
+            .iter()
+            .rev()
+            .fold(0, |value, &bit| (value << 1) | u32::from(u8::from(bit))))
+    }
+
+    fn decode(&mut self, table: &HuffmanTable) -> B3Result<u16> {
+        let position = self.position();
+        (self.decode)(table, &mut self.reader).map_err(|err| match err {
+            B3Error::InvalidCode { .. } => invalid(position, "invalid Huffman code"),
+            err => err,
+        })
+    }
+
+    fn push(&mut self, tokens: &mut Vec<Token>, token: Token) {
+        self.output_len += token.output_len();
+        tokens.push(token);
+    }
+
+    fn read_stored(&mut self) -> B3Result<Vec<Token>> {
+        let padding = (8 - self.position() % 8) % 8;
+        self.read_value(padding)?;
+        let position = self.position();
+        let len = self.read_value(16)?;
+        let nlen = self.read_value(16)?;
+        if len != !nlen & 0xFFFF {
+            return Err(invalid(
+                position,
+                "stored block length doesn't match its complement",
+            ));
+        }
+        let mut tokens = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let byte = self.read_value(8)? as u8;
+            self.push(&mut tokens, Token::Literal(byte));
+        }
+        Ok(tokens)
+    }
+
+    fn read_dynamic_tables(&mut self) -> B3Result<(HuffmanTable, HuffmanTable)> {
+        let position = self.position();
+        let num_literals = self.read_value(5)? as usize + 257;
+        let num_distances = self.read_value(5)? as usize + 1;
+        let num_code_lengths = self.read_value(4)? as usize + 4;
+        if num_literals > 286 || num_distances > 30 {
+            return Err(invalid(position, "too many codes"));
+        }
+
+        let mut code_length_lengths = [0u8; 19];
+        for &symbol in &CODE_LENGTH_ORDER[..num_code_lengths] {
+            code_length_lengths[symbol] = self.read_value(3)? as u8;
+        }
+        let code_lengths = HuffmanTable::from_lengths(&code_length_lengths)
+            .map_err(|_| invalid(position, "invalid code length code"))?;
+
+        let total = num_literals + num_distances;
+        let mut lengths = Vec::with_capacity(total);
+        while lengths.len() < total {
+            let symbol_position = self.position();
+            let (length, repeat) = match self.decode(&code_lengths)? {
+                length @ 0..=15 => (length as u8, 1),
+                16 => {
+                    let &previous = lengths.last().ok_or_else(|| {
+                        invalid(symbol_position, "repeat with no previous length")
+                    })?;
+                    (previous, 3 + self.read_value(2)? as usize)
+                }
+                17 => (0, 3 + self.read_value(3)? as usize),
+                _ => (0, 11 + self.read_value(7)? as usize),
+            };
+            if lengths.len() + repeat > total {
+                return Err(invalid(symbol_position, "too many code lengths"));
+            }
+            lengths.resize(lengths.len() + repeat, length);
+        }
+        if lengths[usize::from(END_OF_BLOCK)] == 0 {
+            return Err(invalid(position, "no end of block code"));
+        }
+
+        let literals = HuffmanTable::from_lengths(&lengths[..num_literals])
+            .map_err(|_| invalid(position, "invalid literal/length code"))?;
+        let distances = HuffmanTable::from_lengths(&lengths[num_literals..])
+            .map_err(|_| invalid(position, "invalid distance code"))?;
+        Ok((literals, distances))
+    }
+
+    fn read_compressed(
+        &mut self,
+        literals: &HuffmanTable,
+        distances: &HuffmanTable,
+    ) -> B3Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        loop {
+            let position = self.position();
+            let symbol = self.decode(literals)?;
+            let token = match symbol {
+                0..=255 => Token::Literal(symbol as u8),
+                END_OF_BLOCK => return Ok(tokens),
+                257..=285 => {
+                    let index = usize::from(symbol - 257);
+                    let length =
+                        LENGTH_BASE[index] + self.read_value(LENGTH_EXTRA[index].into())? as u16;
+                    let index = usize::from(self.decode(distances)?);
+                    if index >= DISTANCE_BASE.len() {
+                        return Err(invalid(position, "invalid distance symbol"));
+                    }
+                    let distance = DISTANCE_BASE[index]
+                        + self.read_value(DISTANCE_EXTRA[index].into())? as u16;
+                    if usize::from(distance) > self.output_len {
+                        return Err(invalid(position, "distance too far back"));
+                    }
+                    Token::Match { length, distance }
+                }
+                _ => return Err(invalid(position, "invalid length symbol")),
+            };
+            self.push(&mut tokens, token);
+        }
+    }
+}
+
+fn invalid(position: usize, reason: &'static str) -> B3Error {
+    B3Error::InvalidStream { position, reason }
+}
+
+/// Append the output of `tokens` to `output`.  Matches must not refer back past the start of
+/// `output`.
+pub fn apply_tokens(tokens: &[Token], output: &mut Vec<u8>) {
+    for &token in tokens {
+        match token {
+            Token::Literal(byte) => output.push(byte),
+            Token::Match { length, distance } => {
+                // The match may overlap the bytes it's copying
+                let start = output.len() - usize::from(distance);
+                for i in start..start + usize::from(length) {
+                    output.push(output[i]);
+                }
+            }
+        }
+    }
+}
+
+/// Decompress a whole DEFLATE stream.  For a reader which can't peek, use
+/// [`Inflater::bitwise`] and [`apply_tokens`] instead.
+pub fn inflate<R: BitPeek>(reader: R) -> B3Result<Vec<u8>> {
+    inflate_blocks(Inflater::new(reader))
+}
+
+fn inflate_blocks<R: BitRead>(mut inflater: Inflater<R>) -> B3Result<Vec<u8>> {
+    let mut output = Vec::new();
+    while let Some(block) = inflater.next_block()? {
+        apply_tokens(&block.tokens, &mut output);
+    }
+    Ok(output)
+}
+
+/// Decompress a DEFLATE stream packed into bytes.
+pub fn inflate_bytes(bytes: &[u8]) -> B3Result<Vec<u8>> {
+    inflate(LsbFirstReader::new(BitCursor::new(BitVec::from_vec(
+        bytes.to_vec(),
+    ))))
+}
+
+/// Split `data` into literals and matches, greedily taking the longest match (found through a
+/// hash chain of earlier positions with the same three bytes) at each position.
+pub fn lz77(data: &[u8]) -> Vec<Token> {
+    const HASH_BITS: u32 = 15;
+    const MAX_CHAIN: usize = 128;
+    const NONE: usize = usize::MAX;
+
+    let hash = |i: usize| {
+        let key = u32::from(data[i]) << 16 | u32::from(data[i + 1]) << 8 | u32::from(data[i + 2]);
+        (key.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    };
+    let mut head = vec![NONE; 1 << HASH_BITS];
+    let mut prev = vec![NONE; data.len()];
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_length, mut best_distance) = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(i)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == NONE || i - candidate > WINDOW_SIZE {
+                    break;
+                }
+                let length = (0..max_length)
+                    .take_while(|&k| data[candidate + k] == data[i + k])
+                    .count();
+                if length > best_length {
+                    (best_length, best_distance) = (length, i - candidate);
+                    if length == max_length {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+            }
+        }
+        let end = if best_length >= MIN_MATCH {
+            tokens.push(Token::Match {
+                length: best_length as u16,
+                distance: best_distance as u16,
+            });
+            i + best_length
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            i + 1
+        };
+        for k in (i..end).filter(|&k| k + MIN_MATCH <= data.len()) {
+            let h = hash(k);
+            prev[k] = head[h];
+            head[h] = k;
+        }
+        i = end;
+    }
+    tokens
+}
+
+/// Write a `nbits` bit value starting from its least significant bit
+fn write_value<W: BitWrite + ?Sized>(writer: &mut W, nbits: usize, value: u32) -> io::Result<()> {
+    let mut bits = [u1::new(0); 16];
+    for (i, bit) in bits[..nbits].iter_mut().enumerate() {
+        *bit = u1::new((value >> i) as u8 & 1);
+    }
+    writer.write_all(&bits[..nbits])
+}
+
+/// Write `tokens` as a fixed Huffman block.  Returns [`B3Error::ValueOutOfRange`] if a match's
+/// length or distance can't be encoded.
+pub fn write_fixed_block<W: BitWrite + ?Sized>(
+    tokens: &[Token],
+    is_final: bool,
+    writer: &mut W,
+) -> B3Result<()> {
+    let (literals, distances) = fixed_tables();
+    write_value(writer, 1, is_final.into())?;
+    write_value(writer, 2, 1)?;
+    for &token in tokens {
+        match token {
+            Token::Literal(byte) => literals.encode(byte.into(), writer)?,
+            Token::Match { length, distance } => {
+                if !(MIN_MATCH..=MAX_MATCH).contains(&usize::from(length)) {
+                    return Err(B3Error::ValueOutOfRange {
+                        value: length.into(),
+                        // The bits needed for the longest match
+                        bits: 9,
+                    });
+                }
+                if !(1..=WINDOW_SIZE).contains(&usize::from(distance)) {
+                    return Err(B3Error::ValueOutOfRange {
+                        value: distance.into(),
+                        // The bits needed for the furthest distance
+                        bits: 16,
+                    });
+                }
+                let index = LENGTH_BASE.partition_point(|&base| base <= length) - 1;
+                literals.encode(257 + index as u16, writer)?;
+                let extra = u32::from(length - LENGTH_BASE[index]);
+                write_value(writer, LENGTH_EXTRA[index].into(), extra)?;
+                let index = DISTANCE_BASE.partition_point(|&base| base <= distance) - 1;
+                distances.encode(index as u16, writer)?;
+                let extra = u32::from(distance - DISTANCE_BASE[index]);
+                write_value(writer, DISTANCE_EXTRA[index].into(), extra)?;
+            }
+        }
+    }
+    literals.encode(END_OF_BLOCK, writer)?;
+    Ok(())
+}
+
+/// Compress `data` into a DEFLATE stream of a single fixed Huffman block.
+pub fn deflate<W: BitWrite + ?Sized>(data: &[u8], writer: &mut W) -> B3Result<()> {
+    write_fixed_block(&lz77(data), true, writer)
+}
+
+/// Compress `data` into a DEFLATE stream packed into bytes.
+pub fn deflate_bytes(data: &[u8]) -> B3Result<Vec<u8>> {
+    let mut writer = LsbFirstWriter::new(BitVec::new());
+    deflate(data, &mut writer)?;
+    Ok(writer.finish()?.to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! testdata {
+        ($name:literal, $suffix:literal) => {
+            include_bytes!(concat!("../testdata/deflate/", $name, $suffix)) as &[u8]
+        };
+    }
+
+    macro_rules! corpus {
+        ($($name:literal),*) => {
+            [$((
+                $name,
+                testdata!($name, ".bin"),
+                [
+                    ("stored", testdata!($name, ".stored.deflate")),
+                    ("fixed", testdata!($name, ".fixed.deflate")),
+                    ("default", testdata!($name, ".default.deflate")),
+                    ("best", testdata!($name, ".best.deflate")),
+                ],
+            )),*]
+        };
+    }
+
+    type Corpus = [(
+        &'static str,
+        &'static [u8],
+        [(&'static str, &'static [u8]); 4],
+    ); 6];
+
+    const CORPUS: Corpus = corpus!("empty", "short", "text", "random", "runs", "allbytes");
+
+    fn blocks(compressed: &[u8]) -> Vec<Block> {
+        let mut inflater = Inflater::new(reader(compressed));
+        let mut blocks = Vec::new();
+        while let Some(block) = inflater.next_block().unwrap() {
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    fn reader(compressed: &[u8]) -> LsbFirstReader<BitCursor<BitVec>> {
+        LsbFirstReader::new(BitCursor::new(BitVec::from_vec(compressed.to_vec())))
+    }
+
+    #[test]
+    fn test_inflate_corpus() {
+        for (name, original, streams) in CORPUS {
+            for (setting, compressed) in streams {
+                assert_eq!(
+                    inflate_bytes(compressed).unwrap(),
+                    original,
+                    "{name}.{setting}"
+                );
+                // And decoding the Huffman codes a bit at a time
+                let inflater = Inflater::bitwise(reader(compressed));
+                assert_eq!(
+                    inflate_blocks(inflater).unwrap(),
+                    original,
+                    "{name}.{setting} (bitwise)"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_structure() {
+        let (_, text, streams) = CORPUS[2];
+        for (setting, block_type) in [
+            ("stored", BlockType::Stored),
+            ("fixed", BlockType::Fixed),
+            ("best", BlockType::Dynamic),
+        ] {
+            let (_, compressed) = streams.iter().find(|(s, _)| *s == setting).unwrap();
+            let blocks = blocks(compressed);
+            assert!(blocks.iter().all(|block| block.block_type == block_type));
+            assert!(blocks.last().unwrap().is_final);
+            assert_eq!(blocks[0].start, 0);
+            // Blocks follow each other, and the stream ends with the last one's padding
+            for pair in blocks.windows(2) {
+                assert_eq!(pair[0].end, pair[1].start);
+            }
+            assert_eq!(blocks.last().unwrap().end.div_ceil(8), compressed.len());
+            let total: usize = blocks
+                .iter()
+                .flat_map(|b| &b.tokens)
+                .map(Token::output_len)
+                .sum();
+            assert_eq!(total, text.len());
+        }
+    }
+
+    #[test]
+    fn test_deflate_round_trip() {
+        for (name, original, _) in CORPUS {
+            let compressed = deflate_bytes(original).unwrap();
+            assert_eq!(inflate_bytes(&compressed).unwrap(), original, "{name}");
+        }
+        let (_, runs, _) = CORPUS[4];
+        assert!(deflate_bytes(runs).unwrap().len() < 200);
+    }
+
+    #[test]
+    fn test_deflate_fixed() {
+        // As zlib compresses them with the fixed strategy
+        assert_eq!(deflate_bytes(b"").unwrap(), vec![0x03, 0x00]);
+        assert_eq!(deflate_bytes(b"a").unwrap(), vec![0x4B, 0x04, 0x00]);
+        let (_, short, streams) = CORPUS[1];
+        assert_eq!(deflate_bytes(short).unwrap(), streams[1].1);
+    }
+
+    #[test]
+    fn test_lz77() {
+        assert_eq!(
+            lz77(b"aaaaab"),
+            vec![
+                Token::Literal(b'a'),
+                Token::Match {
+                    length: 4,
+                    distance: 1
+                },
+                Token::Literal(b'b'),
+            ]
+        );
+        let data = [b'x'; 600];
+        let tokens = lz77(&data);
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(
+            tokens[1],
+            Token::Match {
+                length: 258,
+                distance: 1
+            }
+        );
+        let mut output = Vec::new();
+        apply_tokens(&tokens, &mut output);
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn test_invalid() {
+        // Reserved block type
+        assert_eq!(
+            inflate_bytes(&[0x07]),
+            Err(B3Error::InvalidStream {
+                position: 1,
+                reason: "reserved block type"
+            })
+        );
+        // Stored block with a bad length complement
+        assert_eq!(
+            inflate_bytes(&[0x01, 0x01, 0x00, 0xFF, 0xFF, 0x00]),
+            Err(B3Error::InvalidStream {
+                position: 8,
+                reason: "stored block length doesn't match its complement"
+            })
+        );
+        // A match at the start of the output
+        let mut writer = LsbFirstWriter::new(BitVec::new());
+        let token = Token::Match {
+            length: 3,
+            distance: 1,
+        };
+        write_fixed_block(&[token], true, &mut writer).unwrap();
+        let bytes = writer.finish().unwrap().to_bytes();
+        assert_eq!(
+            inflate_bytes(&bytes),
+            Err(B3Error::InvalidStream {
+                position: 3,
+                reason: "distance too far back"
+            })
+        );
+        // Matches which can't be encoded
+        let mut writer = LsbFirstWriter::new(BitVec::new());
+        let token = Token::Match {
+            length: 259,
+            distance: 1,
+        };
+        assert_eq!(
+            write_fixed_block(&[token], true, &mut writer),
+            Err(B3Error::ValueOutOfRange {
+                value: 259,
+                bits: 9
+            })
+        );
+        let token = Token::Match {
+            length: 3,
+            distance: 32769,
+        };
+        assert_eq!(
+            write_fixed_block(&[token], true, &mut writer),
+            Err(B3Error::ValueOutOfRange {
+                value: 32769,
+                bits: 16
+            })
+        );
+        // Truncated
+        let compressed = deflate_bytes(b"hello, hello").unwrap();
+        assert_eq!(
+            inflate_bytes(&compressed[..compressed.len() - 2]),
+            Err(B3Error::Io(io::ErrorKind::UnexpectedEof))
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! Parity bits, checksums and Hamming error-correcting codes.
+//!
+//! # Example
+//! ```
+//! use b3::{bit_vec::BitVec, ecc::SECDED_8_4};
+//! use ux::u1;
+//!
+//! let data = BitVec::from_bytes(&[0b1011_0000], 4);
+//! let mut encoded = BitVec::new();
+//! SECDED_8_4.encode(&data.get_slice(..).unwrap(), &mut encoded).unwrap();
+//!
+//! // Flip a bit, and it's corrected
+//! encoded.set(5, u1::new(1) - encoded.at(5));
+//! let mut decoded = BitVec::new();
+//! let corrected = SECDED_8_4.decode(&encoded.get_slice(..).unwrap(), &mut decoded).unwrap();
+//! assert_eq!(corrected, vec![5]);
+//! assert_eq!(decoded, data);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use ux::u1;
+
+use crate::{
+    bit_write::BitWrite,
+    error::{B3Error, B3Result},
+    io,
+    slice::BitSlice,
+};
+
+/// The kind of parity bit: one which makes the number of 1 bits even, or odd.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+fn xor_bits(bits: &BitSlice<'_>) -> u1 {
+    bits.iter().fold(u1::new(0), |acc, bit| acc ^ bit)
+}
+
+impl Parity {
+    /// The parity bit to append to `bits`.
+    pub fn bit(&self, bits: &BitSlice<'_>) -> u1 {
+        match self {
+            Parity::Even => xor_bits(bits),
+            Parity::Odd => xor_bits(bits) ^ u1::new(1),
+        }
+    }
+
+    /// Whether `bits`, including their parity bit, have the right parity.
+    pub fn check(&self, bits: &BitSlice<'_>) -> bool {
+        let expected = match self {
+            Parity::Even => u1::new(0),
+            Parity::Odd => u1::new(1),
+        };
+        xor_bits(bits) == expected
+    }
+}
+
+/// A checksum which adds up the input in `width` bit words, taken MSB first.  If the length of
+/// the input isn't a multiple of the width, the last word is padded with 0 bits.
+///
+/// A ones' complement checksum (like the 16-bit [`INTERNET_CHECKSUM`] of RFC 1071) is the ones'
+/// complement of the ones' complement sum of the words.  A modular checksum is the sum of the
+/// words modulo 2^width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Checksum {
+    width: usize,
+    ones_complement: bool,
+}
+
+impl Checksum {
+    /// A ones' complement checksum of `width` (1 to 64) bits.  Panics if `width` is out of
+    /// range.
+    pub const fn ones_complement(width: usize) -> Self {
+        assert!(width > 0 && width <= 64, "checksums must be 1 to 64 bits");
+        Checksum {
+            width,
+            ones_complement: true,
+        }
+    }
+
+    /// A modular checksum of `width` (1 to 64) bits.  Panics if `width` is out of range.
+    pub const fn modular(width: usize) -> Self {
+        assert!(width > 0 && width <= 64, "checksums must be 1 to 64 bits");
+        Checksum {
+            width,
+            ones_complement: false,
+        }
+    }
+
+    /// The width of the checksum, in bits
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn is_ones_complement(&self) -> bool {
+        self.ones_complement
+    }
+
+    /// Compute the checksum of `bits`.
+    pub fn compute(&self, bits: &BitSlice<'_>) -> u64 {
+        let width = self.width;
+        let mask = u64::MAX >> (64 - width);
+        let mut sum = 0u128;
+        let mut word = 0u64;
+        let mut word_len = 0;
+        for bit in bits.iter() {
+            word = (word << 1) | u64::from(bit);
+            word_len += 1;
+            if word_len == width {
+                sum += u128::from(word);
+                word = 0;
+                word_len = 0;
+            }
+        }
+        if word_len > 0 {
+            sum += u128::from(word << (width - word_len));
+        }
+        if self.ones_complement {
+            // Fold the carries back in until the sum fits in the width
+            while sum >> width != 0 {
+                sum = (sum & u128::from(mask)) + (sum >> width);
+            }
+            !(sum as u64) & mask
+        } else {
+            sum as u64 & mask
+        }
+    }
+
+    /// Whether `checksum` is the checksum of `bits`.
+    pub fn check(&self, bits: &BitSlice<'_>, checksum: u64) -> bool {
+        self.compute(bits) == checksum
+    }
+}
+
+/// The 16-bit ones' complement checksum used by IP, TCP and UDP (RFC 1071)
+pub const INTERNET_CHECKSUM: Checksum = Checksum::ones_complement(16);
+
+/// The most data bits supported in a Hamming codeword
+const MAX_DATA_BITS: usize = 64;
+/// The size of a codeword with [`MAX_DATA_BITS`], plus the extended parity bit
+const MAX_CODE_BITS: usize = 72;
+
+/// A Hamming code, optionally extended with an overall parity bit to make a SECDED (single error
+/// correction, double error detection) code.
+///
+/// Codewords use the classic layout: bit `i` of the codeword (counting from 0) is Hamming
+/// position `i + 1`, the parity bits are at the positions which are powers of 2, and the data
+/// bits fill the remaining positions in order.  The overall parity bit of an extended code comes
+/// last.  Codes with fewer data bits than a perfect code (e.g. the (72, 64) SECDED code used for
+/// memory) are shortened by leaving off the last positions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hamming {
+    data_bits: usize,
+    parity_bits: usize,
+    extended: bool,
+}
+
+impl Hamming {
+    /// Create a Hamming code for `data_bits` (1 to 64) data bits per codeword, using the fewest
+    /// parity bits possible.  Panics if `data_bits` is out of range.
+    pub const fn new(data_bits: usize, extended: bool) -> Self {
+        assert!(
+            data_bits > 0 && data_bits <= MAX_DATA_BITS,
+            "Hamming codes support 1 to 64 data bits"
+        );
+        let mut parity_bits = 2;
+        while (1 << parity_bits) < data_bits + parity_bits + 1 {
+            parity_bits += 1;
+        }
+        Hamming {
+            data_bits,
+            parity_bits,
+            extended,
+        }
+    }
+
+    /// The number of data bits in each codeword
+    pub fn data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    /// The number of bits in each codeword
+    pub fn code_bits(&self) -> usize {
+        self.hamming_bits() + self.extended as usize
+    }
+
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+
+    /// The number of bits in each codeword, not counting the extended parity bit
+    fn hamming_bits(&self) -> usize {
+        self.data_bits + self.parity_bits
+    }
+
+    /// Encode one codeword's worth of data bits into |code|
+    fn encode_block(&self, data: &[u1], code: &mut [u1]) {
+        let n = self.hamming_bits();
+        let mut data = data.iter();
+        let mut syndrome = 0;
+        for position in 1..=n {
+            if position.is_power_of_two() {
+                code[position - 1] = u1::new(0);
+            } else {
+                let bit = *data.next().expect("one data bit per position");
+                code[position - 1] = bit;
+                if bit == u1::new(1) {
+                    syndrome ^= position;
+                }
+            }
+        }
+        // Set each parity bit so that the syndrome of the whole codeword is 0
+        for i in 0..self.parity_bits {
+            code[(1 << i) - 1] = u1::new(((syndrome >> i) & 1) as u8);
+        }
+        if self.extended {
+            code[n] = code[..n].iter().fold(u1::new(0), |acc, &bit| acc ^ bit);
+        }
+    }
+
+    /// Correct a single error in |code|, returning the index of the corrected bit (if any), or
+    /// None if the error can't be corrected
+    fn correct_block(&self, code: &mut [u1]) -> Option<Option<usize>> {
+        let n = self.hamming_bits();
+        let syndrome = code[..n]
+            .iter()
+            .enumerate()
+            .filter(|(_, &bit)| bit == u1::new(1))
+            .fold(0, |syndrome, (i, _)| syndrome ^ (i + 1));
+        let parity_ok = !self.extended
+            || code[..=n].iter().fold(u1::new(0), |acc, &bit| acc ^ bit) == u1::new(0);
+        match (syndrome, parity_ok) {
+            (0, true) => Some(None),
+            // Only the overall parity bit is wrong
+            (0, false) => {
+                code[n] ^= u1::new(1);
+                Some(Some(n))
+            }
+            // An extended code with a syndrome but correct overall parity has two errors
+            (_, true) if self.extended => None,
+            // A shortened code can't have an error at a position past its end
+            (syndrome, _) if syndrome > n => None,
+            (syndrome, _) => {
+                code[syndrome - 1] ^= u1::new(1);
+                Some(Some(syndrome - 1))
+            }
+        }
+    }
+
+    fn write_data<W: BitWrite + ?Sized>(&self, code: &[u1], out: &mut W) -> io::Result<()> {
+        let mut data = [u1::new(0); MAX_DATA_BITS];
+        let positions = (1..=self.hamming_bits()).filter(|position| !position.is_power_of_two());
+        for (bit, position) in data.iter_mut().zip(positions) {
+            *bit = code[position - 1];
+        }
+        out.write_all(&data[..self.data_bits])
+    }
+
+    /// Encode `data`, whose length must be a multiple of [`Hamming::data_bits`], writing one
+    /// codeword per block of data bits to `out`.  Panics if the length of `data` isn't a
+    /// multiple of [`Hamming::data_bits`].
+    pub fn encode<W: BitWrite + ?Sized>(&self, data: &BitSlice<'_>, out: &mut W) -> io::Result<()> {
+        assert!(
+            data.len() % self.data_bits == 0,
+            "data length ({}) is not a whole number of {} bit blocks",
+            data.len(),
+            self.data_bits
+        );
+        let mut bits = data.iter();
+        let mut block = [u1::new(0); MAX_DATA_BITS];
+        let mut code = [u1::new(0); MAX_CODE_BITS];
+        for _ in 0..data.len() / self.data_bits {
+            block
+                .iter_mut()
+                .take(self.data_bits)
+                .zip(&mut bits)
+                .for_each(|(dst, src)| *dst = src);
+            self.encode_block(&block[..self.data_bits], &mut code);
+            out.write_all(&code[..self.code_bits()])?;
+        }
+        Ok(())
+    }
+
+    /// Decode a single codeword, correcting a single bit error if there is one, and write its
+    /// data bits to `out`.  Returns the index of the corrected bit within the codeword, if
+    /// there was one, or [`B3Error::UncorrectableError`] if the errors can't be corrected
+    /// (in which case nothing is written).  The error's `offset` is always 0, i.e. the start of
+    /// this codeword; [`Hamming::decode`] reports the offset of the codeword within its input.
+    /// Panics if `code` isn't exactly [`Hamming::code_bits`] long.
+    pub fn decode_block<W: BitWrite + ?Sized>(
+        &self,
+        code: &BitSlice<'_>,
+        out: &mut W,
+    ) -> B3Result<Option<usize>> {
+        assert_eq!(
+            code.len(),
+            self.code_bits(),
+            "codeword length ({}) does not match the code's length ({})",
+            code.len(),
+            self.code_bits()
+        );
+        let mut bits = [u1::new(0); MAX_CODE_BITS];
+        code.iter()
+            .zip(bits.iter_mut())
+            .for_each(|(src, dst)| *dst = src);
+        let corrected = self
+            .correct_block(&mut bits)
+            .ok_or(B3Error::UncorrectableError { offset: 0 })?;
+        self.write_data(&bits, out)?;
+        Ok(corrected)
+    }
+
+    /// Decode a run of codewords, whose length must be a multiple of [`Hamming::code_bits`],
+    /// writing their data bits to `out`.  Returns the indices (within `codes`) of the bits which
+    /// were corrected, or [`B3Error::UncorrectableError`] with the index of the first codeword
+    /// which couldn't be corrected.  Panics if the length of `codes` isn't a multiple of
+    /// [`Hamming::code_bits`].
+    #[cfg(feature = "alloc")]
+    pub fn decode<W: BitWrite + ?Sized>(
+        &self,
+        codes: &BitSlice<'_>,
+        out: &mut W,
+    ) -> B3Result<Vec<usize>> {
+        let code_bits = self.code_bits();
+        assert!(
+            codes.len() % code_bits == 0,
+            "length ({}) is not a whole number of {} bit codewords",
+            codes.len(),
+            code_bits
+        );
+        let mut corrected = Vec::new();
+        for offset in (0..codes.len()).step_by(code_bits) {
+            let code = codes
+                .get_slice(offset..offset + code_bits)
+                .expect("codeword is in range");
+            match self.decode_block(&code, out) {
+                Ok(Some(index)) => corrected.push(offset + index),
+                Ok(None) => {}
+                Err(B3Error::UncorrectableError { .. }) => {
+                    return Err(B3Error::UncorrectableError { offset })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(corrected)
+    }
+}
+
+/// Hamming(7, 4)
+pub const HAMMING_7_4: Hamming = Hamming::new(4, false);
+/// Hamming(15, 11)
+pub const HAMMING_15_11: Hamming = Hamming::new(11, false);
+/// Extended Hamming(8, 4)
+pub const SECDED_8_4: Hamming = Hamming::new(4, true);
+/// Extended Hamming(16, 11)
+pub const SECDED_16_11: Hamming = Hamming::new(11, true);
+/// The (72, 64) SECDED code used by ECC memory
+pub const SECDED_72_64: Hamming = Hamming::new(64, true);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bit_vec::BitVec, bitvec};
+
+    fn flip(vec: &mut BitVec, index: usize) {
+        vec.set(index, vec.at(index) ^ u1::new(1));
+    }
+
+    #[test]
+    fn test_parity() {
+        let bits = bitvec!(1, 0, 1, 1);
+        let slice = bits.get_slice(..).unwrap();
+        assert_eq!(Parity::Even.bit(&slice), u1::new(1));
+        assert_eq!(Parity::Odd.bit(&slice), u1::new(0));
+        assert!(Parity::Odd.check(&slice));
+        assert!(!Parity::Even.check(&slice));
+    }
+
+    #[test]
+    fn test_internet_checksum() {
+        // The example from RFC 1071: the words sum to 0xddf2 with the carries folded in
+        let bytes = [0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7];
+        let data = BitVec::from_vec(bytes.to_vec());
+        let slice = data.get_slice(..).unwrap();
+        assert_eq!(INTERNET_CHECKSUM.compute(&slice), 0x220d);
+        assert!(INTERNET_CHECKSUM.check(&slice, 0x220d));
+        assert!(!INTERNET_CHECKSUM.check(&slice, 0x220c));
+
+        // An odd number of bytes is padded with a zero byte
+        let data = BitVec::from_vec(bytes[..7].to_vec());
+        assert_eq!(
+            INTERNET_CHECKSUM.compute(&data.get_slice(..).unwrap()),
+            0x2304
+        );
+
+        // Appending the checksum makes the checksum of the whole 0
+        let mut data = bytes.to_vec();
+        data.extend([0x22, 0x0d]);
+        let data = BitVec::from_vec(data);
+        assert_eq!(INTERNET_CHECKSUM.compute(&data.get_slice(..).unwrap()), 0);
+    }
+
+    #[test]
+    fn test_modular_checksum() {
+        let data = BitVec::from_vec(vec![0x80, 0x90, 0x0A]);
+        let slice = data.get_slice(..).unwrap();
+        assert_eq!(Checksum::modular(8).compute(&slice), 0x1A);
+        assert_eq!(Checksum::modular(16).compute(&slice), 0x8A90);
+        // Widths which aren't a whole number of bytes: in 5-bit words the data is 10000 00010
+        // 01000 00000 1010(0), which sum to 46, or 14 + 1 with the carry folded in
+        assert_eq!(Checksum::modular(5).compute(&slice), 14);
+        assert_eq!(Checksum::ones_complement(5).compute(&slice), !15 & 0x1F);
+        assert_eq!(Checksum::modular(64).compute(&slice), 0x8090_0A00_0000_0000);
+    }
+
+    #[test]
+    fn test_hamming_7_4() {
+        assert_eq!(HAMMING_7_4.code_bits(), 7);
+        assert_eq!(HAMMING_15_11.code_bits(), 15);
+        assert_eq!(SECDED_72_64.code_bits(), 72);
+
+        // Data 1011 is sent as p1 p2 d1 p3 d2 d3 d4 = 0110011
+        let data = bitvec!(1, 0, 1, 1);
+        let mut code = BitVec::new();
+        HAMMING_7_4
+            .encode(&data.get_slice(..).unwrap(), &mut code)
+            .unwrap();
+        assert_eq!(code, bitvec!(0, 1, 1, 0, 0, 1, 1));
+
+        for i in 0..7 {
+            let mut corrupted = bitvec!(0, 1, 1, 0, 0, 1, 1);
+            flip(&mut corrupted, i);
+            let mut decoded = BitVec::new();
+            let corrected = HAMMING_7_4
+                .decode_block(&corrupted.get_slice(..).unwrap(), &mut decoded)
+                .unwrap();
+            assert_eq!(corrected, Some(i));
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    fn test_secded() {
+        for code in [SECDED_8_4, SECDED_16_11, SECDED_72_64] {
+            let k = code.data_bits();
+            let data = BitVec::from_bytes(&[0x92; 24], k * 3);
+            let mut encoded = BitVec::new();
+            code.encode(&data.get_slice(..).unwrap(), &mut encoded)
+                .unwrap();
+            let n = code.code_bits();
+            assert_eq!(encoded.len(), 3 * n);
+
+            // A single error in each of the first two codewords is corrected, including one in
+            // the overall parity bit
+            let mut corrupted = BitVec::from_bytes(&encoded.to_bytes(), encoded.len());
+            flip(&mut corrupted, 2);
+            flip(&mut corrupted, 2 * n - 1);
+            let mut decoded = BitVec::new();
+            let corrected = code
+                .decode(&corrupted.get_slice(..).unwrap(), &mut decoded)
+                .unwrap();
+            assert_eq!(corrected, vec![2, 2 * n - 1]);
+            assert_eq!(decoded, data);
+
+            // Two errors in the last codeword are detected
+            let mut corrupted = encoded;
+            flip(&mut corrupted, 2 * n);
+            flip(&mut corrupted, 2 * n + 3);
+            let mut decoded = BitVec::new();
+            assert_eq!(
+                code.decode(&corrupted.get_slice(..).unwrap(), &mut decoded),
+                Err(B3Error::UncorrectableError { offset: 2 * n })
+            );
+            assert_eq!(decoded.len(), 2 * k);
+        }
+    }
+
+    #[test]
+    fn test_shortened() {
+        // With 64 data bits, the syndrome can point past the end of the codeword
+        let mut code = bitvec!(0; 71);
+        // Positions 1, 2, 4, 8, 16, 32, 64 are parity bits, so flipping 64, 32, 16 and 8 gives
+        // syndrome 120, which is past the 71 positions
+        for position in [64, 32, 16, 8] {
+            flip(&mut code, position - 1);
+        }
+        let shortened = Hamming::new(64, false);
+        let mut decoded = BitVec::new();
+        assert_eq!(
+            shortened.decode_block(&code.get_slice(..).unwrap(), &mut decoded),
+            Err(B3Error::UncorrectableError { offset: 0 })
+        );
+    }
+}
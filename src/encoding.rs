@@ -0,0 +1,242 @@
+//! Conversions between integers and some of their less common binary encodings: packed BCD,
+//! Gray code, sign-magnitude and one's complement.
+//!
+//! Each encoding works on the low `width` bits of a u64, where `width` is between 1 and 64.
+//! [`BitReadExts`](crate::bit_read_exts::BitReadExts) and
+//! [`BitWriteExts`](crate::bit_write_exts::BitWriteExts) use these to read and write encoded
+//! fields directly.
+//!
+//! # Panics
+//! The functions here panic if `width` is 0 or greater than 64.  The reading and writing
+//! methods instead fail with [`B3Error::InvalidWidth`] before reading or writing any bits.
+
+use crate::error::{B3Error, B3Result};
+
+/// The widest field any of these encodings can be
+const MAX_WIDTH: usize = 64;
+
+/// Check that `width` is between 1 and [`MAX_WIDTH`], so that an invalid width can be reported
+/// before any bits are read or written.
+pub(crate) fn check_width(width: usize) -> B3Result<()> {
+    if width > 0 && width <= MAX_WIDTH {
+        Ok(())
+    } else {
+        Err(B3Error::InvalidWidth {
+            bits: width,
+            max: MAX_WIDTH,
+        })
+    }
+}
+
+fn mask(width: usize) -> u64 {
+    assert!(
+        width > 0 && width <= 64,
+        "Encoded width must be 1 to 64 bits"
+    );
+    u64::MAX >> (64 - width)
+}
+
+fn check_fits(value: i128, fits: bool, width: usize) -> B3Result<()> {
+    if fits {
+        Ok(())
+    } else {
+        Err(B3Error::ValueOutOfRange { value, bits: width })
+    }
+}
+
+/// Decode a packed BCD value: one decimal digit per nibble, with the least significant digit
+/// in the low nibble.  If `width` isn't a multiple of 4, the most significant digit is narrower
+/// (e.g. the 2-bit tens of hours in a timecode).  A digit greater than 9 is reported as
+/// [`B3Error::InvalidCode`], with the digits indexed from the most significant.
+pub fn decode_bcd(bits: u64, width: usize) -> B3Result<u64> {
+    let bits = bits & mask(width);
+    let digits = width.div_ceil(4);
+    let mut value = 0;
+    for index in 0..digits {
+        let digit = (bits >> (4 * (digits - 1 - index))) & 0xF;
+        if digit > 9 {
+            return Err(B3Error::InvalidCode {
+                code: digit as u32,
+                index,
+            });
+        }
+        value = value * 10 + digit;
+    }
+    Ok(value)
+}
+
+/// Encode a value as packed BCD, or return [`B3Error::ValueOutOfRange`] if it has too many
+/// digits for `width`.
+pub fn encode_bcd(value: u64, width: usize) -> B3Result<u64> {
+    let mask = mask(width);
+    let mut bits = 0u128;
+    let mut remaining = value;
+    let mut shift = 0;
+    while remaining != 0 {
+        bits |= ((remaining % 10) as u128) << shift;
+        remaining /= 10;
+        shift += 4;
+    }
+    check_fits(value.into(), bits <= mask.into(), width)?;
+    Ok(bits as u64)
+}
+
+/// Decode a (reflected binary) Gray code value.
+pub fn decode_gray(bits: u64, width: usize) -> u64 {
+    let mut value = bits & mask(width);
+    let mut shift = 1;
+    while shift < 64 {
+        value ^= value >> shift;
+        shift <<= 1;
+    }
+    value
+}
+
+/// Encode a value as a (reflected binary) Gray code, or return [`B3Error::ValueOutOfRange`] if
+/// it doesn't fit in `width` bits.
+pub fn encode_gray(value: u64, width: usize) -> B3Result<u64> {
+    check_fits(value.into(), value <= mask(width), width)?;
+    Ok(value ^ (value >> 1))
+}
+
+/// Decode a sign-magnitude value: the top bit is the sign and the rest are the magnitude.
+/// Negative zero decodes to 0.
+pub fn decode_sign_magnitude(bits: u64, width: usize) -> i64 {
+    let magnitude_mask = mask(width) >> 1;
+    let magnitude = (bits & magnitude_mask) as i64;
+    if bits & (1 << (width - 1)) != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Encode a value as sign-magnitude, or return [`B3Error::ValueOutOfRange`] if its magnitude
+/// doesn't fit in `width - 1` bits.
+pub fn encode_sign_magnitude(value: i64, width: usize) -> B3Result<u64> {
+    let magnitude_mask = mask(width) >> 1;
+    let magnitude = value.unsigned_abs();
+    check_fits(value.into(), magnitude <= magnitude_mask, width)?;
+    if value < 0 {
+        Ok(magnitude | (1 << (width - 1)))
+    } else {
+        Ok(magnitude)
+    }
+}
+
+/// Decode a one's complement value: negative values are the bitwise inverse of their magnitude.
+/// Negative zero (all ones) decodes to 0.
+pub fn decode_ones_complement(bits: u64, width: usize) -> i64 {
+    let mask = mask(width);
+    let bits = bits & mask;
+    if bits & (1 << (width - 1)) != 0 {
+        -((!bits & mask) as i64)
+    } else {
+        bits as i64
+    }
+}
+
+/// Encode a value as one's complement, or return [`B3Error::ValueOutOfRange`] if its magnitude
+/// doesn't fit in `width - 1` bits.
+pub fn encode_ones_complement(value: i64, width: usize) -> B3Result<u64> {
+    let mask = mask(width);
+    let magnitude = value.unsigned_abs();
+    check_fits(value.into(), magnitude <= mask >> 1, width)?;
+    if value < 0 {
+        Ok(!magnitude & mask)
+    } else {
+        Ok(magnitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bcd() {
+        assert_eq!(decode_bcd(0x1234, 16), Ok(1234));
+        // 23 hours: a 2-bit tens digit and a 4-bit units digit
+        assert_eq!(decode_bcd(0b10_0011, 6), Ok(23));
+        assert_eq!(
+            decode_bcd(0x1A, 8),
+            Err(B3Error::InvalidCode {
+                code: 0xA,
+                index: 1
+            })
+        );
+        assert_eq!(
+            decode_bcd(u64::MAX, 64),
+            Err(B3Error::InvalidCode {
+                code: 0xF,
+                index: 0
+            })
+        );
+        assert_eq!(encode_bcd(1234, 16), Ok(0x1234));
+        assert_eq!(encode_bcd(23, 6), Ok(0b10_0011));
+        assert_eq!(encode_bcd(0, 4), Ok(0));
+        assert_eq!(
+            encode_bcd(43, 6),
+            Err(B3Error::ValueOutOfRange { value: 43, bits: 6 })
+        );
+        assert_eq!(
+            encode_bcd(u64::MAX, 64),
+            Err(B3Error::ValueOutOfRange {
+                value: u64::MAX.into(),
+                bits: 64
+            })
+        );
+    }
+
+    #[test]
+    fn test_gray() {
+        let codes = [0b000, 0b001, 0b011, 0b010, 0b110, 0b111, 0b101, 0b100];
+        for (value, code) in codes.into_iter().enumerate() {
+            assert_eq!(encode_gray(value as u64, 3), Ok(code));
+            assert_eq!(decode_gray(code, 3), value as u64);
+        }
+        assert_eq!(
+            decode_gray(encode_gray(u64::MAX, 64).unwrap(), 64),
+            u64::MAX
+        );
+        assert_eq!(
+            encode_gray(8, 3),
+            Err(B3Error::ValueOutOfRange { value: 8, bits: 3 })
+        );
+    }
+
+    #[test]
+    fn test_sign_magnitude() {
+        assert_eq!(decode_sign_magnitude(0b0101, 4), 5);
+        assert_eq!(decode_sign_magnitude(0b1101, 4), -5);
+        assert_eq!(decode_sign_magnitude(0b1000, 4), 0);
+        assert_eq!(encode_sign_magnitude(-5, 4), Ok(0b1101));
+        assert_eq!(encode_sign_magnitude(7, 4), Ok(0b0111));
+        assert_eq!(
+            encode_sign_magnitude(-8, 4),
+            Err(B3Error::ValueOutOfRange { value: -8, bits: 4 })
+        );
+        assert_eq!(decode_sign_magnitude(u64::MAX, 64), -i64::MAX);
+        assert_eq!(
+            encode_sign_magnitude(i64::MIN, 64),
+            Err(B3Error::ValueOutOfRange {
+                value: i64::MIN.into(),
+                bits: 64
+            })
+        );
+    }
+
+    #[test]
+    fn test_ones_complement() {
+        assert_eq!(decode_ones_complement(0b0101, 4), 5);
+        assert_eq!(decode_ones_complement(0b1010, 4), -5);
+        assert_eq!(decode_ones_complement(0b1111, 4), 0);
+        assert_eq!(encode_ones_complement(-5, 4), Ok(0b1010));
+        assert_eq!(encode_ones_complement(-7, 4), Ok(0b1000));
+        assert_eq!(
+            encode_ones_complement(8, 4),
+            Err(B3Error::ValueOutOfRange { value: 8, bits: 4 })
+        );
+        assert_eq!(decode_ones_complement(1 << 63, 64), -i64::MAX);
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::io;
+
 pub type B3Result<T> = Result<T, B3Error>;
 
 #[derive(Error, Debug, PartialEq)]
@@ -10,4 +12,35 @@ pub enum B3Error {
         slice_start: usize,
         slice_end: usize,
     },
+    #[error("Invalid code {code:#x} at index {index}")]
+    InvalidCode { code: u32, index: usize },
+    #[error("Character {ch:?} at index {index} can't be encoded")]
+    UnencodableChar { ch: char, index: usize },
+    #[error("Field of {len} codes is longer than the maximum of {max}")]
+    FieldTooLong { len: usize, max: usize },
+    #[error("Value {value} doesn't fit in {bits} bits")]
+    ValueOutOfRange { value: i128, bits: usize },
+    #[error("Uncorrectable error in the codeword at bit {offset}")]
+    UncorrectableError { offset: usize },
+    #[error("Code {code:#x} has the wrong running disparity")]
+    RunningDisparity { code: u32 },
+    #[error("Code lengths don't make a valid prefix code")]
+    InvalidCodeLengths,
+    #[error("Symbol {symbol} has no code")]
+    UnknownSymbol { symbol: u16 },
+    #[error("Width of {bits} bits isn't between 1 and {max}")]
+    InvalidWidth { bits: usize, max: usize },
+    #[error("Invalid stream at bit {position}: {reason}")]
+    InvalidStream {
+        position: usize,
+        reason: &'static str,
+    },
+    #[error("I/O error: {0:?}")]
+    Io(io::ErrorKind),
+}
+
+impl From<io::Error> for B3Error {
+    fn from(err: io::Error) -> Self {
+        B3Error::Io(err.kind())
+    }
 }
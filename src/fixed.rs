@@ -0,0 +1,251 @@
+use crate::error::{B3Error, B3Result};
+
+/// The layout of a Qm.n fixed-point value: `m` integer bits followed by `n` fractional bits, for
+/// a total width of `m + n` bits.  A signed format is two's complement and its sign bit counts
+/// towards the integer bits, so a signed Q1.15 is 16 bits wide and covers [-1, 1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct QFormat {
+    int_bits: usize,
+    frac_bits: usize,
+    signed: bool,
+}
+
+/// The widest format a value can be read or written in
+const MAX_WIDTH: usize = 64;
+
+impl QFormat {
+    /// Panics if the format is empty, wider than 64 bits, or is signed without any integer bits
+    /// to hold the sign.  Use [`QFormat::try_new`] for formats which aren't known to be valid.
+    pub const fn new(int_bits: usize, frac_bits: usize, signed: bool) -> Self {
+        let width = int_bits + frac_bits;
+        assert!(
+            width > 0 && width <= MAX_WIDTH,
+            "Q format must be 1 to 64 bits wide"
+        );
+        assert!(
+            !signed || int_bits > 0,
+            "Signed Q format needs an integer bit for the sign"
+        );
+        QFormat {
+            int_bits,
+            frac_bits,
+            signed,
+        }
+    }
+
+    /// Like [`QFormat::new`], but returns [`B3Error::InvalidWidth`] instead of panicking if the
+    /// format is invalid.  For a signed format without any integer bits, the error's width is the
+    /// number of integer bits (0).
+    pub fn try_new(int_bits: usize, frac_bits: usize, signed: bool) -> B3Result<Self> {
+        let width = int_bits.saturating_add(frac_bits);
+        if width == 0 || width > MAX_WIDTH {
+            return Err(B3Error::InvalidWidth {
+                bits: width,
+                max: MAX_WIDTH,
+            });
+        }
+        if signed && int_bits == 0 {
+            return Err(B3Error::InvalidWidth {
+                bits: int_bits,
+                max: MAX_WIDTH,
+            });
+        }
+        Ok(QFormat {
+            int_bits,
+            frac_bits,
+            signed,
+        })
+    }
+
+    pub const fn signed(int_bits: usize, frac_bits: usize) -> Self {
+        QFormat::new(int_bits, frac_bits, true)
+    }
+
+    pub const fn unsigned(int_bits: usize, frac_bits: usize) -> Self {
+        QFormat::new(int_bits, frac_bits, false)
+    }
+
+    pub fn int_bits(&self) -> usize {
+        self.int_bits
+    }
+
+    pub fn frac_bits(&self) -> usize {
+        self.frac_bits
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signed
+    }
+
+    /// The width of a value in this format, in bits
+    pub fn width(&self) -> usize {
+        self.int_bits + self.frac_bits
+    }
+
+    fn min_raw(&self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.width() - 1))
+        } else {
+            0
+        }
+    }
+
+    fn max_raw(&self) -> i128 {
+        if self.signed {
+            (1i128 << (self.width() - 1)) - 1
+        } else {
+            (1i128 << self.width()) - 1
+        }
+    }
+
+    /// 2^-frac_bits
+    fn resolution(&self) -> f64 {
+        f64::from_bits(((1023 - self.frac_bits) as u64) << 52)
+    }
+}
+
+/// A fixed-point value: an integer which is scaled by 2^-n, where n is the number of fractional
+/// bits in its [`QFormat`].  Keeping the raw integer means no precision is lost when values are
+/// read and written back, even for formats too wide to be represented exactly by an f64.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fixed {
+    bits: u64,
+    format: QFormat,
+}
+
+impl Fixed {
+    /// Create a fixed-point value from its bits, as they appear in the buffer.  Bits above the
+    /// format's width are ignored.
+    pub fn from_bits(format: QFormat, bits: u64) -> Self {
+        let mask = u64::MAX >> (64 - format.width());
+        Fixed {
+            bits: bits & mask,
+            format,
+        }
+    }
+
+    /// Create a fixed-point value from its (unscaled) integer value, or None if it doesn't fit in
+    /// the format.
+    pub fn from_raw(format: QFormat, raw: i128) -> Option<Self> {
+        if raw < format.min_raw() || raw > format.max_raw() {
+            return None;
+        }
+        Some(Fixed::from_bits(format, raw as u64))
+    }
+
+    /// Convert an f64 to the nearest value in the given format (with ties rounding away from
+    /// zero), or None if it's NaN or out of range.
+    pub fn from_f64(format: QFormat, value: f64) -> Option<Self> {
+        if value.is_nan() {
+            return None;
+        }
+        let scaled = value / format.resolution();
+        // 'as' truncates towards zero (and saturates), so nudge away from zero first to round
+        let rounded = if scaled < 0.0 {
+            (scaled - 0.5) as i128
+        } else {
+            (scaled + 0.5) as i128
+        };
+        Fixed::from_raw(format, rounded)
+    }
+
+    pub fn format(&self) -> QFormat {
+        self.format
+    }
+
+    /// The bits of this value as they appear in the buffer, in the low `width` bits
+    pub fn to_bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// The unscaled integer value, i.e. the value multiplied by 2^frac_bits
+    pub fn raw(&self) -> i128 {
+        let width = self.format.width();
+        if self.format.signed {
+            // Shift the sign bit to the top and back down to sign extend it
+            (((self.bits << (64 - width)) as i64) >> (64 - width)) as i128
+        } else {
+            self.bits as i128
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.raw() as f64 * self.format.resolution()
+    }
+}
+
+impl From<Fixed> for f64 {
+    fn from(value: Fixed) -> Self {
+        value.to_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed() {
+        let q1_15 = QFormat::signed(1, 15);
+        assert_eq!(q1_15.width(), 16);
+        assert_eq!(Fixed::from_bits(q1_15, 0x4000).to_f64(), 0.5);
+        assert_eq!(Fixed::from_bits(q1_15, 0x8000).to_f64(), -1.0);
+        assert_eq!(Fixed::from_bits(q1_15, 0xc000).raw(), -0x4000);
+
+        let value = Fixed::from_f64(q1_15, -0.25).unwrap();
+        assert_eq!(value.to_bits(), 0xe000);
+        assert_eq!(f64::from(value), -0.25);
+        assert_eq!(Fixed::from_f64(q1_15, 1.0), None);
+        assert_eq!(Fixed::from_f64(q1_15, f64::NAN), None);
+    }
+
+    #[test]
+    fn test_unsigned() {
+        // An odd width: 3 integer bits and 2 fractional bits, 0.25 resolution
+        let q3_2 = QFormat::unsigned(3, 2);
+        assert_eq!(Fixed::from_bits(q3_2, 0b11111).to_f64(), 7.75);
+        assert_eq!(Fixed::from_f64(q3_2, 2.6).unwrap().to_bits(), 0b01010);
+        assert_eq!(Fixed::from_f64(q3_2, 8.0), None);
+        assert_eq!(Fixed::from_f64(q3_2, -0.25), None);
+        assert_eq!(Fixed::from_raw(q3_2, 31).unwrap().to_f64(), 7.75);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(
+            QFormat::try_new(3, 2, false).unwrap(),
+            QFormat::unsigned(3, 2)
+        );
+        assert_eq!(
+            QFormat::try_new(32, 32, true).unwrap(),
+            QFormat::signed(32, 32)
+        );
+        assert!(matches!(
+            QFormat::try_new(0, 0, false),
+            Err(B3Error::InvalidWidth { bits: 0, max: 64 })
+        ));
+        assert!(matches!(
+            QFormat::try_new(33, 32, true),
+            Err(B3Error::InvalidWidth { bits: 65, max: 64 })
+        ));
+        assert!(matches!(
+            QFormat::try_new(0, 16, true),
+            Err(B3Error::InvalidWidth { bits: 0, max: 64 })
+        ));
+        assert!(matches!(
+            QFormat::try_new(usize::MAX, 1, false),
+            Err(B3Error::InvalidWidth { .. })
+        ));
+    }
+
+    #[test]
+    fn test_full_width() {
+        let q64_0 = QFormat::unsigned(64, 0);
+        assert_eq!(Fixed::from_bits(q64_0, u64::MAX).raw(), u64::MAX as i128);
+        let q32_32 = QFormat::signed(32, 32);
+        assert_eq!(
+            Fixed::from_bits(q32_32, u64::MAX).to_f64(),
+            -1.0 / 4294967296.0
+        );
+    }
+}
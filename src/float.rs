@@ -0,0 +1,106 @@
+/// Convert the bits of an IEEE 754 half precision float to an f32.  Every half value is exactly
+/// representable as an f32, so this never loses precision.
+pub(crate) fn f16_bits_to_f32(bits: u16) -> f32 {
+    let sign = ((bits as u32) & 0x8000) << 16;
+    let exp = ((bits >> 10) & 0x1f) as u32;
+    let man = (bits & 0x3ff) as u32;
+    match exp {
+        0 => {
+            // Zero or subnormal: man * 2^-24
+            let magnitude = man as f32 * f32::from_bits((127 - 24) << 23);
+            f32::from_bits(sign | magnitude.to_bits())
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (man << 13)),
+        _ => f32::from_bits(sign | ((exp + 127 - 15) << 23) | (man << 13)),
+    }
+}
+
+/// Round `value >> shift` to the nearest integer, with ties going to the even one.
+fn shift_round_even(value: u32, shift: u32) -> u32 {
+    let truncated = value >> shift;
+    let rem = value & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    if rem > halfway || (rem == halfway && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
+
+/// Convert an f32 to the bits of the nearest IEEE 754 half precision float.  Values too large
+/// for a half become infinity, values too small become (signed) zero and NaNs stay NaNs.
+pub(crate) fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let man = bits & 0x7f_ffff;
+    if exp == 0xff {
+        // Keep NaNs quiet so dropping payload bits can't turn one into infinity
+        let nan = if man != 0 {
+            0x200 | (man >> 13) as u16
+        } else {
+            0
+        };
+        return sign | 0x7c00 | nan;
+    }
+    let half_exp = exp - 127 + 15;
+    if half_exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exp <= 0 {
+        if half_exp < -10 {
+            return sign;
+        }
+        // Subnormal half: restore the implicit bit and shift it into place.  Rounding up may
+        // carry into the exponent, which correctly gives the smallest normal value.
+        let man = man | 0x80_0000;
+        return sign | shift_round_even(man, (14 - half_exp) as u32) as u16;
+    }
+    // A carry out of the mantissa bumps the exponent, and may round up to infinity
+    let rounded = shift_round_even(((half_exp as u32) << 23) | man, 13);
+    sign | rounded as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f16_to_f32() {
+        assert_eq!(f16_bits_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_bits_to_f32(0xc000), -2.0);
+        assert_eq!(f16_bits_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_bits_to_f32(0x0001), 5.960_464_5e-8);
+        assert_eq!(f16_bits_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+        assert_eq!(f16_bits_to_f32(0x7c00), f32::INFINITY);
+        assert!(f16_bits_to_f32(0x7e00).is_nan());
+    }
+
+    #[test]
+    fn test_f32_to_f16() {
+        assert_eq!(f32_to_f16_bits(1.0), 0x3c00);
+        assert_eq!(f32_to_f16_bits(-2.0), 0xc000);
+        assert_eq!(f32_to_f16_bits(65504.0), 0x7bff);
+        // Rounds past the largest half to infinity
+        assert_eq!(f32_to_f16_bits(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16_bits(1e10), 0x7c00);
+        assert_eq!(f32_to_f16_bits(5.960_464_5e-8), 0x0001);
+        assert_eq!(f32_to_f16_bits(1e-10), 0x0000);
+        assert_eq!(f32_to_f16_bits(-1e-10), 0x8000);
+        // 1 + 2^-11 (0x3f80_1000) is halfway between 1 and the next half, ties go to the even mantissa
+        assert_eq!(f32_to_f16_bits(f32::from_bits(0x3f80_1000)), 0x3c00);
+        assert_eq!(f32_to_f16_bits(f32::from_bits(0x3f80_3000)), 0x3c02);
+        assert_eq!(f32_to_f16_bits(f32::NAN) & 0x7e00, 0x7e00);
+    }
+
+    #[test]
+    fn test_f16_round_trip() {
+        for bits in 0..=u16::MAX {
+            let value = f16_bits_to_f32(bits);
+            if value.is_nan() {
+                continue;
+            }
+            assert_eq!(f32_to_f16_bits(value), bits);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Canonical Huffman codes, as used by DEFLATE, JPEG and many other formats.
+//!
+//! A [`HuffmanTable`] is built from the length of each symbol's code (as DEFLATE describes its
+//! codes), or from the number of codes of each length and the symbols in code order (as JPEG
+//! does).  Codes are read and written most significant bit first, in the order of the bit
+//! stream.  For formats which pack their bits into bytes LSB first, such as DEFLATE, read and
+//! write through the adaptors in [`bit_order`](crate::bit_order).
+//!
+//! Decoding from a reader which implements [`BitPeek`] looks up the next few bits in a table, and
+//! only falls back to reading bit by bit for longer codes.
+//!
+//! # Example
+//! ```
+//! use b3::{bit_cursor::BitCursor, bit_vec::BitVec, huffman::HuffmanTable};
+//!
+//! // The example from RFC 1951: A to H with code lengths 3, 3, 3, 3, 3, 2, 4, 4
+//! let table = HuffmanTable::from_lengths(&[3, 3, 3, 3, 3, 2, 4, 4]).unwrap();
+//! assert_eq!(table.code(5), Some((0b00, 2)));
+//! assert_eq!(table.code(7), Some((0b1111, 4)));
+//!
+//! let mut bits = BitVec::new();
+//! for symbol in [7, 0, 5] {
+//!     table.encode(symbol, &mut bits).unwrap();
+//! }
+//! let mut cursor = BitCursor::new(bits);
+//! assert_eq!(table.decode(&mut cursor).unwrap(), 7);
+//! assert_eq!(table.decode(&mut cursor).unwrap(), 0);
+//! assert_eq!(table.decode(&mut cursor).unwrap(), 5);
+//! ```
+
+use alloc::{vec, vec::Vec};
+use ux::u1;
+
+use crate::{
+    bit_read::{BitPeek, BitRead},
+    bit_write::BitWrite,
+    error::{B3Error, B3Result},
+    util::write_bits_msb_first,
+};
+
+/// The longest code supported
+pub const MAX_CODE_LENGTH: usize = 32;
+/// The most bits which can be looked up in one go
+pub const MAX_LOOKUP_BITS: usize = 16;
+/// The number of bits looked up by default, if the code has codes this long
+const DEFAULT_LOOKUP_BITS: usize = 10;
+
+/// A canonical Huffman code, mapping symbols (from 0 to 65535) to codes of up to 32 bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HuffmanTable {
+    /// The number of codes of each length
+    counts: [u32; MAX_CODE_LENGTH + 1],
+    /// The symbols, in order of their codes
+    symbols: Vec<u16>,
+    /// The code and length of each symbol, with a length of 0 for symbols without codes
+    codes: Vec<(u32, u8)>,
+    max_length: usize,
+    lookup_bits: usize,
+    /// For each value of the next `lookup_bits` bits, `symbol << 8 | length` for the code they
+    /// start with, or 0 if the code is longer (or there isn't one)
+    lookup: Vec<u32>,
+}
+
+impl HuffmanTable {
+    /// Build a table from the length of each symbol's code, with a length of 0 for symbols which
+    /// don't have codes.  Codes of the same length are assigned in order of their symbols, as in
+    /// DEFLATE.
+    ///
+    /// Returns [`B3Error::InvalidCodeLengths`] if a length is over 32, or there are too many
+    /// short codes to make a prefix code.  There may be too few: bit sequences which aren't
+    /// codes are reported when they're decoded.
+    pub fn from_lengths(lengths: &[u8]) -> B3Result<Self> {
+        if lengths.len() > usize::from(u16::MAX) + 1 {
+            return Err(B3Error::InvalidCodeLengths);
+        }
+        let mut counts = [0u32; MAX_CODE_LENGTH + 1];
+        for &length in lengths {
+            *counts
+                .get_mut(usize::from(length))
+                .ok_or(B3Error::InvalidCodeLengths)? += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u16> = (0..lengths.len())
+            .filter(|&symbol| lengths[symbol] != 0)
+            .map(|symbol| symbol as u16)
+            .collect();
+        symbols.sort_by_key(|&symbol| lengths[usize::from(symbol)]);
+        HuffmanTable::new(counts, symbols)
+    }
+
+    /// Build a table from the number of codes of each length (`counts[i]` being the number of
+    /// codes of length `i + 1`), and the symbols in order of their codes, as in a JPEG DHT
+    /// segment.
+    ///
+    /// Returns [`B3Error::InvalidCodeLengths`] if there are codes over 32 bits long, there are
+    /// too many short codes to make a prefix code, the number of symbols doesn't match the
+    /// counts, or a symbol appears twice.
+    pub fn from_counts(counts: &[u8], symbols: &[u16]) -> B3Result<Self> {
+        if counts.len() > MAX_CODE_LENGTH {
+            return Err(B3Error::InvalidCodeLengths);
+        }
+        let mut all_counts = [0u32; MAX_CODE_LENGTH + 1];
+        for (length, &count) in counts.iter().enumerate() {
+            all_counts[length + 1] = count.into();
+        }
+        let total: u32 = all_counts.iter().sum();
+        if total as usize != symbols.len() {
+            return Err(B3Error::InvalidCodeLengths);
+        }
+        HuffmanTable::new(all_counts, symbols.to_vec())
+    }
+
+    fn new(counts: [u32; MAX_CODE_LENGTH + 1], symbols: Vec<u16>) -> B3Result<Self> {
+        // Check that the codes fit, i.e. the Kraft sum is at most 1
+        let mut available = 1i64;
+        for &count in &counts[1..] {
+            available = available * 2 - i64::from(count);
+            if available < 0 {
+                return Err(B3Error::InvalidCodeLengths);
+            }
+        }
+
+        let num_symbols = symbols
+            .iter()
+            .map(|&s| usize::from(s) + 1)
+            .max()
+            .unwrap_or(0);
+        let mut codes = vec![(0, 0); num_symbols];
+        let mut code = 0u64;
+        let mut index = 0;
+        for (length, &count) in counts.iter().enumerate().skip(1) {
+            for _ in 0..count {
+                let entry = &mut codes[usize::from(symbols[index])];
+                if entry.1 != 0 {
+                    return Err(B3Error::InvalidCodeLengths);
+                }
+                *entry = (code as u32, length as u8);
+                code += 1;
+                index += 1;
+            }
+            code <<= 1;
+        }
+        let max_length = counts.iter().rposition(|&count| count != 0).unwrap_or(0);
+
+        let mut table = HuffmanTable {
+            counts,
+            symbols,
+            codes,
+            max_length,
+            lookup_bits: 0,
+            lookup: Vec::new(),
+        };
+        table.build_lookup(max_length.min(DEFAULT_LOOKUP_BITS));
+        Ok(table)
+    }
+
+    /// Use a lookup table for codes of up to `lookup_bits` bits, instead of the default of up
+    /// to 10.  Longer codes are decoded bit by bit.  Panics if `lookup_bits` is over 16.
+    pub fn with_lookup_bits(mut self, lookup_bits: usize) -> Self {
+        assert!(
+            lookup_bits <= MAX_LOOKUP_BITS,
+            "Huffman lookup tables can be at most 16 bits"
+        );
+        self.build_lookup(lookup_bits);
+        self
+    }
+
+    fn build_lookup(&mut self, lookup_bits: usize) {
+        self.lookup_bits = lookup_bits;
+        self.lookup = vec![0; 1 << lookup_bits];
+        for (symbol, &(code, length)) in self.codes.iter().enumerate() {
+            let length = usize::from(length);
+            if length == 0 || length > lookup_bits {
+                continue;
+            }
+            let start = (code as usize) << (lookup_bits - length);
+            let entry = ((symbol as u32) << 8) | length as u32;
+            self.lookup[start..start + (1 << (lookup_bits - length))].fill(entry);
+        }
+    }
+
+    /// The code for a symbol and its length in bits, or None if the symbol doesn't have a code.
+    pub fn code(&self, symbol: u16) -> Option<(u32, usize)> {
+        match self.codes.get(usize::from(symbol)) {
+            Some(&(code, length)) if length != 0 => Some((code, length.into())),
+            _ => None,
+        }
+    }
+
+    /// The length of the longest code
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    /// The number of bits looked up in one go by [`HuffmanTable::decode`]
+    pub fn lookup_bits(&self) -> usize {
+        self.lookup_bits
+    }
+
+    /// Read and decode the next symbol, using a table lookup for codes of up to
+    /// [`HuffmanTable::lookup_bits`] bits.  Returns [`B3Error::InvalidCode`] if the bits
+    /// aren't a code.
+    pub fn decode<R: BitPeek + ?Sized>(&self, reader: &mut R) -> B3Result<u16> {
+        let mut bits = [u1::new(0); MAX_LOOKUP_BITS];
+        let n = reader.peek(&mut bits[..self.lookup_bits])?;
+        // Bits past the end of the stream are looked up as 0s, so make sure the code fits
+        let index = bits[..n].iter().fold(0usize, |index, &bit| {
+            (index << 1) | usize::from(u8::from(bit))
+        }) << (self.lookup_bits - n);
+        let entry = self.lookup[index];
+        let length = (entry & 0xFF) as usize;
+        if entry != 0 && length <= n {
+            reader.read_exact(&mut bits[..length])?;
+            return Ok((entry >> 8) as u16);
+        }
+        self.decode_bitwise(reader)
+    }
+
+    /// Read and decode the next symbol a bit at a time, from any reader.  Returns
+    /// [`B3Error::InvalidCode`] if the bits aren't a code.
+    pub fn decode_bitwise<R: BitRead + ?Sized>(&self, reader: &mut R) -> B3Result<u16> {
+        // The codes of each length are consecutive, starting from `first`, and their symbols
+        // start at `index`
+        let mut code = 0u64;
+        let mut first = 0u64;
+        let mut index = 0u64;
+        let mut bit = [u1::new(0)];
+        for &count in &self.counts[1..=self.max_length] {
+            reader.read_exact(&mut bit)?;
+            code |= u64::from(u8::from(bit[0]));
+            let count = u64::from(count);
+            if code < first + count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(B3Error::InvalidCode {
+            code: (code >> 1) as u32,
+            index: 0,
+        })
+    }
+
+    /// Write the code for `symbol`, or return [`B3Error::UnknownSymbol`] if it doesn't have
+    /// one.
+    pub fn encode<W: BitWrite + ?Sized>(&self, symbol: u16, writer: &mut W) -> B3Result<()> {
+        let (code, length) = self.code(symbol).ok_or(B3Error::UnknownSymbol { symbol })?;
+        write_bits_msb_first(writer, length, code.into())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bit_cursor::BitCursor,
+        bit_order::{LsbFirstReader, LsbFirstWriter},
+        bit_read_exts::BitReadExts,
+        bit_vec::BitVec,
+        bit_write_exts::BitWriteExts,
+        bitvec,
+    };
+
+    fn deflate_fixed_lengths() -> Vec<u8> {
+        let mut lengths = vec![8; 144];
+        lengths.extend([9; 112]);
+        lengths.extend([7; 24]);
+        lengths.extend([8; 8]);
+        lengths
+    }
+
+    #[test]
+    fn test_deflate_fixed() {
+        // The fixed literal/length code from RFC 1951 (3.2.6)
+        let table = HuffmanTable::from_lengths(&deflate_fixed_lengths()).unwrap();
+        assert_eq!(table.code(0), Some((0b0011_0000, 8)));
+        assert_eq!(table.code(143), Some((0b1011_1111, 8)));
+        assert_eq!(table.code(144), Some((0b1_1001_0000, 9)));
+        assert_eq!(table.code(255), Some((0b1_1111_1111, 9)));
+        assert_eq!(table.code(256), Some((0, 7)));
+        assert_eq!(table.code(280), Some((0b1100_0000, 8)));
+        assert_eq!(table.max_length(), 9);
+    }
+
+    #[test]
+    fn test_jpeg_dc_luminance() {
+        // The example DC luminance table from ITU T.81 (K.3)
+        let counts = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+        let symbols: Vec<u16> = (0..12).collect();
+        let table = HuffmanTable::from_counts(&counts, &symbols).unwrap();
+        assert_eq!(table.code(0), Some((0b00, 2)));
+        assert_eq!(table.code(5), Some((0b110, 3)));
+        assert_eq!(table.code(6), Some((0b1110, 4)));
+        assert_eq!(table.code(11), Some((0b1_1111_1110, 9)));
+
+        // The same bits decode with and without lookups, including codes too long to look up
+        for lookup_bits in [0, 3, 9, 16] {
+            let table = table.clone().with_lookup_bits(lookup_bits);
+            let bits = bitvec!(1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 0);
+            let mut cursor = BitCursor::new(bits);
+            assert_eq!(table.decode(&mut cursor).unwrap(), 11);
+            assert_eq!(table.decode(&mut cursor).unwrap(), 0);
+            assert_eq!(table.decode_bitwise(&mut cursor).unwrap(), 5);
+            assert_eq!(cursor.bits_remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            HuffmanTable::from_lengths(&[1, 1, 1]),
+            Err(B3Error::InvalidCodeLengths)
+        );
+        assert_eq!(
+            HuffmanTable::from_lengths(&[33]),
+            Err(B3Error::InvalidCodeLengths)
+        );
+        assert_eq!(
+            HuffmanTable::from_counts(&[2], &[0, 0]),
+            Err(B3Error::InvalidCodeLengths)
+        );
+        assert_eq!(
+            HuffmanTable::from_counts(&[1, 1], &[0]),
+            Err(B3Error::InvalidCodeLengths)
+        );
+
+        // An incomplete code: 0 and 10, with 11 unused
+        let table = HuffmanTable::from_lengths(&[1, 2]).unwrap();
+        let mut cursor = BitCursor::new(bitvec!(1, 1, 0));
+        assert_eq!(
+            table.decode(&mut cursor),
+            Err(B3Error::InvalidCode { code: 3, index: 0 })
+        );
+        assert_eq!(
+            table.encode(2, &mut BitVec::new()),
+            Err(B3Error::UnknownSymbol { symbol: 2 })
+        );
+        // Running out of bits part way through a code
+        let mut cursor = BitCursor::new(bitvec!(1));
+        assert_eq!(
+            table.decode(&mut cursor),
+            Err(B3Error::Io(crate::io::ErrorKind::UnexpectedEof))
+        );
+    }
+
+    #[test]
+    fn test_lsb_first() {
+        // "a" compressed with a fixed Huffman block: BFINAL = 1 and BTYPE = 01 (packed LSB first),
+        // then the literal and the end of block code
+        let table = HuffmanTable::from_lengths(&deflate_fixed_lengths()).unwrap();
+        let mut writer = LsbFirstWriter::new(BitVec::new());
+        writer.write_u1(u1::new(1)).unwrap();
+        writer.write_u2(ux::u2::new(0b10)).unwrap();
+        table.encode(u16::from(b'a'), &mut writer).unwrap();
+        table.encode(256, &mut writer).unwrap();
+        let bytes = writer.finish().unwrap().to_bytes();
+        assert_eq!(bytes, vec![0x4B, 0x04, 0x00]);
+
+        let mut reader = LsbFirstReader::new(BitCursor::new(BitVec::from_vec(bytes)));
+        assert_eq!(reader.read_u3().unwrap(), ux::u3::new(0b110));
+        assert_eq!(table.decode(&mut reader).unwrap(), u16::from(b'a'));
+        assert_eq!(table.decode(&mut reader).unwrap(), 256);
+    }
+}
@@ -0,0 +1,102 @@
+//! The I/O types used by [`BitRead`](crate::bit_read::BitRead),
+//! [`BitWrite`](crate::bit_write::BitWrite) and [`BitCursor`](crate::bit_cursor::BitCursor).
+//!
+//! With the `std` feature (the default) these are re-exports of the types in [`std::io`].  Without
+//! it, they're minimal crate-local equivalents which work under `#![no_std]`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Result, Seek, SeekFrom};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::{Error, ErrorKind, Result, Seek, SeekFrom};
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use core::fmt;
+
+    /// A list specifying general categories of I/O error, mirroring `std::io::ErrorKind`.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        InvalidInput,
+        InvalidData,
+        UnexpectedEof,
+        WriteZero,
+        Other,
+    }
+
+    impl ErrorKind {
+        fn as_str(&self) -> &'static str {
+            match self {
+                ErrorKind::InvalidInput => "invalid input parameter",
+                ErrorKind::InvalidData => "invalid data",
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::Other => "other error",
+            }
+        }
+    }
+
+    /// The error type for I/O operations, mirroring `std::io::Error`.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: &'static str,
+    }
+
+    impl Error {
+        /// Create a new error from a known kind of error and a message.
+        pub fn new(kind: ErrorKind, message: &'static str) -> Error {
+            Error { kind, message }
+        }
+
+        /// Return the kind of this error.
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Error {
+                kind,
+                message: kind.as_str(),
+            }
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Possible ways to seek within a [`Seek`] implementor, mirroring `std::io::SeekFrom`.
+    #[derive(Copy, PartialEq, Eq, Clone, Debug)]
+    pub enum SeekFrom {
+        Start(u64),
+        End(i64),
+        Current(i64),
+    }
+
+    /// A cursor which can be moved within a stream, mirroring `std::io::Seek`.
+    pub trait Seek {
+        /// Seek to an offset, returning the new position from the start of the stream.
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+        /// Rewind to the beginning of the stream.
+        fn rewind(&mut self) -> Result<()> {
+            self.seek(SeekFrom::Start(0))?;
+            Ok(())
+        }
+
+        /// Return the current seek position from the start of the stream.
+        fn stream_position(&mut self) -> Result<u64> {
+            self.seek(SeekFrom::Current(0))
+        }
+    }
+}
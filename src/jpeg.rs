@@ -0,0 +1,376 @@
+//! Adaptors for the entropy-coded segments of JPEG scans.
+//!
+//! In a scan, each 0xFF byte of entropy-coded data is followed by a stuffed 0x00 byte, so that it
+//! can't be mistaken for the start of a marker.  A [`ScanReader`] removes the stuffed bytes from
+//! the bytes read through it and stops at the next marker, and a [`ScanWriter`] inserts them.
+//!
+//! # Example
+//! ```
+//! use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_vec::BitVec, jpeg::ScanReader};
+//!
+//! let bytes = vec![0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD9];
+//! let mut reader = ScanReader::new(BitCursor::new(BitVec::from_vec(bytes)));
+//! assert_eq!(reader.read_u8().unwrap(), 0x12);
+//! assert_eq!(reader.read_u8().unwrap(), 0xFF);
+//! assert_eq!(reader.read_u8().unwrap(), 0x34);
+//! // The end of image marker ends the data
+//! assert!(reader.read_u1().is_err());
+//! assert_eq!(reader.marker(), Some(0xD9));
+//! ```
+
+use ux::u1;
+
+use crate::{
+    bit_read::{BitPeek, BitRead},
+    bit_write::BitWrite,
+    io,
+    util::{read_bits_msb_first, write_bits_msb_first},
+};
+
+/// The bits of `byte`, MSB first
+fn byte_to_bits(byte: u8) -> [u1; 8] {
+    core::array::from_fn(|i| u1::new((byte >> (7 - i)) & 1))
+}
+
+/// The byte made up of `bits` (at most 8), MSB first
+fn bits_to_byte(bits: &[u1]) -> u8 {
+    bits.iter()
+        .fold(0, |byte, &bit| (byte << 1) | u8::from(bit))
+}
+
+/// A reader which reads the entropy-coded data of a JPEG scan from an inner reader of bytes,
+/// removing the 0x00 stuffed after each 0xFF byte.
+///
+/// Reads stop at the next marker, as if the data had ended there.  [`ScanReader::marker`] gives
+/// the marker, and [`ScanReader::take_marker`] carries on reading past it, e.g. after a restart
+/// (RSTn) marker.  0xFF fill bytes before a marker are skipped.
+#[derive(Debug)]
+pub struct ScanReader<R> {
+    inner: R,
+    byte: [u1; 8],
+    pos: usize,
+    marker: Option<u8>,
+}
+
+impl<R> ScanReader<R> {
+    pub fn new(inner: R) -> Self {
+        ScanReader {
+            inner,
+            byte: [u1::new(0); 8],
+            pos: 8,
+            marker: None,
+        }
+    }
+
+    /// The marker which ended the data, if the reader has reached one.
+    pub fn marker(&self) -> Option<u8> {
+        self.marker
+    }
+
+    /// Return the marker which ended the data, if the reader has reached one, and carry on
+    /// reading the data after it.
+    pub fn take_marker(&mut self) -> Option<u8> {
+        self.marker.take()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Return the inner reader.  The rest of the current byte, if part of it has been read, is
+    /// lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead> ScanReader<R> {
+    /// Read the next byte from the inner reader, or None at its end
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut first = [u1::new(0)];
+        if self.inner.read(&mut first)? == 0 {
+            return Ok(None);
+        }
+        // Once a byte has started, the rest of it must be there
+        let rest = read_bits_msb_first(&mut self.inner, 7)?;
+        Ok(Some((u8::from(first[0]) << 7) | rest as u8))
+    }
+
+    /// Read the next byte of data, returning false at a marker or the end of the inner reader
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.marker.is_some() {
+            return Ok(false);
+        }
+        let byte = match self.read_byte()? {
+            Some(byte) => byte,
+            None => return Ok(false),
+        };
+        if byte == 0xFF {
+            loop {
+                match self.read_byte()? {
+                    Some(0x00) => break,
+                    // Fill bytes before a marker
+                    Some(0xFF) => {}
+                    Some(marker) => {
+                        self.marker = Some(marker);
+                        return Ok(false);
+                    }
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "stream ended after 0xFF",
+                        ))
+                    }
+                }
+            }
+        }
+        self.byte = byte_to_bits(byte);
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+impl<R: BitRead> BitRead for ScanReader<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        for (i, dst) in buf.iter_mut().enumerate() {
+            if self.pos == 8 && !self.fill()? {
+                return Ok(i);
+            }
+            *dst = self.byte[self.pos];
+            self.pos += 1;
+        }
+        Ok(buf.len())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        if self.read(buf)? < buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BitPeek> BitPeek for ScanReader<R> {
+    /// Peeks at most 8 bytes of data past the current byte, and stops short of a 0xFF byte
+    /// whose following byte it can't see.
+    fn peek(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let buffered = (8 - self.pos).min(buf.len());
+        buf[..buffered].copy_from_slice(&self.byte[self.pos..self.pos + buffered]);
+        if buffered == buf.len() || self.marker.is_some() {
+            return Ok(buffered);
+        }
+        let mut upcoming = [u1::new(0); 64];
+        let peeked = self.inner.peek(&mut upcoming)? / 8;
+        let mut bytes = upcoming[..peeked * 8].chunks(8).map(bits_to_byte);
+        let mut n = buffered;
+        while n < buf.len() {
+            let byte = match (bytes.next(), bytes.clone().next()) {
+                (Some(0xFF), Some(0x00)) => {
+                    bytes.next();
+                    0xFF
+                }
+                (Some(0xFF), _) | (None, _) => break,
+                (Some(byte), _) => byte,
+            };
+            let count = 8.min(buf.len() - n);
+            buf[n..n + count].copy_from_slice(&byte_to_bits(byte)[..count]);
+            n += count;
+        }
+        Ok(n)
+    }
+}
+
+/// A writer which packs the bits written to it into bytes, and writes them to an inner writer
+/// as the entropy-coded data of a JPEG scan, stuffing a 0x00 after each 0xFF byte.
+///
+/// Bits are written to the inner writer a whole byte at a time, so use [`ScanWriter::finish`]
+/// to pad and write the last byte.  As JPEG requires, padding bits are 1s.
+#[derive(Debug)]
+pub struct ScanWriter<W> {
+    inner: W,
+    byte: [u1; 8],
+    len: usize,
+}
+
+impl<W> ScanWriter<W> {
+    pub fn new(inner: W) -> Self {
+        ScanWriter {
+            inner,
+            byte: [u1::new(0); 8],
+            len: 0,
+        }
+    }
+
+    /// The number of bits written which don't yet make up a whole byte
+    pub fn pending_bits(&self) -> usize {
+        self.len
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+}
+
+impl<W: BitWrite> ScanWriter<W> {
+    fn flush_byte(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.byte)?;
+        if self.byte == [u1::new(1); 8] {
+            self.inner.write_all(&[u1::new(0); 8])?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Pad the current byte (if part of it has been written) with 1s and write it.
+    pub fn align_to_byte(&mut self) -> io::Result<()> {
+        if self.len != 0 {
+            self.byte[self.len..].fill(u1::new(1));
+            self.flush_byte()?;
+        }
+        Ok(())
+    }
+
+    /// Pad the current byte and write a marker, such as a restart (RSTn) marker, after it.
+    pub fn write_marker(&mut self, marker: u8) -> io::Result<()> {
+        self.align_to_byte()?;
+        write_bits_msb_first(&mut self.inner, 8, 0xFF)?;
+        write_bits_msb_first(&mut self.inner, 8, marker.into())
+    }
+
+    /// Pad and write the last byte, and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.align_to_byte()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: BitWrite> BitWrite for ScanWriter<W> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        for &bit in buf {
+            self.byte[self.len] = bit;
+            self.len += 1;
+            if self.len == 8 {
+                self.flush_byte()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_vec::BitVec,
+        bit_write_exts::BitWriteExts, bitarray, huffman::HuffmanTable,
+    };
+
+    fn scan_reader(bytes: &[u8]) -> ScanReader<BitCursor<BitVec>> {
+        ScanReader::new(BitCursor::new(BitVec::from_vec(bytes.to_vec())))
+    }
+
+    #[test]
+    fn test_restart_markers() {
+        let mut reader = scan_reader(&[0xAB, 0xFF, 0xD0, 0xFF, 0x00, 0xFF, 0xFF, 0xD9]);
+        assert_eq!(reader.read_u8().unwrap(), 0xAB);
+        assert_eq!(reader.read(&mut [u1::new(0); 4]).unwrap(), 0);
+        assert_eq!(reader.marker(), Some(0xD0));
+        assert_eq!(reader.take_marker(), Some(0xD0));
+        assert_eq!(reader.read_u8().unwrap(), 0xFF);
+        // The fill byte before the marker is skipped
+        assert_eq!(reader.read(&mut [u1::new(0); 4]).unwrap(), 0);
+        assert_eq!(reader.take_marker(), Some(0xD9));
+        assert_eq!(reader.read(&mut [u1::new(0); 4]).unwrap(), 0);
+        assert_eq!(reader.marker(), None);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut reader = scan_reader(&[0x12, 0xFF]);
+        assert_eq!(reader.read_u8().unwrap(), 0x12);
+        assert_eq!(
+            reader.read_u1().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut reader = scan_reader(&[0xF0, 0xFF, 0x00, 0x81, 0xFF, 0xD9]);
+        let mut buf = [u1::new(0); 4];
+        reader.read_exact(&mut buf).unwrap();
+        let mut peeked = [u1::new(0); 32];
+        assert_eq!(reader.peek(&mut peeked).unwrap(), 20);
+        assert_eq!(
+            peeked[..20],
+            bitarray!(0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 1)
+        );
+        let mut buf = [u1::new(0); 32];
+        assert_eq!(reader.read(&mut buf).unwrap(), 20);
+        assert_eq!(buf[..20], peeked[..20]);
+        assert_eq!(reader.peek(&mut peeked).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_writer() {
+        let mut writer = ScanWriter::new(BitVec::new());
+        writer.write_u8(0xFF).unwrap();
+        writer.write_u8(0x12).unwrap();
+        writer.write_all(&bitarray!(1, 0)).unwrap();
+        writer.write_marker(0xD0).unwrap();
+        // Padding which makes a 0xFF byte is stuffed too
+        writer.write_all(&bitarray!(1, 1, 1)).unwrap();
+        assert_eq!(writer.pending_bits(), 3);
+        let bytes = writer.finish().unwrap().to_bytes();
+        assert_eq!(bytes, vec![0xFF, 0x00, 0x12, 0xBF, 0xFF, 0xD0, 0xFF, 0x00]);
+    }
+
+    #[test]
+    fn test_huffman() {
+        // The luminance DC table from the JPEG spec (K.3), in which 11's code is
+        // 0b1_1111_1110
+        let table = HuffmanTable::from_counts(
+            &[0, 1, 5, 1, 1, 1, 1, 1, 1],
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        )
+        .unwrap();
+        let symbols = [11, 11, 11, 0, 11, 5, 11, 11, 11, 11];
+        let mut writer = ScanWriter::new(BitVec::new());
+        for symbol in symbols {
+            table.encode(symbol, &mut writer).unwrap();
+        }
+        writer.write_marker(0xD9).unwrap();
+        let bytes = writer.finish().unwrap().to_bytes();
+        assert!(bytes.windows(2).any(|pair| pair == [0xFF, 0x00]));
+
+        let mut reader = scan_reader(&bytes);
+        for symbol in symbols {
+            assert_eq!(table.decode(&mut reader).unwrap(), symbol);
+        }
+        let mut reader = scan_reader(&bytes);
+        for symbol in symbols {
+            assert_eq!(table.decode_bitwise(&mut reader).unwrap(), symbol);
+        }
+        // Only padding is left before the marker
+        let mut rest = [u1::new(0); 8];
+        let n = reader.read(&mut rest).unwrap();
+        assert!(rest[..n].iter().all(|&bit| bit == u1::new(1)));
+        assert_eq!(reader.marker(), Some(0xD9));
+    }
+}
@@ -0,0 +1,387 @@
+//! Linear feedback shift registers, and the scramblers built from them.
+//!
+//! Polynomials are written the way scrambler specifications give them, e.g. `x^7 + x^4 + 1` for
+//! 802.11: bit `k - 1` of the polynomial is set for each `x^k` term, and the `1` term is implied.
+//! The `x^width` term must be present, so 802.11's polynomial is `0b100_1000`.
+//!
+//! # Example
+//! ```
+//! use b3::lfsr::Lfsr;
+//! use ux::u1;
+//!
+//! // The 802.11 scrambler sequence, from the all ones state
+//! let lfsr = Lfsr::fibonacci(7, 0b100_1000, 0x7F);
+//! let start: Vec<u8> = lfsr.take(8).map(u8::from).collect();
+//! assert_eq!(start, [0, 0, 0, 0, 1, 1, 1, 0]);
+//! ```
+
+use ux::u1;
+
+use crate::{bit_read::BitRead, bit_write::BitWrite, io};
+
+/// How an [`Lfsr`] applies its feedback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LfsrKind {
+    /// The taps are XORed together and shifted in, and that feedback bit is the output.  Bit
+    /// `k - 1` of the state holds the bit from `k` steps ago.
+    Fibonacci,
+    /// The bit shifted out of the top of the state is the output, and is XORed into the state at
+    /// each tap, as in PCIe.
+    Galois,
+}
+
+/// A linear feedback shift register of 1 to 64 bits.
+///
+/// An `Lfsr` is an endless iterator over the bits of its sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lfsr {
+    kind: LfsrKind,
+    width: usize,
+    poly: u64,
+    state: u64,
+}
+
+impl Lfsr {
+    /// Panics if `width` isn't between 1 and 64, or `poly` doesn't have exactly `width` bits.
+    /// Bits of `seed` above `width` are ignored.
+    pub const fn new(kind: LfsrKind, width: usize, poly: u64, seed: u64) -> Self {
+        assert!(width > 0 && width <= 64, "LFSR must be 1 to 64 bits wide");
+        let mask = u64::MAX >> (64 - width);
+        assert!(
+            poly & !mask == 0 && poly >> (width - 1) == 1,
+            "LFSR polynomial must have an x^width term and no higher terms"
+        );
+        Lfsr {
+            kind,
+            width,
+            poly,
+            state: seed & mask,
+        }
+    }
+
+    pub const fn fibonacci(width: usize, poly: u64, seed: u64) -> Self {
+        Lfsr::new(LfsrKind::Fibonacci, width, poly, seed)
+    }
+
+    pub const fn galois(width: usize, poly: u64, seed: u64) -> Self {
+        Lfsr::new(LfsrKind::Galois, width, poly, seed)
+    }
+
+    pub fn kind(&self) -> LfsrKind {
+        self.kind
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn poly(&self) -> u64 {
+        self.poly
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Reload the register, e.g. at the start of a frame.  Bits of `seed` above the register's
+    /// width are ignored.
+    pub fn set_state(&mut self, seed: u64) {
+        self.state = seed & self.mask();
+    }
+
+    /// Step the register, returning the next bit of the sequence.
+    pub fn next_bit(&mut self) -> u1 {
+        match self.kind {
+            LfsrKind::Fibonacci => {
+                let bit = self.feedback();
+                self.shift_in(bit);
+                bit
+            }
+            LfsrKind::Galois => {
+                let bit = self.state >> (self.width - 1);
+                self.state = (self.state << 1) & self.mask();
+                if bit == 1 {
+                    // The x^width term wraps around to the implied 1 term
+                    self.state ^= ((self.poly << 1) | 1) & self.mask();
+                }
+                u1::new(bit as u8)
+            }
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+
+    /// The XOR of the Fibonacci taps
+    fn feedback(&self) -> u1 {
+        u1::new(((self.state & self.poly).count_ones() & 1) as u8)
+    }
+
+    fn shift_in(&mut self, bit: u1) {
+        self.state = ((self.state << 1) | u64::from(u8::from(bit))) & self.mask();
+    }
+}
+
+impl Iterator for Lfsr {
+    type Item = u1;
+
+    fn next(&mut self) -> Option<u1> {
+        Some(self.next_bit())
+    }
+}
+
+/// An additive (synchronous) scrambler, which XORs each bit with the next bit of an LFSR's
+/// sequence.  This is its own inverse, so the same adaptor scrambles or descrambles the bits
+/// read from or written to the inner reader or writer.  Both ends must start with the same
+/// state, so it's usually reloaded with [`Lfsr::set_state`] at the start of each frame.
+#[derive(Debug)]
+pub struct AdditiveScrambler<T> {
+    inner: T,
+    lfsr: Lfsr,
+}
+
+impl<T> AdditiveScrambler<T> {
+    pub fn new(inner: T, lfsr: Lfsr) -> Self {
+        AdditiveScrambler { inner, lfsr }
+    }
+
+    pub fn lfsr(&self) -> &Lfsr {
+        &self.lfsr
+    }
+
+    pub fn lfsr_mut(&mut self) -> &mut Lfsr {
+        &mut self.lfsr
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn scramble(&mut self, bits: &mut [u1]) {
+        for bit in bits {
+            *bit ^= self.lfsr.next_bit();
+        }
+    }
+}
+
+impl<R: BitRead> BitRead for AdditiveScrambler<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.scramble(&mut buf[..n]);
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        self.inner.read_exact(buf)?;
+        self.scramble(buf);
+        Ok(())
+    }
+}
+
+impl<W: BitWrite> BitWrite for AdditiveScrambler<W> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        let mut scrambled = [u1::new(0); 64];
+        for chunk in buf.chunks(scrambled.len()) {
+            let scrambled = &mut scrambled[..chunk.len()];
+            scrambled.copy_from_slice(chunk);
+            self.scramble(scrambled);
+            self.inner.write_all(scrambled)?;
+        }
+        Ok(())
+    }
+}
+
+/// A self-synchronizing (multiplicative) scrambler, which XORs each bit with the taps of a
+/// Fibonacci LFSR holding the previous scrambled bits.  A descrambler recovers the data after
+/// `width` bits, whatever state it started in.
+///
+/// Whether the adaptor scrambles or descrambles depends on how it was created, not on whether
+/// it's used for reading or writing.
+#[derive(Debug)]
+pub struct SelfSyncScrambler<T> {
+    inner: T,
+    lfsr: Lfsr,
+    descramble: bool,
+}
+
+impl<T> SelfSyncScrambler<T> {
+    /// Create an adaptor which scrambles the bits read or written.  Panics if `lfsr` isn't a
+    /// Fibonacci LFSR.
+    pub fn scrambler(inner: T, lfsr: Lfsr) -> Self {
+        SelfSyncScrambler::new(inner, lfsr, false)
+    }
+
+    /// Create an adaptor which descrambles the bits read or written.  Panics if `lfsr` isn't a
+    /// Fibonacci LFSR.
+    pub fn descrambler(inner: T, lfsr: Lfsr) -> Self {
+        SelfSyncScrambler::new(inner, lfsr, true)
+    }
+
+    fn new(inner: T, lfsr: Lfsr, descramble: bool) -> Self {
+        assert_eq!(
+            lfsr.kind(),
+            LfsrKind::Fibonacci,
+            "Self-synchronizing scramblers need a Fibonacci LFSR"
+        );
+        SelfSyncScrambler {
+            inner,
+            lfsr,
+            descramble,
+        }
+    }
+
+    pub fn lfsr(&self) -> &Lfsr {
+        &self.lfsr
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn process(&mut self, bits: &mut [u1]) {
+        for bit in bits {
+            let input = *bit;
+            *bit = input ^ self.lfsr.feedback();
+            // The register always holds the scrambled bits
+            self.lfsr
+                .shift_in(if self.descramble { input } else { *bit });
+        }
+    }
+}
+
+impl<R: BitRead> BitRead for SelfSyncScrambler<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.process(&mut buf[..n]);
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        self.inner.read_exact(buf)?;
+        self.process(buf);
+        Ok(())
+    }
+}
+
+impl<W: BitWrite> BitWrite for SelfSyncScrambler<W> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        let mut processed = [u1::new(0); 64];
+        for chunk in buf.chunks(processed.len()) {
+            let processed = &mut processed[..chunk.len()];
+            processed.copy_from_slice(chunk);
+            self.process(processed);
+            self.inner.write_all(processed)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_vec::BitVec,
+        bit_write_exts::BitWriteExts, byte_order::BigEndian,
+    };
+
+    fn sequence(lfsr: Lfsr, n: usize) -> BitVec {
+        let mut vec = BitVec::new();
+        lfsr.take(n).for_each(|bit| vec.push(bit));
+        vec
+    }
+
+    #[test]
+    fn test_802_11() {
+        // The 127 bit sequence from IEEE 802.11 (17.3.5.5), which then repeats
+        let expected = [
+            0x0E, 0xF2, 0xC9, 0x02, 0x26, 0x2E, 0xB6, 0x0C, 0xD4, 0xE7, 0xB4, 0x2A, 0xFA, 0x51,
+            0xB8, 0xFE,
+        ];
+        let mut lfsr = Lfsr::fibonacci(7, 0b100_1000, 0x7F);
+        assert_eq!(sequence(lfsr, 127), BitVec::from_bytes(&expected, 127));
+        lfsr.nth(126);
+        assert_eq!(lfsr.state(), 0x7F);
+    }
+
+    #[test]
+    fn test_dvb() {
+        // The DVB energy dispersal PRBS, 1 + x^14 + x^15, loaded with 100101010000000
+        let lfsr = Lfsr::fibonacci(15, 0x6000, 0b000_0000_1010_1001);
+        assert_eq!(sequence(lfsr, 32).to_bytes(), vec![0x03, 0xF6, 0x08, 0x34]);
+    }
+
+    #[test]
+    fn test_pcie() {
+        // The PCIe 1.x scrambler, x^16 + x^5 + x^4 + x^3 + 1 in Galois form, seeded with FFFF,
+        // whose bytes are sent LSB first
+        let lfsr = Lfsr::galois(16, 0x801C, 0xFFFF);
+        let bytes = sequence(lfsr, 64).to_bytes();
+        let bytes: Vec<u8> = bytes.into_iter().map(u8::reverse_bits).collect();
+        assert_eq!(bytes, vec![0xFF, 0x17, 0xC0, 0x14, 0xB2, 0xE7, 0x02, 0x82]);
+    }
+
+    #[test]
+    fn test_additive() {
+        let lfsr = Lfsr::fibonacci(7, 0b100_1000, 0x7F);
+        let mut scrambler = AdditiveScrambler::new(BitVec::new(), lfsr);
+        scrambler.write_u16::<BigEndian>(0).unwrap();
+        scrambler.write_u16::<BigEndian>(0xFFFF).unwrap();
+        let scrambled = scrambler.into_inner();
+        // Scrambling zeros gives the sequence itself
+        assert_eq!(scrambled.to_bytes(), vec![0x0E, 0xF2, 0x36, 0xFD]);
+
+        let mut descrambler = AdditiveScrambler::new(BitCursor::new(scrambled), lfsr);
+        assert_eq!(descrambler.read_u16::<BigEndian>().unwrap(), 0);
+        assert_eq!(descrambler.read_u16::<BigEndian>().unwrap(), 0xFFFF);
+    }
+
+    #[test]
+    fn test_self_sync() {
+        // The 64b/66b scrambler, 1 + x^39 + x^58
+        let poly = (1 << 57) | (1 << 38);
+        let data: Vec<u64> = (0..8).map(|i| 0x0123_4567_89AB_CDEF * i).collect();
+        let mut scrambler =
+            SelfSyncScrambler::scrambler(BitVec::new(), Lfsr::fibonacci(58, poly, 0x2A));
+        for &word in &data {
+            scrambler.write_u64::<BigEndian>(word).unwrap();
+        }
+        let scrambled = scrambler.into_inner();
+        assert_ne!(scrambled.to_bytes()[..8], [0; 8]);
+
+        // A descrambler with the wrong state recovers once the register is full of
+        // scrambled bits
+        let mut descrambler =
+            SelfSyncScrambler::descrambler(BitCursor::new(scrambled), Lfsr::fibonacci(58, poly, 0));
+        assert_ne!(descrambler.read_u64::<BigEndian>().unwrap(), data[0]);
+        for &word in &data[1..] {
+            assert_eq!(descrambler.read_u64::<BigEndian>().unwrap(), word);
+        }
+    }
+}
@@ -1,17 +1,45 @@
 //#![feature(trace_macros)]
 //trace_macros!(true);
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 pub mod bit_buffer;
+#[cfg(feature = "alloc")]
+pub mod bit_bytes;
 pub mod bit_cursor;
+pub mod bit_order;
 pub mod bit_read;
 pub mod bit_read_exts;
+#[cfg(feature = "serde")]
+pub mod bit_serde;
+pub mod bit_store;
 pub mod bit_traits;
+#[cfg(feature = "alloc")]
 pub mod bit_vec;
 pub mod bit_write;
 pub mod bit_write_exts;
 pub mod byte_order;
+pub mod crc;
+#[cfg(feature = "alloc")]
+pub mod deflate;
+pub mod ecc;
+pub mod encoding;
 pub mod error;
+pub mod fixed;
+mod float;
+#[cfg(feature = "alloc")]
+pub mod huffman;
+pub mod io;
+pub mod jpeg;
+pub mod lfsr;
+pub mod line_code;
+mod macros;
 pub mod slice;
+pub mod stuffing;
+#[cfg(feature = "alloc")]
+pub mod text;
 mod util;
 
 pub use ux;
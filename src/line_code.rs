@@ -0,0 +1,875 @@
+//! Line codes, which turn data bits into the bits sent on a wire (or seen by a logic analyzer or
+//! SDR) and back.
+//!
+//! Each code implements [`LineCode`], which converts a fixed size symbol at a time.  A
+//! [`LineEncoder`] encodes the bits written to it, and a [`LineDecoder`] decodes the bits read
+//! through it, reporting where the line bits broke the code's rules.
+//!
+//! # Example
+//! ```
+//! use b3::{
+//!     bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_vec::BitVec,
+//!     bit_write_exts::BitWriteExts, line_code::{LineDecoder, LineEncoder, Manchester},
+//! };
+//!
+//! let mut encoder = LineEncoder::new(BitVec::new(), Manchester::Ieee);
+//! encoder.write_u8(0x5A).unwrap();
+//! let line = encoder.finish().unwrap();
+//! assert_eq!(line.to_bytes(), vec![0x99, 0x66]);
+//!
+//! let mut decoder = LineDecoder::new(BitCursor::new(line), Manchester::Ieee);
+//! assert_eq!(decoder.read_u8().unwrap(), 0x5A);
+//! ```
+
+use ux::u1;
+
+use crate::{
+    bit_read::BitRead,
+    bit_write::BitWrite,
+    error::{B3Error, B3Result},
+    io,
+};
+
+/// The most data bits in a symbol of any of the codes here
+const MAX_DATA_BITS: usize = 8;
+/// The most line bits in a symbol of any of the codes here
+const MAX_CODE_BITS: usize = 10;
+
+/// A line code, which encodes a fixed number of data bits as a fixed number of line bits.
+pub trait LineCode {
+    /// The number of data bits in each symbol (at most 8).
+    fn data_bits(&self) -> usize;
+
+    /// The number of line bits in each symbol (at most 10).
+    fn code_bits(&self) -> usize;
+
+    /// Encode one symbol's worth of `data` bits into `code`.
+    fn encode(&mut self, data: &[u1], code: &mut [u1]);
+
+    /// Decode one symbol's worth of `code` bits into `data`, or return an error if they aren't
+    /// valid for the code.
+    fn decode(&mut self, code: &[u1], data: &mut [u1]) -> B3Result<()>;
+}
+
+fn bits_to_u32(bits: &[u1]) -> u32 {
+    bits.iter()
+        .fold(0, |value, &bit| (value << 1) | u32::from(u8::from(bit)))
+}
+
+fn u32_to_bits(value: u32, bits: &mut [u1]) {
+    let n = bits.len();
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = u1::new(((value >> (n - 1 - i)) & 1) as u8);
+    }
+}
+
+/// Non-return-to-zero inverted: each bit is sent as either a change of level or no change.
+///
+/// NRZI has no invalid codes, so decoding never fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Nrzi {
+    transition_on: u1,
+    level: u1,
+}
+
+impl Nrzi {
+    /// NRZ-M, where a 1 is sent as a change of level (e.g. HDLC in NRZI mode), starting from
+    /// `level`.
+    pub const fn mark(level: u1) -> Self {
+        Nrzi {
+            transition_on: u1::new(1),
+            level,
+        }
+    }
+
+    /// NRZ-S, where a 0 is sent as a change of level, starting from `level`.  This is the NRZI
+    /// used by USB, where the idle J state is 1 at full speed.
+    pub const fn space(level: u1) -> Self {
+        Nrzi {
+            transition_on: u1::new(0),
+            level,
+        }
+    }
+
+    /// The current line level
+    pub fn level(&self) -> u1 {
+        self.level
+    }
+}
+
+impl LineCode for Nrzi {
+    fn data_bits(&self) -> usize {
+        1
+    }
+
+    fn code_bits(&self) -> usize {
+        1
+    }
+
+    fn encode(&mut self, data: &[u1], code: &mut [u1]) {
+        if data[0] == self.transition_on {
+            self.level ^= u1::new(1);
+        }
+        code[0] = self.level;
+    }
+
+    fn decode(&mut self, code: &[u1], data: &mut [u1]) -> B3Result<()> {
+        data[0] = if code[0] == self.level {
+            self.transition_on ^ u1::new(1)
+        } else {
+            self.transition_on
+        };
+        self.level = code[0];
+        Ok(())
+    }
+}
+
+/// Manchester code, where each bit is sent as two half-bits with a transition in the middle.
+/// A pair of equal half-bits is a code violation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Manchester {
+    /// IEEE 802.3: a 0 is sent as high then low (10), and a 1 as low then high (01).
+    Ieee,
+    /// G. E. Thomas' convention: a 0 is sent as low then high (01), and a 1 as high then low
+    /// (10).
+    Thomas,
+}
+
+impl LineCode for Manchester {
+    fn data_bits(&self) -> usize {
+        1
+    }
+
+    fn code_bits(&self) -> usize {
+        2
+    }
+
+    fn encode(&mut self, data: &[u1], code: &mut [u1]) {
+        let first = match self {
+            Manchester::Ieee => data[0] ^ u1::new(1),
+            Manchester::Thomas => data[0],
+        };
+        code[0] = first;
+        code[1] = first ^ u1::new(1);
+    }
+
+    fn decode(&mut self, code: &[u1], data: &mut [u1]) -> B3Result<()> {
+        if code[0] == code[1] {
+            return Err(B3Error::InvalidCode {
+                code: bits_to_u32(&code[..2]),
+                index: 0,
+            });
+        }
+        data[0] = match self {
+            Manchester::Ieee => code[1],
+            Manchester::Thomas => code[0],
+        };
+        Ok(())
+    }
+}
+
+/// Differential Manchester code (as used by IEEE 802.5), where there's always a transition in the
+/// middle of each bit, and a 0 also has a transition at the start of the bit.  A bit without a
+/// transition in the middle is a code violation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DifferentialManchester {
+    level: u1,
+}
+
+impl DifferentialManchester {
+    /// Start from the given line level
+    pub const fn new(level: u1) -> Self {
+        DifferentialManchester { level }
+    }
+
+    /// The current line level
+    pub fn level(&self) -> u1 {
+        self.level
+    }
+}
+
+impl LineCode for DifferentialManchester {
+    fn data_bits(&self) -> usize {
+        1
+    }
+
+    fn code_bits(&self) -> usize {
+        2
+    }
+
+    fn encode(&mut self, data: &[u1], code: &mut [u1]) {
+        code[0] = if data[0] == u1::new(0) {
+            self.level ^ u1::new(1)
+        } else {
+            self.level
+        };
+        code[1] = code[0] ^ u1::new(1);
+        self.level = code[1];
+    }
+
+    fn decode(&mut self, code: &[u1], data: &mut [u1]) -> B3Result<()> {
+        if code[0] == code[1] {
+            return Err(B3Error::InvalidCode {
+                code: bits_to_u32(&code[..2]),
+                index: 0,
+            });
+        }
+        data[0] = if code[0] == self.level {
+            u1::new(1)
+        } else {
+            u1::new(0)
+        };
+        self.level = code[1];
+        Ok(())
+    }
+}
+
+/// 4B5B code groups for the data nibbles 0 to F, from FDDI and 100BASE-X
+const CODE_4B5B: [u8; 16] = [
+    0b11110, 0b01001, 0b10100, 0b10101, 0b01010, 0b01011, 0b01110, 0b01111, 0b10010, 0b10011,
+    0b10110, 0b10111, 0b11010, 0b11011, 0b11100, 0b11101,
+];
+
+/// 4B5B code (FDDI, 100BASE-X), where each nibble is sent as a 5 bit code group.  Both are
+/// MSB first.  Control code groups (J, K, T, R, idle and so on) and unused code groups are code
+/// violations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Code4b5b;
+
+impl LineCode for Code4b5b {
+    fn data_bits(&self) -> usize {
+        4
+    }
+
+    fn code_bits(&self) -> usize {
+        5
+    }
+
+    fn encode(&mut self, data: &[u1], code: &mut [u1]) {
+        let nibble = bits_to_u32(&data[..4]);
+        u32_to_bits(CODE_4B5B[nibble as usize].into(), &mut code[..5]);
+    }
+
+    fn decode(&mut self, code: &[u1], data: &mut [u1]) -> B3Result<()> {
+        let group = bits_to_u32(&code[..5]);
+        let nibble = CODE_4B5B
+            .iter()
+            .position(|&g| u32::from(g) == group)
+            .ok_or(B3Error::InvalidCode {
+                code: group,
+                index: 0,
+            })?;
+        u32_to_bits(nibble as u32, &mut data[..4]);
+        Ok(())
+    }
+}
+
+/// An 8b/10b symbol: a data byte (D.x.y) or one of the control symbols (K.x.y).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symbol8b10b {
+    Data(u8),
+    Control(u8),
+}
+
+/// 5b/6b code blocks (abcdei) for EDCBA = 0 to 31, with negative running disparity
+const CODE_5B6B: [u8; 32] = [
+    0b100111, 0b011101, 0b101101, 0b110001, 0b110101, 0b101001, 0b011001, 0b111000, 0b111001,
+    0b100101, 0b010101, 0b110100, 0b001101, 0b101100, 0b011100, 0b010111, 0b011011, 0b100011,
+    0b010011, 0b110010, 0b001011, 0b101010, 0b011010, 0b111010, 0b110011, 0b100110, 0b010110,
+    0b110110, 0b001110, 0b101110, 0b011110, 0b101011,
+];
+/// The 5b/6b code block for K.28, with negative running disparity
+const CODE_K28_6B: u8 = 0b001111;
+/// 3b/4b code blocks (fghj) for HGF = 0 to 7, with negative running disparity
+const CODE_3B4B: [u8; 8] = [
+    0b1011, 0b1001, 0b0101, 0b1100, 0b1101, 0b1010, 0b0110, 0b1110,
+];
+/// The alternate 3b/4b code block for HGF = 7, with negative running disparity
+const CODE_A7: u8 = 0b0111;
+
+const fn is_control_symbol(value: u8) -> bool {
+    matches!(
+        value,
+        0x1C | 0x3C | 0x5C | 0x7C | 0x9C | 0xBC | 0xDC | 0xFC | 0xF7 | 0xFB | 0xFD | 0xFE
+    )
+}
+
+/// Complement a block if it's unbalanced, or is one of the balanced blocks which has two forms
+/// (D.07 and D.x.3), to get its form for positive running disparity
+const fn complement_for_positive(block: u8, width: u32, has_two_forms: bool) -> u8 {
+    if block.count_ones() * 2 != width || has_two_forms {
+        !block & ((1 << width) - 1)
+    } else {
+        block
+    }
+}
+
+/// The running disparity after a `width` bit block, given the running disparity before it
+const fn disparity_after(block: u16, width: u32, rd_positive: bool) -> bool {
+    let ones = block.count_ones() * 2;
+    if ones == width {
+        rd_positive
+    } else {
+        ones > width
+    }
+}
+
+/// Encode a symbol, which must be valid, as its 10 bit code (abcdeifghj, with a as the MSB)
+const fn encode_8b10b(value: u8, control: bool, rd_positive: bool) -> u16 {
+    let x = (value & 0x1F) as usize;
+    let y = (value >> 5) as usize;
+    if control {
+        // Control symbols all have unbalanced 6b blocks with negative running disparity, so
+        // their 4b blocks follow positive disparity, and they always use the alternate D.x.A7
+        // block.  Their forms for positive running disparity are the complements of the whole
+        // code.
+        let six = if x == 28 { CODE_K28_6B } else { CODE_5B6B[x] };
+        let four = if y == 7 { CODE_A7 } else { CODE_3B4B[y] };
+        let four = complement_for_positive(four, 4, y == 3);
+        let code = ((six as u16) << 4) | four as u16;
+        return if rd_positive { !code & 0x3FF } else { code };
+    }
+    let six = if rd_positive {
+        complement_for_positive(CODE_5B6B[x], 6, x == 7)
+    } else {
+        CODE_5B6B[x]
+    };
+    let rd_positive = disparity_after(six as u16, 6, rd_positive);
+    let use_a7 = y == 7
+        && if rd_positive {
+            matches!(x, 11 | 13 | 14)
+        } else {
+            matches!(x, 17 | 18 | 20)
+        };
+    let four = if use_a7 { CODE_A7 } else { CODE_3B4B[y] };
+    let four = if rd_positive {
+        complement_for_positive(four, 4, y == 3)
+    } else {
+        four
+    };
+    ((six as u16) << 4) | four as u16
+}
+
+/// Marks an unused entry in [`DECODE_8B10B`]
+const INVALID_8B10B: u16 = 0xFFFF;
+
+/// The symbols for each 10 bit code, indexed by running disparity (negative, then positive).
+/// Control symbols have bit 8 set.
+const DECODE_8B10B: [[u16; 1024]; 2] = {
+    let mut table = [[INVALID_8B10B; 1024]; 2];
+    let mut symbol = 0;
+    while symbol < 512 {
+        let value = (symbol & 0xFF) as u8;
+        let control = symbol > 0xFF;
+        if !control || is_control_symbol(value) {
+            table[0][encode_8b10b(value, control, false) as usize] = symbol as u16;
+            table[1][encode_8b10b(value, control, true) as usize] = symbol as u16;
+        }
+        symbol += 1;
+    }
+    table
+};
+
+/// 8b/10b code (Fibre Channel, Gigabit Ethernet, PCIe 1 and 2, SATA, USB 3 and more), which
+/// keeps the line DC balanced by tracking the running disparity.
+///
+/// As a [`LineCode`], each byte is taken MSB first (HGFEDCBA) and sent as a 10 bit code in
+/// transmission order (abcdeifghj), and control symbols are code violations.  To handle control
+/// symbols such as the K.28.5 comma, use [`Code8b10b::encode_symbol`] and
+/// [`Code8b10b::decode_symbol`] directly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Code8b10b {
+    rd_positive: bool,
+}
+
+impl Code8b10b {
+    /// Start with negative running disparity
+    pub const fn new() -> Self {
+        Code8b10b { rd_positive: false }
+    }
+
+    /// Whether the current running disparity is positive
+    pub fn is_rd_positive(&self) -> bool {
+        self.rd_positive
+    }
+
+    /// Encode a symbol as a 10 bit code, with `a` in bit 9 and `j` in bit 0, or return
+    /// [`B3Error::InvalidCode`] if it's not one of the 12 control symbols.
+    pub fn encode_symbol(&mut self, symbol: Symbol8b10b) -> B3Result<u16> {
+        let code = match symbol {
+            Symbol8b10b::Data(value) => encode_8b10b(value, false, self.rd_positive),
+            Symbol8b10b::Control(value) if is_control_symbol(value) => {
+                encode_8b10b(value, true, self.rd_positive)
+            }
+            Symbol8b10b::Control(value) => {
+                return Err(B3Error::InvalidCode {
+                    code: value.into(),
+                    index: 0,
+                })
+            }
+        };
+        self.rd_positive = disparity_after(code, 10, self.rd_positive);
+        Ok(code)
+    }
+
+    /// Decode a 10 bit code, with `a` in bit 9 and `j` in bit 0.  Returns
+    /// [`B3Error::InvalidCode`] if it's not a code at all, or [`B3Error::RunningDisparity`] if
+    /// it's a code for the other running disparity.  In the latter case the running disparity
+    /// is resynchronized to the code.
+    pub fn decode_symbol(&mut self, code: u16) -> B3Result<Symbol8b10b> {
+        let code = code & 0x3FF;
+        let symbol = DECODE_8B10B[self.rd_positive as usize][code as usize];
+        if symbol == INVALID_8B10B {
+            let other = !self.rd_positive;
+            if DECODE_8B10B[other as usize][code as usize] == INVALID_8B10B {
+                return Err(B3Error::InvalidCode {
+                    code: code.into(),
+                    index: 0,
+                });
+            }
+            self.rd_positive = disparity_after(code, 10, other);
+            return Err(B3Error::RunningDisparity { code: code.into() });
+        }
+        self.rd_positive = disparity_after(code, 10, self.rd_positive);
+        if symbol > 0xFF {
+            Ok(Symbol8b10b::Control(symbol as u8))
+        } else {
+            Ok(Symbol8b10b::Data(symbol as u8))
+        }
+    }
+}
+
+impl LineCode for Code8b10b {
+    fn data_bits(&self) -> usize {
+        8
+    }
+
+    fn code_bits(&self) -> usize {
+        10
+    }
+
+    fn encode(&mut self, data: &[u1], code: &mut [u1]) {
+        let value = bits_to_u32(&data[..8]) as u8;
+        let symbol = self
+            .encode_symbol(Symbol8b10b::Data(value))
+            .expect("data symbols are always valid");
+        u32_to_bits(symbol.into(), &mut code[..10]);
+    }
+
+    fn decode(&mut self, code: &[u1], data: &mut [u1]) -> B3Result<()> {
+        let code = bits_to_u32(&code[..10]);
+        match self.decode_symbol(code as u16)? {
+            Symbol8b10b::Data(value) => {
+                u32_to_bits(value.into(), &mut data[..8]);
+                Ok(())
+            }
+            Symbol8b10b::Control(_) => Err(B3Error::InvalidCode { code, index: 0 }),
+        }
+    }
+}
+
+/// A writer which encodes the bits written to it with a [`LineCode`], and writes the line bits
+/// to an inner writer.
+///
+/// Bits are encoded a whole symbol at a time, so bits which don't fill a symbol are held until
+/// more are written.  Use [`LineEncoder::finish`] to check that there are none left over.
+#[derive(Debug)]
+pub struct LineEncoder<W, C> {
+    inner: W,
+    code: C,
+    pending: [u1; MAX_DATA_BITS],
+    pending_len: usize,
+}
+
+impl<W, C: LineCode> LineEncoder<W, C> {
+    pub fn new(inner: W, code: C) -> Self {
+        assert!(code.data_bits() <= MAX_DATA_BITS && code.code_bits() <= MAX_CODE_BITS);
+        LineEncoder {
+            inner,
+            code,
+            pending: [u1::new(0); MAX_DATA_BITS],
+            pending_len: 0,
+        }
+    }
+
+    /// The number of bits written which don't yet make up a whole symbol
+    pub fn pending_bits(&self) -> usize {
+        self.pending_len
+    }
+
+    pub fn code(&self) -> &C {
+        &self.code
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Return the inner writer.  Any pending bits which don't make up a whole symbol are lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Return the inner writer, or an [`io::ErrorKind::InvalidInput`] error if the bits written
+    /// don't make up a whole number of symbols.  The inner writer is dropped along with the
+    /// error, so to keep it, check [`LineEncoder::pending_bits`] first and use
+    /// [`LineEncoder::into_inner`].
+    pub fn finish(self) -> io::Result<W> {
+        if self.pending_len != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bits written don't fill a line code symbol",
+            ));
+        }
+        Ok(self.inner)
+    }
+}
+
+impl<W: BitWrite, C: LineCode> BitWrite for LineEncoder<W, C> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        let data_bits = self.code.data_bits();
+        let code_bits = self.code.code_bits();
+        let mut code = [u1::new(0); MAX_CODE_BITS];
+        for &bit in buf {
+            self.pending[self.pending_len] = bit;
+            self.pending_len += 1;
+            if self.pending_len == data_bits {
+                self.code
+                    .encode(&self.pending[..data_bits], &mut code[..code_bits]);
+                self.inner.write_all(&code[..code_bits])?;
+                self.pending_len = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A reader which reads line bits from an inner reader and decodes them with a [`LineCode`].
+///
+/// If a symbol isn't valid for the code, reads fail with [`io::ErrorKind::InvalidData`], and
+/// [`LineDecoder::error_position`] gives the position of the start of that symbol.  Reads also
+/// fail if the inner reader ends part way through a symbol.
+#[derive(Debug)]
+pub struct LineDecoder<R, C> {
+    inner: R,
+    code: C,
+    data: [u1; MAX_DATA_BITS],
+    data_pos: usize,
+    data_len: usize,
+    position: usize,
+    error_position: Option<usize>,
+    error: Option<B3Error>,
+}
+
+impl<R, C: LineCode> LineDecoder<R, C> {
+    pub fn new(inner: R, code: C) -> Self {
+        assert!(code.data_bits() <= MAX_DATA_BITS && code.code_bits() <= MAX_CODE_BITS);
+        LineDecoder {
+            inner,
+            code,
+            data: [u1::new(0); MAX_DATA_BITS],
+            data_pos: 0,
+            data_len: 0,
+            position: 0,
+            error_position: None,
+            error: None,
+        }
+    }
+
+    /// The number of line bits which have been read from the inner reader.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The position in the inner reader's bits of the start of the invalid symbol, if there was
+    /// one.
+    pub fn error_position(&self) -> Option<usize> {
+        self.error_position
+    }
+
+    /// The error the code reported for the invalid symbol, if there was one.
+    pub fn error(&self) -> Option<&B3Error> {
+        self.error.as_ref()
+    }
+
+    pub fn code(&self) -> &C {
+        &self.code
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead, C: LineCode> LineDecoder<R, C> {
+    fn violation() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, "line code violation")
+    }
+
+    /// Decode the next symbol, returning false at the end of the inner reader
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.error_position.is_some() {
+            return Err(Self::violation());
+        }
+        let code_bits = self.code.code_bits();
+        let mut code = [u1::new(0); MAX_CODE_BITS];
+        let mut filled = 0;
+        while filled < code_bits {
+            match self.inner.read(&mut code[filled..code_bits])? {
+                0 if filled == 0 => return Ok(false),
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "line code symbol was cut short",
+                    ))
+                }
+                n => filled += n,
+            }
+        }
+        let start = self.position;
+        self.position += code_bits;
+        let data_bits = self.code.data_bits();
+        if let Err(e) = self
+            .code
+            .decode(&code[..code_bits], &mut self.data[..data_bits])
+        {
+            self.error_position = Some(start);
+            self.error = Some(e);
+            return Err(Self::violation());
+        }
+        self.data_pos = 0;
+        self.data_len = data_bits;
+        Ok(true)
+    }
+}
+
+impl<R: BitRead, C: LineCode> BitRead for LineDecoder<R, C> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        for (i, dst) in buf.iter_mut().enumerate() {
+            if self.data_pos == self.data_len && !self.fill()? {
+                return Ok(i);
+            }
+            *dst = self.data[self.data_pos];
+            self.data_pos += 1;
+        }
+        Ok(buf.len())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        if self.read(buf)? < buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_vec::BitVec,
+        bit_write_exts::BitWriteExts, bitarray, bitvec,
+    };
+
+    fn encode_bytes<C: LineCode>(code: C, bytes: &[u8]) -> BitVec {
+        let mut encoder = LineEncoder::new(BitVec::new(), code);
+        for &byte in bytes {
+            encoder.write_u8(byte).unwrap();
+        }
+        encoder.finish().unwrap()
+    }
+
+    fn decode_bytes<C: LineCode>(code: C, line: BitVec, len: usize) -> Vec<u8> {
+        let mut decoder = LineDecoder::new(BitCursor::new(line), code);
+        (0..len).map(|_| decoder.read_u8().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_nrzi() {
+        // The USB sync pattern, 00000001, is KJKJKJKK on the line, starting from idle (J)
+        let line = encode_bytes(Nrzi::space(u1::new(1)), &[0x01]);
+        assert_eq!(line, bitvec!(0, 1, 0, 1, 0, 1, 0, 0));
+        assert_eq!(decode_bytes(Nrzi::space(u1::new(1)), line, 1), vec![0x01]);
+
+        let line = encode_bytes(Nrzi::mark(u1::new(0)), &[0b1100_1010]);
+        assert_eq!(line, bitvec!(1, 0, 0, 0, 1, 1, 0, 0));
+        assert_eq!(decode_bytes(Nrzi::mark(u1::new(0)), line, 1), vec![0xCA]);
+    }
+
+    #[test]
+    fn test_manchester() {
+        let line = encode_bytes(Manchester::Thomas, &[0x5A]);
+        assert_eq!(line.to_bytes(), vec![0x66, 0x99]);
+        assert_eq!(decode_bytes(Manchester::Thomas, line, 1), vec![0x5A]);
+
+        let line = bitvec!(0, 1, 1, 0, 1, 1, 0, 1);
+        let mut decoder = LineDecoder::new(BitCursor::new(line), Manchester::Ieee);
+        assert_eq!(decoder.read_u2().unwrap(), ux::u2::new(0b10));
+        assert_eq!(
+            decoder.read_u1().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(decoder.error_position(), Some(4));
+    }
+
+    #[test]
+    fn test_differential_manchester() {
+        let line = encode_bytes(DifferentialManchester::new(u1::new(0)), &[0x60]);
+        assert_eq!(
+            line,
+            bitvec!(1, 0, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0)
+        );
+        assert_eq!(
+            decode_bytes(DifferentialManchester::new(u1::new(0)), line, 1),
+            vec![0x60]
+        );
+
+        // A J symbol, with no transition in the middle
+        let line = bitvec!(1, 0, 0, 0);
+        let mut decoder = LineDecoder::new(
+            BitCursor::new(line),
+            DifferentialManchester::new(u1::new(0)),
+        );
+        assert!(decoder.read_u2().is_err());
+        assert_eq!(decoder.error_position(), Some(2));
+    }
+
+    #[test]
+    fn test_4b5b() {
+        let line = encode_bytes(Code4b5b, &[0x0A, 0x5F]);
+        assert_eq!(
+            line,
+            bitvec!(1, 1, 1, 1, 0, 1, 0, 1, 1, 0, 0, 1, 0, 1, 1, 1, 1, 1, 0, 1)
+        );
+        assert_eq!(decode_bytes(Code4b5b, line, 2), vec![0x0A, 0x5F]);
+
+        // J K, the start of stream delimiter
+        let line = bitvec!(1, 1, 0, 0, 0, 1, 0, 0, 0, 1);
+        let mut decoder = LineDecoder::new(BitCursor::new(line), Code4b5b);
+        assert!(decoder.read_u4().is_err());
+        assert_eq!(decoder.error_position(), Some(0));
+        assert!(matches!(
+            decoder.error(),
+            Some(B3Error::InvalidCode { code: 0b11000, .. })
+        ));
+    }
+
+    #[test]
+    // Codes are grouped as their 6b and 4b blocks
+    #[allow(clippy::unusual_byte_groupings)]
+    fn test_8b10b_symbols() {
+        // (symbol, code with negative running disparity, code with positive running disparity)
+        let vectors = [
+            (Symbol8b10b::Data(0x00), 0b100111_0100, 0b011000_1011),
+            (Symbol8b10b::Data(0xB5), 0b101010_1010, 0b101010_1010),
+            (Symbol8b10b::Data(0x4A), 0b010101_0101, 0b010101_0101),
+            (Symbol8b10b::Data(0x07), 0b111000_1011, 0b000111_0100),
+            (Symbol8b10b::Data(0xF1), 0b100011_0111, 0b100011_0001),
+            (Symbol8b10b::Data(0xEB), 0b110100_1110, 0b110100_1000),
+            (Symbol8b10b::Data(0xFF), 0b101011_0001, 0b010100_1110),
+            (Symbol8b10b::Control(0xBC), 0b001111_1010, 0b110000_0101),
+            (Symbol8b10b::Control(0x3C), 0b001111_1001, 0b110000_0110),
+            (Symbol8b10b::Control(0xFE), 0b011110_1000, 0b100001_0111),
+        ];
+        for (symbol, negative, positive) in vectors {
+            for (rd_positive, code) in [(false, negative), (true, positive)] {
+                let mut encoder = Code8b10b { rd_positive };
+                assert_eq!(encoder.encode_symbol(symbol), Ok(code), "{symbol:?}");
+                let mut decoder = Code8b10b { rd_positive };
+                assert_eq!(decoder.decode_symbol(code), Ok(symbol));
+                assert_eq!(decoder.is_rd_positive(), encoder.is_rd_positive());
+            }
+        }
+
+        // Every data symbol round trips from both running disparities
+        for rd_positive in [false, true] {
+            for value in 0..=255 {
+                let mut encoder = Code8b10b { rd_positive };
+                let code = encoder.encode_symbol(Symbol8b10b::Data(value)).unwrap();
+                let mut decoder = Code8b10b { rd_positive };
+                assert_eq!(decoder.decode_symbol(code), Ok(Symbol8b10b::Data(value)));
+            }
+        }
+
+        let mut code = Code8b10b::new();
+        assert_eq!(
+            code.encode_symbol(Symbol8b10b::Control(0x00)),
+            Err(B3Error::InvalidCode { code: 0, index: 0 })
+        );
+        assert_eq!(
+            code.decode_symbol(0b000000_1111),
+            Err(B3Error::InvalidCode {
+                code: 0b000000_1111,
+                index: 0
+            })
+        );
+        // K.28.5 for positive running disparity, received with negative running disparity
+        assert_eq!(
+            code.decode_symbol(0b110000_0101),
+            Err(B3Error::RunningDisparity {
+                code: 0b110000_0101
+            })
+        );
+        assert!(!code.is_rd_positive());
+    }
+
+    #[test]
+    fn test_8b10b_stream() {
+        let bytes = [0x00, 0x00, 0xFF, 0x4A, 0x07, 0x07];
+        let line = encode_bytes(Code8b10b::new(), &bytes);
+        assert_eq!(line.len(), 60);
+        // D.0.0 is balanced, so it leaves the running disparity negative
+        assert_eq!(line.to_bytes()[..2], [0b1001_1101, 0b0010_0111]);
+        assert_eq!(decode_bytes(Code8b10b::new(), line, 6), bytes);
+
+        // A comma in the middle of the data
+        let mut line = encode_bytes(Code8b10b::new(), &[0x00]);
+        line.write_all(&bitarray!(1, 1, 0, 0, 0, 0, 0, 1, 0, 1))
+            .unwrap();
+        let mut decoder = LineDecoder::new(BitCursor::new(line), Code8b10b::new());
+        assert_eq!(decoder.read_u8().unwrap(), 0x00);
+        assert!(decoder.read_u8().is_err());
+        assert_eq!(decoder.error_position(), Some(10));
+        assert_eq!(
+            decoder.error(),
+            Some(&B3Error::RunningDisparity {
+                code: 0b11_0000_0101
+            })
+        );
+    }
+
+    #[test]
+    fn test_encoder_into_inner() {
+        let mut encoder = LineEncoder::new(BitVec::new(), Code4b5b);
+        encoder.write_all(&bitarray!(1, 0, 1, 0, 1)).unwrap();
+        assert_eq!(encoder.pending_bits(), 1);
+        assert_eq!(encoder.into_inner(), bitvec!(1, 0, 1, 1, 0));
+
+        let mut encoder = LineEncoder::new(BitVec::new(), Code4b5b);
+        encoder.write_all(&bitarray!(1)).unwrap();
+        assert_eq!(
+            encoder.finish().unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
@@ -0,0 +1,26 @@
+/// Create a [u1; N] array.  This is mainly used for testing the byteorder functions, which expect
+/// specifically-sized u1 arrays as their arguments.
+#[macro_export]
+macro_rules! bitarray {
+    (0$(, $rest:tt)*) => {
+        bitarray!(@internal [::ux::u1::new(0)] $($rest),*)
+    };
+    (1$(, $rest:tt)*) => {
+        bitarray!(@internal [::ux::u1::new(1)] $($rest),*)
+    };
+    (@internal [$($done:expr$(,)?)+] 0$(, $rest:tt)*) => {
+        bitarray!(@internal [$($done)*, ::ux::u1::new(0)] $($rest),*)
+    };
+    (@internal [$($done:expr$(,)?)+] 1$(, $rest:tt)*) => {
+        bitarray!(@internal [$($done)*, ::ux::u1::new(1)] $($rest),*)
+    };
+    (@internal [$($done:expr$(,)?)+]) => {
+        [$($done, )*]
+    };
+    ($val:expr$(, $rest:tt)*) => {
+        compile_error!("Only 1s and 0s are valid when creating a bitarray")
+    };
+    (@internal [$($done:expr$(,)?)+] $val:expr$(, $rest:tt)*) => {
+        compile_error!("Only 1s and 0s are valid when creating a bitarray")
+    };
+}
@@ -1,32 +1,291 @@
-use std::ops::RangeBounds;
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem,
+    ops::RangeBounds,
+    sync::atomic::{self, AtomicU8},
+};
 
 use ux::u1;
 
+#[cfg(feature = "alloc")]
+use crate::bit_vec::BitVec;
 use crate::{
-    bit_buffer::{BitBuffer, BitBufferMut},
+    bit_buffer::{BitBuffer, BitBufferMut, BitBufferSplit},
     bit_read::BitRead,
-    bit_vec::BitVec,
+    bit_store::BitStore,
     bit_write::BitWrite,
     error::{B3Error, B3Result},
-    util::{get_bit, get_start_end_bit_index_from_range, set_bit},
+    io,
+    util::{assert_bits_fit, get_bit, get_start_end_bit_index_from_range, hash_bits, set_bit},
 };
 
 // TODO: Multiple operations here are done bit-by-bit and _could_ likely be optimized to do
 // byte-wise operations when possible.
 
+/// The bytes underlying a [`BitSlice`]: an optional first byte, a run of bytes, and an optional
+/// last byte.  The first and last bytes are copies of bytes a [`BitSliceMut`] shares with a
+/// sibling slice (see [`BitSliceMut`]).
+#[derive(Clone, Copy, Debug)]
+struct SliceBytes<'a> {
+    head: Option<u8>,
+    body: &'a [u8],
+    tail: Option<u8>,
+}
+
+impl<'a> SliceBytes<'a> {
+    fn new(body: &'a [u8]) -> SliceBytes<'a> {
+        SliceBytes {
+            head: None,
+            body,
+            tail: None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.head.is_some() as usize + self.body.len() + self.tail.is_some() as usize
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        let index = match self.head {
+            Some(byte) if index == 0 => return byte,
+            Some(_) => index - 1,
+            None => index,
+        };
+        match self.body.get(index) {
+            Some(&byte) => byte,
+            None => {
+                assert_eq!(index, self.body.len(), "byte index out of range");
+                self.tail.expect("byte index out of range")
+            }
+        }
+    }
+
+    /// The bytes in [start, end)
+    fn sub(&self, start: usize, end: usize) -> SliceBytes<'a> {
+        assert!(start <= end && end <= self.len());
+        let skip = self.head.is_some() as usize;
+        let body_start = start.saturating_sub(skip).min(self.body.len());
+        let body_end = end.saturating_sub(skip).min(self.body.len());
+        SliceBytes {
+            head: self.head.filter(|_| start == 0 && end > 0),
+            body: &self.body[body_start..body_end],
+            tail: self.tail.filter(|_| start < end && end == self.len()),
+        }
+    }
+
+    /// Read |n| (in 1..=8) bits starting at bit |bit_pos|.  The bits are returned in the most
+    /// significant bits of the result, with the remaining bits set to 0.
+    fn read_bits(&self, bit_pos: usize, n: usize) -> u8 {
+        let byte_pos = bit_pos / 8;
+        let offset = bit_pos % 8;
+        let mut value = self.get(byte_pos) << offset;
+        if offset + n > 8 {
+            value |= self.get(byte_pos + 1) >> (8 - offset);
+        }
+        value & u8::leading_mask(n)
+    }
+}
+
+/// The bytes underlying a [`BitSliceMut`]: an optional first byte, a run of bytes, and an
+/// optional last byte.  The first and last bytes may be shared with a sibling slice, so they're
+/// accessed atomically; the bytes in between belong to this slice alone.
+#[derive(Debug)]
+struct MutBytes<'a> {
+    head: Option<&'a AtomicU8>,
+    body: &'a mut [u8],
+    tail: Option<&'a AtomicU8>,
+}
+
+impl<'a> MutBytes<'a> {
+    fn new(body: &'a mut [u8]) -> MutBytes<'a> {
+        MutBytes {
+            head: None,
+            body,
+            tail: None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.head.is_some() as usize + self.body.len() + self.tail.is_some() as usize
+    }
+
+    /// A copy of these bytes which can be shared.  Any bits in the first and last bytes which
+    /// belong to a sibling slice may go stale, but a [`BitSlice`] never reads those.
+    fn snapshot(&self) -> SliceBytes<'_> {
+        SliceBytes {
+            head: self.head.map(|byte| byte.load(atomic::Ordering::Relaxed)),
+            body: self.body,
+            tail: self.tail.map(|byte| byte.load(atomic::Ordering::Relaxed)),
+        }
+    }
+
+    fn reborrow(&mut self) -> MutBytes<'_> {
+        MutBytes {
+            head: self.head,
+            body: self.body,
+            tail: self.tail,
+        }
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        self.snapshot().get(index)
+    }
+
+    /// Set the bits of byte |index| selected by |mask| to those of |value|.  A shared byte is
+    /// updated with atomic AND and OR operations which only touch the masked bits, so a sibling
+    /// slice can update its own bits of the byte at the same time.
+    fn set(&mut self, index: usize, mask: u8, value: u8) {
+        let value = value & mask;
+        let index = match self.head {
+            Some(byte) if index == 0 => return set_shared(byte, mask, value),
+            Some(_) => index - 1,
+            None => index,
+        };
+        match self.body.get_mut(index) {
+            Some(byte) => *byte = (*byte & !mask) | value,
+            None => {
+                assert_eq!(index, self.body.len(), "byte index out of range");
+                set_shared(self.tail.expect("byte index out of range"), mask, value)
+            }
+        }
+    }
+
+    /// Split these bytes at byte |index|.  If |shared| is false, the first part has the bytes in
+    /// [0, index) and the second those in [index, len).  If it's true, the byte at |index| goes
+    /// into both: it's the last byte of the first part and the first byte of the second.
+    fn split(self, index: usize, shared: bool) -> (MutBytes<'a>, MutBytes<'a>) {
+        let len = self.len();
+        assert!(index < len || (!shared && index == len));
+        let skip = self.head.is_some() as usize;
+        let MutBytes { head, body, tail } = self;
+        if index < skip {
+            let first = MutBytes {
+                head: head.filter(|_| shared),
+                body: Default::default(),
+                tail: None,
+            };
+            return (first, MutBytes { head, body, tail });
+        }
+        if index - skip > body.len() || (shared && index - skip == body.len()) {
+            // The split is at (or, if shared, within) the last byte
+            let second = MutBytes {
+                head: tail.filter(|_| shared),
+                body: Default::default(),
+                tail: None,
+            };
+            return (MutBytes { head, body, tail }, second);
+        }
+        let (first, rest) = body.split_at_mut(index - skip);
+        if shared {
+            let (byte, rest) = rest.split_first_mut().expect("byte index out of range");
+            // SAFETY: `byte` is valid and exclusively borrowed for 'a, and an AtomicU8 has the
+            // same size and alignment as a u8.  The `&mut u8` is consumed here, so for the rest
+            // of 'a the byte is only ever accessed through the returned `&AtomicU8`.
+            let byte: Option<&AtomicU8> = Some(unsafe { AtomicU8::from_ptr(byte) });
+            (
+                MutBytes {
+                    head,
+                    body: first,
+                    tail: byte,
+                },
+                MutBytes {
+                    head: byte,
+                    body: rest,
+                    tail,
+                },
+            )
+        } else {
+            (
+                MutBytes {
+                    head,
+                    body: first,
+                    tail: None,
+                },
+                MutBytes {
+                    head: None,
+                    body: rest,
+                    tail,
+                },
+            )
+        }
+    }
+
+    /// The bytes in [start, end)
+    fn sub(&mut self, start: usize, end: usize) -> MutBytes<'_> {
+        let (bytes, _) = self.reborrow().split(end, false);
+        bytes.split(start, false).1
+    }
+
+    /// Write the |n| (in 1..=8) most significant bits of |value| starting at bit |bit_pos|,
+    /// leaving all other bits unchanged.
+    fn write_bits(&mut self, bit_pos: usize, value: u8, n: usize) {
+        let byte_pos = bit_pos / 8;
+        let offset = bit_pos % 8;
+        let mask = u8::leading_mask(n);
+        let value = value & mask;
+        self.set(byte_pos, mask >> offset, value >> offset);
+        if offset + n > 8 {
+            let shift = 8 - offset;
+            self.set(byte_pos + 1, mask << shift, value << shift);
+        }
+    }
+}
+
+/// Set the bits of |byte| selected by |mask| to those of |value| (which must already be masked),
+/// leaving its other bits to whichever slice they belong to.
+fn set_shared(byte: &AtomicU8, mask: u8, value: u8) {
+    byte.fetch_and(!mask | value, atomic::Ordering::Relaxed);
+    byte.fetch_or(value, atomic::Ordering::Relaxed);
+}
+
+/// Copy |nbits| bits from |src| starting at bit |src_bit| into |dst| starting at bit |dst_bit|.
+fn copy_bits(
+    src: SliceBytes<'_>,
+    src_bit: usize,
+    dst: &mut MutBytes<'_>,
+    dst_bit: usize,
+    nbits: usize,
+) {
+    let mut index = 0;
+    if src_bit % 8 == 0 && dst_bit % 8 == 0 {
+        // Both are byte-aligned, so whole bytes can be copied directly
+        let num_bytes = nbits / 8;
+        for i in 0..num_bytes {
+            dst.set(dst_bit / 8 + i, 0xFF, src.get(src_bit / 8 + i));
+        }
+        index = num_bytes * 8;
+    }
+    while index < nbits {
+        let n = (nbits - index).min(8);
+        let bits = src.read_bits(src_bit + index, n);
+        dst.write_bits(dst_bit + index, bits, n);
+        index += n;
+    }
+}
+
 /// A slice of bits.  |start_bit_index| is inclusive, |end_bit_index| is exclusive
-#[derive(Debug, Eq)]
+#[derive(Debug)]
 pub struct BitSlice<'a> {
-    buf: &'a [u8],
+    buf: SliceBytes<'a>,
     start_bit_index: usize,
     end_bit_index: usize,
 }
 
+// Both kinds of slice can always be sent to, or shared with, other threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<BitSlice<'_>>();
+    assert_send_sync::<BitSliceMut<'_>>();
+};
+
 #[allow(clippy::len_without_is_empty)]
-impl BitSlice<'_> {
-    pub(crate) fn new(buf: &[u8], start_bit_index: usize, end_bit_index: usize) -> BitSlice {
+impl<'a> BitSlice<'a> {
+    // Only used by the alloc containers
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    pub(crate) fn new(buf: &[u8], start_bit_index: usize, end_bit_index: usize) -> BitSlice<'_> {
         BitSlice {
-            buf,
+            buf: SliceBytes::new(buf),
             start_bit_index,
             end_bit_index,
         }
@@ -40,10 +299,10 @@ impl BitSlice<'_> {
     ///
     /// * `index`: The index.
     pub fn at(&self, index: usize) -> u1 {
-        assert!(index < self.end_bit_index);
+        assert!(index < self.len());
         let bit_pos = self.start_bit_index + index;
         let byte_pos = bit_pos / 8;
-        let byte = self.buf[byte_pos];
+        let byte = self.buf.get(byte_pos);
         get_bit(byte, bit_pos % 8)
     }
 
@@ -55,10 +314,30 @@ impl BitSlice<'_> {
         }
     }
 
-    /// Get a slice of this slice corresponding to the given range.
+    /// An iterator over the bits in this slice packed MSB-first into bytes, with any unused bits
+    /// in the last byte set to 0.
+    pub(crate) fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        let bit_offset = self.start_bit_index % 8;
+        let num_bytes = self.len().div_ceil(8);
+        (0..num_bytes).map(move |i| {
+            let byte_pos = self.start_bit_index / 8 + i;
+            let mut byte = self.buf.get(byte_pos) << bit_offset;
+            if bit_offset > 0 && byte_pos + 1 < self.buf.len() {
+                byte |= self.buf.get(byte_pos + 1) >> (8 - bit_offset);
+            }
+            if i == num_bytes - 1 {
+                byte & u8::leading_mask(self.len() - i * 8)
+            } else {
+                byte
+            }
+        })
+    }
+
+    /// Get a slice of this slice corresponding to the given range.  It borrows the same buffer as
+    /// this slice, rather than this slice itself.
     ///
     /// * `range`: The range.
-    pub fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+    pub fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'a>> {
         let (start_bit_index, end_bit_index) =
             get_start_end_bit_index_from_range(&range, self.len());
         let bit_len = end_bit_index - start_bit_index;
@@ -67,38 +346,57 @@ impl BitSlice<'_> {
         let end_bit_index = start_bit_index + bit_len;
 
         let start_byte = start_bit_index / 8;
-        let end_byte = (end_bit_index - 1) / 8;
+        // Exclusive, so that an empty range needs no bytes
+        let end_byte = end_bit_index.div_ceil(8);
         // We now need to adjust the start_bit_index to be relative to the start_byte
         let start_bit_index = start_bit_index - start_byte * 8;
-        if end_byte >= self.buf.len() {
+        if end_byte > self.buf.len() {
             return Err(B3Error::SliceOutOfRange {
                 len: self.buf.len(),
                 slice_start: start_byte,
                 slice_end: end_byte,
             });
         }
-        Ok(BitSlice::new(
-            &self.buf[start_byte..=end_byte],
+        Ok(BitSlice {
+            buf: self.buf.sub(start_byte, end_byte),
             start_bit_index,
-            start_bit_index + bit_len,
-        ))
+            end_bit_index: start_bit_index + bit_len,
+        })
     }
 }
 
 impl PartialEq for BitSlice<'_> {
     fn eq(&self, other: &Self) -> bool {
-        if self.len() != other.len() {
-            return false;
-        }
-        self.iter()
-            .zip(other.iter())
-            .try_for_each(|(left, right)| if left == right { Ok(()) } else { Err(()) })
-            .is_ok()
+        self.len() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for BitSlice<'_> {}
+
+impl Hash for BitSlice<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_bits(self.len(), self.bytes(), state);
+    }
+}
+
+impl PartialOrd for BitSlice<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BitSlice<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Comparing the packed bytes compares the bits lexicographically.  If one slice is a
+        // prefix of the other, the shorter one is less.
+        self.bytes()
+            .cmp(other.bytes())
+            .then(self.len().cmp(&other.len()))
     }
 }
 
 impl BitRead for BitSlice<'_> {
-    fn read(&mut self, buf: &mut [u1]) -> std::io::Result<usize> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
         let n = self.len().min(buf.len());
         // TODO: optimize...
         for (i, bit) in buf.iter_mut().enumerate().take(n) {
@@ -107,10 +405,10 @@ impl BitRead for BitSlice<'_> {
         Ok(n)
     }
 
-    fn read_exact(&mut self, buf: &mut [u1]) -> std::io::Result<()> {
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
         if buf.len() > self.len() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::UnexpectedEof,
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
                 "failed to fill whole buffer",
             ));
         }
@@ -122,6 +420,24 @@ impl BitRead for BitSlice<'_> {
 
         Ok(())
     }
+
+    fn read_into_bytes(
+        &mut self,
+        buf: &mut [u8],
+        bit_offset: usize,
+        nbits: usize,
+    ) -> io::Result<()> {
+        assert_bits_fit(buf.len(), bit_offset, nbits);
+        if nbits > self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        let mut dst = MutBytes::new(buf);
+        copy_bits(self.buf, self.start_bit_index, &mut dst, bit_offset, nbits);
+        Ok(())
+    }
 }
 
 /// An interator over a [`BitSlice`].
@@ -158,17 +474,53 @@ impl PartialEq<&[u1]> for BitSlice<'_> {
     }
 }
 
+impl<const N: usize> PartialEq<[u1; N]> for BitSlice<'_> {
+    fn eq(&self, other: &[u1; N]) -> bool {
+        PartialEq::eq(self, &&other[..])
+    }
+}
+
+impl PartialOrd<&[u1]> for BitSlice<'_> {
+    fn partial_cmp(&self, other: &&[u1]) -> Option<Ordering> {
+        Some(self.iter().cmp(other.iter().copied()))
+    }
+}
+
+impl<const N: usize> PartialOrd<[u1; N]> for BitSlice<'_> {
+    fn partial_cmp(&self, other: &[u1; N]) -> Option<Ordering> {
+        PartialOrd::partial_cmp(self, &&other[..])
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl PartialEq<BitVec> for BitSlice<'_> {
     fn eq(&self, other: &BitVec) -> bool {
-        if self.len() != other.len() {
-            return false;
-        }
-        for (i, ref bit) in other.iter().enumerate() {
-            if &self.at(i) != bit {
-                return false;
-            }
-        }
-        true
+        self.len() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<BitSlice<'_>> for BitVec {
+    fn eq(&self, other: &BitSlice<'_>) -> bool {
+        PartialEq::eq(other, self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd<BitVec> for BitSlice<'_> {
+    fn partial_cmp(&self, other: &BitVec) -> Option<Ordering> {
+        Some(
+            self.bytes()
+                .cmp(other.bytes())
+                .then(self.len().cmp(&other.len())),
+        )
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd<BitSlice<'_>> for BitVec {
+    fn partial_cmp(&self, other: &BitSlice<'_>) -> Option<Ordering> {
+        PartialOrd::partial_cmp(other, self).map(Ordering::reverse)
     }
 }
 
@@ -181,23 +533,50 @@ impl BitBuffer for BitSlice<'_> {
     }
 }
 
+impl<'a> BitBufferSplit for BitSlice<'a> {
+    type Part<'b>
+        = BitSlice<'a>
+    where
+        Self: 'b;
+
+    fn get_part<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'a>> {
+        self.get_slice(range)
+    }
+}
+
 /// A mutable slice of bits.  |start_bit_index| is inclusive, |end_bit_index| is exclusive
+///
+/// # Aliasing
+/// A [`BitSliceMut`] has exclusive access to its _bits_, but not necessarily to its _bytes_:
+/// [`BitSliceMut::split_at_mut`] and [`BitSliceMut::chunks_mut`] create slices which can share
+/// the byte containing the boundary between them.  To allow that, a [`BitSliceMut`] holds its
+/// first and last bytes as `&AtomicU8`s, and writes to them use atomic AND and OR operations
+/// which only change the bits belonging to the slice.  Slices sharing a byte can therefore be
+/// used from different threads (e.g. to fill the chunks from [`BitSliceMut::chunks_mut`] in
+/// parallel) without racing, and a [`BitSliceMut`] is [`Send`] and [`Sync`] like `&mut [u8]`.
+/// The bytes in between belong to the slice alone and are held as `&mut [u8]`.
+///
+/// A [`BitSlice`] taken from a [`BitSliceMut`] copies the first and last bytes rather than
+/// borrowing them.  The slice's own bits in those bytes can't change while it's borrowed, so the
+/// copy is always accurate for them.
 #[derive(Debug)]
 pub struct BitSliceMut<'a> {
-    buf: &'a mut [u8],
+    buf: MutBytes<'a>,
     start_bit_index: usize,
     end_bit_index: usize,
 }
 
 #[allow(clippy::len_without_is_empty)]
-impl BitSliceMut<'_> {
+impl<'a> BitSliceMut<'a> {
+    // Only used by the alloc containers
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
     pub(crate) fn new(
         buf: &mut [u8],
         start_bit_index: usize,
         end_bit_index: usize,
     ) -> BitSliceMut<'_> {
         BitSliceMut {
-            buf,
+            buf: MutBytes::new(buf),
             start_bit_index,
             end_bit_index,
         }
@@ -208,21 +587,22 @@ impl BitSliceMut<'_> {
     }
 
     pub fn at(&self, index: usize) -> u1 {
-        assert!(index < self.end_bit_index);
+        assert!(index < self.len());
         let bit_pos = self.start_bit_index + index;
         let byte_pos = bit_pos / 8;
-        let byte = self.buf[byte_pos];
+        let byte = self.buf.get(byte_pos);
         get_bit(byte, bit_pos % 8)
     }
 
     pub fn set(&mut self, index: usize, value: u1) {
-        assert!(index < self.end_bit_index);
+        assert!(index < self.len());
         let bit_pos = self.start_bit_index + index;
         let byte_pos = bit_pos / 8;
         // Now make bit_pos relative to the byte
         let bit_pos = bit_pos % 8;
-        let byte = &mut self.buf[byte_pos];
-        set_bit(byte, bit_pos, value);
+        let mut byte = self.buf.get(byte_pos);
+        set_bit(&mut byte, bit_pos, value);
+        self.buf.set(byte_pos, 0x80 >> bit_pos, byte);
     }
 
     pub fn get_slice<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
@@ -234,21 +614,22 @@ impl BitSliceMut<'_> {
         let end_bit_index = start_bit_index + bit_len;
 
         let start_byte = start_bit_index / 8;
-        let end_byte = (end_bit_index - 1) / 8;
+        // Exclusive, so that an empty range needs no bytes
+        let end_byte = end_bit_index.div_ceil(8);
         // We now need to adjust the start_bit_index to be relative to the start_byte
         let start_bit_index = start_bit_index - start_byte * 8;
-        if end_byte >= self.buf.len() {
+        if end_byte > self.buf.len() {
             return Err(B3Error::SliceOutOfRange {
                 len: self.buf.len(),
                 slice_start: start_byte,
                 slice_end: end_byte,
             });
         }
-        Ok(BitSlice::new(
-            &self.buf[start_byte..=end_byte],
+        Ok(BitSlice {
+            buf: self.buf.snapshot().sub(start_byte, end_byte),
             start_bit_index,
-            start_bit_index + bit_len,
-        ))
+            end_bit_index: start_bit_index + bit_len,
+        })
     }
 
     pub fn get_slice_mut<T: RangeBounds<usize>>(&mut self, range: T) -> B3Result<BitSliceMut<'_>> {
@@ -260,26 +641,326 @@ impl BitSliceMut<'_> {
         let end_bit_index = start_bit_index + bit_len;
 
         let start_byte = start_bit_index / 8;
-        let end_byte = (end_bit_index - 1) / 8;
+        // Exclusive, so that an empty range needs no bytes
+        let end_byte = end_bit_index.div_ceil(8);
         // We now need to adjust the start_bit_index to be relative to the start_byte
         let start_bit_index = start_bit_index - start_byte * 8;
-        if end_byte >= self.buf.len() {
+        if end_byte > self.buf.len() {
             return Err(B3Error::SliceOutOfRange {
                 len: self.buf.len(),
                 slice_start: start_byte,
                 slice_end: end_byte,
             });
         }
-        Ok(BitSliceMut::new(
-            &mut self.buf[start_byte..=end_byte],
+        Ok(BitSliceMut {
+            buf: self.buf.sub(start_byte, end_byte),
             start_bit_index,
-            start_bit_index + bit_len,
-        ))
+            end_bit_index: start_bit_index + bit_len,
+        })
+    }
+
+    /// Divide this slice into two at `mid`: the first contains the bits in [0, mid) and the
+    /// second the bits in [mid, len).  The two slices can be modified independently, even if
+    /// `mid` isn't on a byte boundary.  Panics if `mid` is greater than the length of the slice.
+    ///
+    /// # Example
+    /// ```
+    /// use ux::u1;
+    /// use b3::bitvec;
+    ///
+    /// let mut vec = bitvec!(0; 12);
+    /// let mut slice = vec.get_slice_mut(..).unwrap();
+    /// let (mut header, mut payload) = slice.split_at_mut(3);
+    /// header.fill(u1::new(1));
+    /// payload.set(0, u1::new(1));
+    /// assert_eq!(vec, bitvec!(1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0));
+    /// ```
+    pub fn split_at_mut(&mut self, mid: usize) -> (BitSliceMut<'_>, BitSliceMut<'_>) {
+        assert!(
+            mid <= self.len(),
+            "mid ({}) is greater than the slice length ({})",
+            mid,
+            self.len()
+        );
+        BitSliceMut {
+            buf: self.buf.reborrow(),
+            start_bit_index: self.start_bit_index,
+            end_bit_index: self.end_bit_index,
+        }
+        .split_into(mid)
+    }
+
+    /// [`BitSliceMut::split_at_mut`], but consuming this slice so the two halves keep its
+    /// lifetime.  If `mid` isn't on a byte boundary, the byte containing it goes into both.
+    fn split_into(self, mid: usize) -> (BitSliceMut<'a>, BitSliceMut<'a>) {
+        let mid = self.start_bit_index + mid;
+        let (first, second) = self.buf.split(mid / 8, mid % 8 != 0);
+        let skip = mid / 8 * 8;
+        (
+            BitSliceMut {
+                buf: first,
+                start_bit_index: self.start_bit_index,
+                end_bit_index: mid,
+            },
+            BitSliceMut {
+                buf: second,
+                start_bit_index: mid - skip,
+                end_bit_index: self.end_bit_index - skip,
+            },
+        )
+    }
+
+    /// Get an iterator over non-overlapping mutable chunks of `chunk_size` bits, starting at the
+    /// beginning of the slice.  If `chunk_size` doesn't divide the length of the slice, the last
+    /// chunk will be shorter.  Panics if `chunk_size` is 0.
+    pub fn chunks_mut(&mut self, chunk_size: usize) -> BitSliceChunksMut<'_> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        BitSliceChunksMut {
+            rest: BitSliceMut {
+                buf: self.buf.reborrow(),
+                start_bit_index: self.start_bit_index,
+                end_bit_index: self.end_bit_index,
+            },
+            chunk_size,
+        }
+    }
+
+    /// Read |n| (in 1..=8) bits starting at |index|, returned in the most significant bits.
+    fn read_bits(&self, index: usize, n: usize) -> u8 {
+        self.buf
+            .snapshot()
+            .read_bits(self.start_bit_index + index, n)
+    }
+
+    /// Write the |n| (in 1..=8) most significant bits of |value| starting at |index|.
+    fn write_bits(&mut self, index: usize, value: u8, n: usize) {
+        self.buf.write_bits(self.start_bit_index + index, value, n)
+    }
+
+    /// Copy all of the bits from `src` into this slice.  Panics if the two slices have different
+    /// lengths.
+    pub fn copy_from_bitslice(&mut self, src: &BitSlice<'_>) {
+        assert_eq!(
+            self.len(),
+            src.len(),
+            "source slice length ({}) does not match destination slice length ({})",
+            src.len(),
+            self.len(),
+        );
+        let len = self.len();
+        copy_bits(
+            src.buf,
+            src.start_bit_index,
+            &mut self.buf,
+            self.start_bit_index,
+            len,
+        );
+    }
+
+    /// Copy all of the bits from `src` into this slice.  This is the same as
+    /// [`BitSliceMut::copy_from_bitslice`], and is provided to mirror `[T]::clone_from_slice`.
+    pub fn clone_from_bitslice(&mut self, src: &BitSlice<'_>) {
+        self.copy_from_bitslice(src)
+    }
+
+    /// Copy the bits in the range `src` to the position starting at `dest` within this slice.
+    /// The two regions may overlap.  Panics if either region is out of bounds.
+    pub fn copy_within<T: RangeBounds<usize>>(&mut self, src: T, dest: usize) {
+        let (src_start, src_end) = get_start_end_bit_index_from_range(&src, self.len());
+        assert!(
+            src_start <= src_end && src_end <= self.len(),
+            "source range {}..{} is out of bounds for a slice of length {}",
+            src_start,
+            src_end,
+            self.len()
+        );
+        let count = src_end - src_start;
+        assert!(
+            dest <= self.len() - count,
+            "destination ({}) is out of bounds for a copy of {} bits in a slice of length {}",
+            dest,
+            count,
+            self.len()
+        );
+        if dest <= src_start {
+            // Copy front to back, so no source bits are overwritten before they're read
+            let mut copied = 0;
+            while copied < count {
+                let n = (count - copied).min(8);
+                let bits = self.read_bits(src_start + copied, n);
+                self.write_bits(dest + copied, bits, n);
+                copied += n;
+            }
+        } else {
+            // Copy back to front, so no source bits are overwritten before they're read
+            let mut remaining = count;
+            while remaining > 0 {
+                let n = remaining.min(8);
+                remaining -= n;
+                let bits = self.read_bits(src_start + remaining, n);
+                self.write_bits(dest + remaining, bits, n);
+            }
+        }
+    }
+
+    /// Set every bit in this slice to `value`.
+    pub fn fill(&mut self, value: u1) {
+        let byte = if value == u1::new(1) { 0xFF } else { 0x00 };
+        let len = self.len();
+        // Fill up to the first byte boundary bit by bit, then whole bytes, then the remainder
+        let head = ((8 - self.start_bit_index % 8) % 8).min(len);
+        if head > 0 {
+            self.write_bits(0, byte, head);
+        }
+        let num_bytes = (len - head) / 8;
+        let first_byte = (self.start_bit_index + head) / 8;
+        for i in first_byte..first_byte + num_bytes {
+            self.buf.set(i, 0xFF, byte);
+        }
+        let tail_start = head + num_bytes * 8;
+        if tail_start < len {
+            self.write_bits(tail_start, byte, len - tail_start);
+        }
+    }
+
+    /// Swap the bits at indices `a` and `b`.  Panics if either index is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        assert!(a < self.len() && b < self.len());
+        let bit_a = self.at(a);
+        let bit_b = self.at(b);
+        self.set(a, bit_b);
+        self.set(b, bit_a);
+    }
+
+    /// Swap all of the bits in this slice with those in `other`.  Panics if the two slices have
+    /// different lengths.
+    pub fn swap_with_bitslice(&mut self, other: &mut BitSliceMut<'_>) {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "slices to swap must have the same length"
+        );
+        let len = self.len();
+        let mut index = 0;
+        while index < len {
+            let n = (len - index).min(8);
+            let ours = self.read_bits(index, n);
+            let theirs = other.read_bits(index, n);
+            self.write_bits(index, theirs, n);
+            other.write_bits(index, ours, n);
+            index += n;
+        }
+    }
+
+    /// Reverse the order of the bits in this slice.
+    pub fn reverse(&mut self) {
+        self.reverse_range(0, self.len());
+    }
+
+    /// Reverse the order of the bits within each byte (i.e. each group of 8 bits, counting from
+    /// the start of this slice), e.g. to convert between MSB-first and LSB-first bit order.
+    /// Panics if the length of this slice isn't a multiple of 8.
+    pub fn reverse_bits_in_bytes(&mut self) {
+        assert!(
+            self.len() % 8 == 0,
+            "slice length ({}) is not a whole number of bytes",
+            self.len()
+        );
+        if self.start_bit_index % 8 == 0 {
+            let first_byte = self.start_bit_index / 8;
+            for i in first_byte..first_byte + self.len() / 8 {
+                let byte = self.buf.get(i);
+                self.buf.set(i, 0xFF, byte.reverse_bits());
+            }
+        } else {
+            for index in (0..self.len()).step_by(8) {
+                let byte = self.read_bits(index, 8);
+                self.write_bits(index, byte.reverse_bits(), 8);
+            }
+        }
+    }
+
+    /// Reverse the order of the bytes within each `width` bit word (counting from the start of
+    /// this slice), e.g. `swap_bytes_in_words(32)` converts a run of big endian u32s to little
+    /// endian.  Panics if `width` isn't a non-zero multiple of 8, or if the length of this slice
+    /// isn't a multiple of `width`.
+    pub fn swap_bytes_in_words(&mut self, width: usize) {
+        assert!(
+            width > 0 && width % 8 == 0,
+            "word width ({}) is not a whole number of bytes",
+            width
+        );
+        assert!(
+            self.len() % width == 0,
+            "slice length ({}) is not a whole number of {} bit words",
+            self.len(),
+            width
+        );
+        let word_bytes = width / 8;
+        if self.start_bit_index % 8 == 0 {
+            let first_byte = self.start_bit_index / 8;
+            for word in (first_byte..first_byte + self.len() / 8).step_by(word_bytes) {
+                for i in 0..word_bytes / 2 {
+                    let front = self.buf.get(word + i);
+                    let back = self.buf.get(word + word_bytes - 1 - i);
+                    self.buf.set(word + i, 0xFF, back);
+                    self.buf.set(word + word_bytes - 1 - i, 0xFF, front);
+                }
+            }
+        } else {
+            for word in (0..self.len()).step_by(width) {
+                for i in 0..word_bytes / 2 {
+                    let front = word + i * 8;
+                    let back = word + width - (i + 1) * 8;
+                    let front_byte = self.read_bits(front, 8);
+                    let back_byte = self.read_bits(back, 8);
+                    self.write_bits(front, back_byte, 8);
+                    self.write_bits(back, front_byte, 8);
+                }
+            }
+        }
+    }
+
+    /// Reverse the order of the bits in [start, end).
+    fn reverse_range(&mut self, mut start: usize, mut end: usize) {
+        // Swap whole bytes from either end, reversing the bits within each
+        while end - start >= 16 {
+            let front = self.read_bits(start, 8);
+            let back = self.read_bits(end - 8, 8);
+            self.write_bits(start, back.reverse_bits(), 8);
+            self.write_bits(end - 8, front.reverse_bits(), 8);
+            start += 8;
+            end -= 8;
+        }
+        while end - start >= 2 {
+            self.swap(start, end - 1);
+            start += 1;
+            end -= 1;
+        }
+    }
+
+    /// Rotate the bits in this slice in-place such that the first `mid` bits move to the end
+    /// while the last `len - mid` bits move to the front.  Panics if `mid` is greater than the
+    /// length of the slice.
+    pub fn rotate_left(&mut self, mid: usize) {
+        assert!(mid <= self.len());
+        let len = self.len();
+        self.reverse_range(0, mid);
+        self.reverse_range(mid, len);
+        self.reverse_range(0, len);
+    }
+
+    /// Rotate the bits in this slice in-place such that the last `k` bits move to the front
+    /// while the first `len - k` bits move to the end.  Panics if `k` is greater than the length
+    /// of the slice.
+    pub fn rotate_right(&mut self, k: usize) {
+        assert!(k <= self.len());
+        self.rotate_left(self.len() - k);
     }
 }
 
 impl BitWrite for BitSliceMut<'_> {
-    fn write(&mut self, buf: &[u1]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
         let n = self.len().min(buf.len());
         for (i, bit) in buf.iter().enumerate().take(n) {
             self.set(i, *bit);
@@ -287,10 +968,10 @@ impl BitWrite for BitSliceMut<'_> {
         Ok(n)
     }
 
-    fn write_all(&mut self, buf: &[u1]) -> std::io::Result<()> {
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
         if buf.len() > self.len() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::UnexpectedEof,
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
                 "failed to write whole buffer",
             ));
         }
@@ -301,6 +982,85 @@ impl BitWrite for BitSliceMut<'_> {
         }
         Ok(())
     }
+
+    fn write_from_bytes(&mut self, buf: &[u8], bit_offset: usize, nbits: usize) -> io::Result<()> {
+        assert_bits_fit(buf.len(), bit_offset, nbits);
+        if nbits > self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to write whole buffer",
+            ));
+        }
+        let src = SliceBytes::new(buf);
+        copy_bits(src, bit_offset, &mut self.buf, self.start_bit_index, nbits);
+        Ok(())
+    }
+}
+
+/// An iterator over non-overlapping mutable chunks of a [`BitSliceMut`], created by
+/// [`BitSliceMut::chunks_mut`].
+#[derive(Debug)]
+pub struct BitSliceChunksMut<'a> {
+    rest: BitSliceMut<'a>,
+    chunk_size: usize,
+}
+
+impl<'a> Iterator for BitSliceChunksMut<'a> {
+    type Item = BitSliceMut<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.len() == 0 {
+            return None;
+        }
+        let mid = self.rest.len().min(self.chunk_size);
+        let rest = mem::replace(
+            &mut self.rest,
+            BitSliceMut {
+                buf: MutBytes::new(Default::default()),
+                start_bit_index: 0,
+                end_bit_index: 0,
+            },
+        );
+        let (chunk, rest) = rest.split_into(mid);
+        self.rest = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.rest.len().div_ceil(self.chunk_size);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for BitSliceChunksMut<'_> {}
+
+impl PartialEq for BitSliceMut<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // safety: these slice ranges will always be valid
+        PartialEq::eq(&self.get_slice(..).unwrap(), &other.get_slice(..).unwrap())
+    }
+}
+
+impl Eq for BitSliceMut<'_> {}
+
+impl Hash for BitSliceMut<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // safety: this slice range will always be valid
+        Hash::hash(&self.get_slice(..).unwrap(), state)
+    }
+}
+
+impl PartialOrd for BitSliceMut<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BitSliceMut<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // safety: these slice ranges will always be valid
+        Ord::cmp(&self.get_slice(..).unwrap(), &other.get_slice(..).unwrap())
+    }
 }
 
 impl PartialEq<&[u1]> for BitSliceMut<'_> {
@@ -310,6 +1070,7 @@ impl PartialEq<&[u1]> for BitSliceMut<'_> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl PartialEq<BitVec> for BitSliceMut<'_> {
     fn eq(&self, other: &BitVec) -> bool {
         // safety: this slice range will always be valid
@@ -326,6 +1087,17 @@ impl BitBuffer for BitSliceMut<'_> {
     }
 }
 
+impl BitBufferSplit for BitSliceMut<'_> {
+    type Part<'b>
+        = BitSlice<'b>
+    where
+        Self: 'b;
+
+    fn get_part<T: RangeBounds<usize>>(&self, range: T) -> B3Result<BitSlice<'_>> {
+        self.get_slice(range)
+    }
+}
+
 impl BitBufferMut for BitSliceMut<'_> {
     fn get_slice_mut<T: RangeBounds<usize>>(&mut self, range: T) -> B3Result<BitSliceMut<'_>> {
         self.get_slice_mut(range)
@@ -334,9 +1106,13 @@ impl BitBufferMut for BitSliceMut<'_> {
 
 #[cfg(test)]
 mod tests {
+    use std::hash::DefaultHasher;
+
+    use proptest::{collection::vec, prelude::*};
     use ux::u1;
 
-    use crate::bitvec;
+    use super::*;
+    use crate::{bitarray, bitvec};
 
     #[test]
     fn get_slice_from_bit_slice() {
@@ -356,6 +1132,21 @@ mod tests {
         assert_eq!(slice_two, bitvec!(1, 0, 1, 0));
     }
 
+    #[test]
+    fn get_empty_slice_from_bit_slice() {
+        let mut vec = bitvec!(1, 0, 1, 0, 1, 0, 1, 0);
+        let slice = vec.get_slice(8..).expect("valid slice");
+        assert_eq!(slice.len(), 0);
+        assert_eq!(slice.get_slice(..).expect("valid slice").len(), 0);
+
+        let mut slice = vec.get_slice_mut(3..).expect("valid slice");
+        assert_eq!(slice.get_slice(5..).expect("valid slice").len(), 0);
+        assert_eq!(slice.get_slice_mut(2..2).expect("valid slice").len(), 0);
+        let mut empty = slice.get_slice_mut(5..).expect("valid slice");
+        empty.copy_within(.., 0);
+        assert_eq!(empty.len(), 0);
+    }
+
     #[test]
     fn get_slice_mut_from_bit_slice_mut() {
         let mut vec = bitvec!(1, 0, 1, 0, 1, 0);
@@ -394,4 +1185,330 @@ mod tests {
 
         assert_eq!(slice_one, slice_two);
     }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn to_bitvec(bits: &[bool]) -> BitVec {
+        let mut vec = BitVec::new();
+        bits.iter().for_each(|&b| vec.push(u1::new(b as u8)));
+        vec
+    }
+
+    /// Build a BitVec with `offset` bits of garbage in front of `bits`, so slices of it start at
+    /// an arbitrary bit offset.
+    fn to_offset_bitvec(bits: &[bool], offset: usize) -> BitVec {
+        let mut vec = to_bitvec(&vec![true; offset]);
+        bits.iter().for_each(|&b| vec.push(u1::new(b as u8)));
+        vec
+    }
+
+    #[test]
+    fn test_tail_bits_canonicalized() {
+        let mut vec_one = bitvec!(1, 0, 1, 1);
+        vec_one.pop();
+        let vec_two = bitvec!(1, 0, 1);
+        assert_eq!(vec_one, vec_two);
+        assert_eq!(hash_of(&vec_one), hash_of(&vec_two));
+        assert_eq!(vec_one.cmp(&vec_two), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(bitvec!(0, 1) < bitvec!(1, 0));
+        assert!(bitvec!(1) < bitvec!(1, 0));
+        assert!(bitvec!(0, 0, 0, 0, 0, 0, 0, 0, 1) > bitvec!(0, 0, 0, 0, 0, 0, 0, 0));
+        let vec = bitvec!(1, 1, 0, 1);
+        assert!(vec.get_slice(1..).unwrap() < vec);
+        assert!(vec.get_slice(1..).unwrap() < bitarray!(1, 1));
+    }
+
+    #[test]
+    fn test_bulk_ops() {
+        let mut vec = bitvec!(0; 20);
+        let mut slice = vec.get_slice_mut(3..17).unwrap();
+        slice.fill(u1::new(1));
+        assert_eq!(slice, bitvec!(1; 14));
+        assert_eq!(vec.count_ones(), 14);
+        assert_eq!(vec.first_one(), Some(3));
+
+        let mut vec = bitvec!(1, 1, 0, 0, 1, 0, 1, 0, 0, 0);
+        let mut slice = vec.get_slice_mut(1..).unwrap();
+        slice.reverse();
+        assert_eq!(slice, bitvec!(0, 0, 0, 1, 0, 1, 0, 0, 1));
+        slice.rotate_left(3);
+        assert_eq!(slice, bitvec!(1, 0, 1, 0, 0, 1, 0, 0, 0));
+        slice.rotate_right(4);
+        assert_eq!(slice, bitvec!(1, 0, 0, 0, 1, 0, 1, 0, 0));
+        slice.swap(0, 8);
+        assert_eq!(slice, bitvec!(0, 0, 0, 0, 1, 0, 1, 0, 1));
+        slice.copy_within(4.., 0);
+        assert_eq!(slice, bitvec!(1, 0, 1, 0, 1, 0, 1, 0, 1));
+        assert_eq!(vec.at(0), u1::new(1));
+
+        let src = bitvec!(1, 0, 1, 1);
+        let mut vec = bitvec!(0; 6);
+        vec.get_slice_mut(1..5)
+            .unwrap()
+            .copy_from_bitslice(&src.get_slice(..).unwrap());
+        assert_eq!(vec, bitvec!(0, 1, 0, 1, 1, 0));
+
+        let mut left = bitvec!(1, 1, 1);
+        let mut right = bitvec!(0, 0, 0, 0);
+        left.get_slice_mut(..)
+            .unwrap()
+            .swap_with_bitslice(&mut right.get_slice_mut(1..).unwrap());
+        assert_eq!(left, bitvec!(0, 0, 0));
+        assert_eq!(right, bitvec!(0, 1, 1, 1));
+
+        let mut vec = BitVec::from_bytes(&[0xFF, 0x12, 0x34, 0x56, 0x78], 40);
+        let mut slice = vec.get_slice_mut(4..36).unwrap();
+        slice.swap_bytes_in_words(16);
+        assert_eq!(vec.to_bytes(), vec![0xF2, 0x3F, 0x16, 0x74, 0x58]);
+        let mut slice = vec.get_slice_mut(8..).unwrap();
+        slice.reverse_bits_in_bytes();
+        assert_eq!(vec.to_bytes(), vec![0xF2, 0xFC, 0x68, 0x2E, 0x1A]);
+    }
+
+    #[test]
+    fn test_split_at_mut() {
+        let mut vec = bitvec!(0; 16);
+        let mut slice = vec.get_slice_mut(2..14).unwrap();
+        let (mut left, mut right) = slice.split_at_mut(5);
+        assert_eq!(left.len(), 5);
+        assert_eq!(right.len(), 7);
+        // Bits 6 and 7 of the first byte belong to different halves
+        left.fill(u1::new(1));
+        right.set(0, u1::new(0));
+        right.set(1, u1::new(1));
+        left.swap_with_bitslice(&mut right.get_slice_mut(2..).unwrap());
+        assert_eq!(vec, bitvec!(0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0));
+
+        let mut slice = vec.get_slice_mut(..).unwrap();
+        let (empty, all) = slice.split_at_mut(0);
+        assert_eq!(empty.len(), 0);
+        assert_eq!(all.len(), 16);
+    }
+
+    #[test]
+    fn test_split_at_mut_get_slice_across_threads() {
+        let mut vec = bitvec!(0; 16);
+        let mut slice = vec.get_slice_mut(..).unwrap();
+        let (mut left, mut right) = slice.split_at_mut(5);
+        left.set(4, u1::new(1));
+        let left = left.get_slice(..).unwrap();
+        // The halves share a byte, but `left` can still be read on another thread while `right`
+        // is written
+        std::thread::scope(|s| {
+            let reader = s.spawn(|| left.iter().collect::<Vec<_>>());
+            right.fill(u1::new(1));
+            assert_eq!(reader.join().unwrap(), [0, 0, 0, 0, 1].map(u1::new));
+        });
+        assert_eq!(vec, bitvec!(0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1));
+    }
+
+    #[test]
+    fn test_chunks_mut_across_threads() {
+        let mut vec = bitvec!(0; 30);
+        let mut slice = vec.get_slice_mut(..).unwrap();
+        // Every pair of neighbouring chunks shares a byte, and each chunk is filled on its own
+        // thread, many times over to give the writes to the shared bytes a chance to interleave
+        std::thread::scope(|s| {
+            for (i, mut chunk) in slice.chunks_mut(3).enumerate() {
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        chunk.fill(u1::new(0));
+                        chunk.fill(u1::new((i % 2) as u8));
+                    }
+                });
+            }
+        });
+        let expected: Vec<_> = (0..30).map(|i| u1::new(((i / 3) % 2) as u8)).collect();
+        assert_eq!(vec.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_chunks_mut() {
+        let mut vec = bitvec!(0; 13);
+        let mut slice = vec.get_slice_mut(1..).unwrap();
+        let chunks = slice.chunks_mut(5);
+        assert_eq!(chunks.len(), 3);
+        let mut chunks: Vec<_> = chunks.collect();
+        assert_eq!(chunks[2].len(), 2);
+        // All of the chunks can be written to at the same time
+        for chunk in chunks.iter_mut() {
+            chunk.set(1, u1::new(1));
+        }
+        assert_eq!(vec, bitvec!(0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1));
+    }
+
+    /// Run `op` on a mutable slice of `bits` starting at bit `offset` within a larger buffer and
+    /// check that it produces the same bits as running `expected` on a `Vec<bool>`, without
+    /// touching any of the bits around the slice.
+    fn check_bulk_op(
+        bits: &[bool],
+        offset: usize,
+        op: impl FnOnce(&mut BitSliceMut<'_>),
+        expected: impl FnOnce(&mut Vec<bool>),
+    ) -> Result<(), TestCaseError> {
+        let mut backing = to_offset_bitvec(bits, offset);
+        (0..11).for_each(|_| backing.push(u1::new(1)));
+        op(&mut backing.get_slice_mut(offset..offset + bits.len()).unwrap());
+
+        let mut expected_bits = vec![true; offset];
+        let mut model = bits.to_vec();
+        expected(&mut model);
+        expected_bits.extend(model);
+        expected_bits.extend([true; 11]);
+        prop_assert_eq!(backing, to_bitvec(&expected_bits));
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bulk_ops_match_model(
+            bits in vec(any::<bool>(), 1..80),
+            other in vec(any::<bool>(), 1..80),
+            offset in 0usize..=8,
+            other_offset in 0usize..=8,
+            mid in any::<prop::sample::Index>(),
+            src_start in any::<prop::sample::Index>(),
+            src_end in any::<prop::sample::Index>(),
+            dest in any::<prop::sample::Index>(),
+            value in any::<bool>(),
+        ) {
+            let len = bits.len();
+            let mid = mid.index(len + 1);
+
+            check_bulk_op(&bits, offset, |s| s.reverse(), |v| v.reverse())?;
+            check_bulk_op(&bits, offset, |s| s.rotate_left(mid), |v| v.rotate_left(mid))?;
+            check_bulk_op(&bits, offset, |s| s.rotate_right(mid), |v| v.rotate_right(mid))?;
+            check_bulk_op(
+                &bits,
+                offset,
+                |s| s.fill(u1::new(value as u8)),
+                |v| v.fill(value),
+            )?;
+
+            // The byte-wise ops need whole bytes/words, so use the longest prefix which has them
+            for width in [8, 16, 32, 64] {
+                let words = &bits[..len / width * width];
+                if words.is_empty() {
+                    continue;
+                }
+                if width == 8 {
+                    check_bulk_op(
+                        words,
+                        offset,
+                        |s| s.reverse_bits_in_bytes(),
+                        |v| v.chunks_mut(8).for_each(|byte| byte.reverse()),
+                    )?;
+                }
+                check_bulk_op(
+                    words,
+                    offset,
+                    |s| s.swap_bytes_in_words(width),
+                    |v| {
+                        for word in v.chunks_mut(width) {
+                            let swapped: Vec<bool> = word.chunks(8).rev().flatten().copied().collect();
+                            word.copy_from_slice(&swapped);
+                        }
+                    },
+                )?;
+            }
+
+            let (start, end) = {
+                let a = src_start.index(len + 1);
+                let b = src_end.index(len + 1);
+                (a.min(b), a.max(b))
+            };
+            let dest = dest.index(len - (end - start) + 1);
+            check_bulk_op(
+                &bits,
+                offset,
+                |s| s.copy_within(start..end, dest),
+                |v| v.copy_within(start..end, dest),
+            )?;
+
+            // Copy from / swap with a slice of the same length at a (possibly) different offset
+            let other: Vec<bool> = other.iter().copied().cycle().take(len).collect();
+            let mut other_backing = to_offset_bitvec(&other, other_offset);
+            let other_range = other_offset..other_offset + len;
+            check_bulk_op(
+                &bits,
+                offset,
+                |s| s.copy_from_bitslice(&other_backing.get_slice(other_range.clone()).unwrap()),
+                |v| v.copy_from_slice(&other),
+            )?;
+            check_bulk_op(
+                &bits,
+                offset,
+                |s| s.swap_with_bitslice(&mut other_backing.get_slice_mut(other_range.clone()).unwrap()),
+                |v| v.copy_from_slice(&other),
+            )?;
+            prop_assert_eq!(other_backing.get_slice(other_range).unwrap(), to_bitvec(&bits));
+
+            // Writing to both halves of a split only changes their own bits
+            check_bulk_op(
+                &bits,
+                offset,
+                |s| {
+                    let (mut left, mut right) = s.split_at_mut(mid);
+                    left.fill(u1::new(value as u8));
+                    right.reverse();
+                },
+                |v| {
+                    let (left, right) = v.split_at_mut(mid);
+                    left.fill(value);
+                    right.reverse();
+                },
+            )?;
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_consistent_across_containers(
+            left in vec(any::<bool>(), 0..80),
+            right in vec(any::<bool>(), 0..80),
+            left_offset in 0usize..=8,
+            right_offset in 0usize..=8,
+        ) {
+            let expected_eq = left == right;
+            let expected_ord = left.cmp(&right);
+
+            let left_vec = to_bitvec(&left);
+            let right_vec = to_bitvec(&right);
+            let left_words = BitVec::<u64>::from(to_bitvec(&left));
+            let right_words = BitVec::<u64>::from(to_bitvec(&right));
+            let left_backing = to_offset_bitvec(&left, left_offset);
+            let right_backing = to_offset_bitvec(&right, right_offset);
+            let left_slice = left_backing.get_slice(left_offset..left_offset + left.len()).unwrap();
+            let right_slice =
+                right_backing.get_slice(right_offset..right_offset + right.len()).unwrap();
+
+            prop_assert_eq!(left_vec == right_vec, expected_eq);
+            prop_assert_eq!(left_words == right_words, expected_eq);
+            prop_assert_eq!(left_slice == right_slice, expected_eq);
+            prop_assert_eq!(left_slice == right_vec, expected_eq);
+            prop_assert_eq!(left_vec == right_slice, expected_eq);
+
+            prop_assert_eq!(left_vec.cmp(&right_vec), expected_ord);
+            prop_assert_eq!(left_words.cmp(&right_words), expected_ord);
+            prop_assert_eq!(left_slice.cmp(&right_slice), expected_ord);
+            prop_assert_eq!(left_slice.partial_cmp(&right_vec), Some(expected_ord));
+            prop_assert_eq!(left_vec.partial_cmp(&right_slice), Some(expected_ord));
+
+            // Equal bits hash the same regardless of the container or storage
+            let left_hash = hash_of(&left_vec);
+            prop_assert_eq!(hash_of(&left_words), left_hash);
+            prop_assert_eq!(hash_of(&left_slice), left_hash);
+            if expected_eq {
+                prop_assert_eq!(hash_of(&right_slice), left_hash);
+            }
+        }
+    }
 }
@@ -0,0 +1,354 @@
+//! Bit stuffing, as used by link layers which need to limit the length of runs of equal bits.
+//!
+//! After a run of [`Stuffing::run_length`] equal bits (of the polarity the rule applies to), the
+//! sender inserts a stuff bit of the opposite value, and the receiver removes it again.  A
+//! [`Stuffer`] stuffs the bits written to it, and a [`Destuffer`] removes the stuff bits from the
+//! bits read through it, reporting where the stream broke the rule.
+//!
+//! # Example
+//! ```
+//! use b3::{
+//!     bit_cursor::BitCursor, bit_read::BitRead, bitarray, bitvec,
+//!     stuffing::{Destuffer, HDLC},
+//! };
+//! use ux::u1;
+//!
+//! // Six ones are sent as 1111101
+//! let mut destuffer = Destuffer::new(BitCursor::new(bitvec!(1, 1, 1, 1, 1, 0, 1, 0)), HDLC);
+//! let mut buf = [u1::new(0); 7];
+//! destuffer.read_exact(&mut buf).unwrap();
+//! assert_eq!(buf, bitarray!(1, 1, 1, 1, 1, 1, 0));
+//! ```
+
+use ux::u1;
+
+use crate::{bit_read::BitRead, bit_write::BitWrite, io};
+
+/// Which runs of bits are limited by a [`Stuffing`] rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StuffPolarity {
+    /// A 0 is stuffed after a run of 1s
+    Ones,
+    /// A 1 is stuffed after a run of 0s
+    Zeros,
+    /// The opposite bit is stuffed after a run of either value
+    Both,
+}
+
+impl StuffPolarity {
+    fn applies_to(&self, bit: u1) -> bool {
+        match self {
+            StuffPolarity::Ones => bit == u1::new(1),
+            StuffPolarity::Zeros => bit == u1::new(0),
+            StuffPolarity::Both => true,
+        }
+    }
+}
+
+/// A bit stuffing rule: the longest run of equal bits allowed, and which bits it applies to.
+///
+/// Stuff bits count towards the following run, so with [`CAN`] stuffing `00000 1 1111` is
+/// followed by another stuff bit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Stuffing {
+    run_length: usize,
+    polarity: StuffPolarity,
+}
+
+impl Stuffing {
+    /// Panics if `run_length` is 0.
+    pub const fn new(run_length: usize, polarity: StuffPolarity) -> Self {
+        assert!(run_length > 0, "Stuffing run length must be at least 1");
+        Stuffing {
+            run_length,
+            polarity,
+        }
+    }
+
+    pub fn run_length(&self) -> usize {
+        self.run_length
+    }
+
+    pub fn polarity(&self) -> StuffPolarity {
+        self.polarity
+    }
+}
+
+/// HDLC (and so PPP, AX.25 and friends): a 0 after five consecutive 1s.
+pub const HDLC: Stuffing = Stuffing::new(5, StuffPolarity::Ones);
+/// CAN: the opposite bit after five consecutive equal bits.
+pub const CAN: Stuffing = Stuffing::new(5, StuffPolarity::Both);
+/// USB: a 0 after six consecutive 1s.  This applies to the bits before NRZI encoding, where a 0
+/// forces a transition on the wire.
+pub const USB: Stuffing = Stuffing::new(6, StuffPolarity::Ones);
+
+/// Tracks the current run of equal bits
+#[derive(Debug)]
+struct RunState {
+    stuffing: Stuffing,
+    last: Option<u1>,
+    run: usize,
+}
+
+impl RunState {
+    fn new(stuffing: Stuffing) -> Self {
+        RunState {
+            stuffing,
+            last: None,
+            run: 0,
+        }
+    }
+
+    /// Add a bit to the run, returning the stuff bit which must follow it, if any
+    fn push(&mut self, bit: u1) -> Option<u1> {
+        if self.last == Some(bit) {
+            self.run += 1;
+        } else {
+            self.last = Some(bit);
+            self.run = 1;
+        }
+        if self.run == self.stuffing.run_length && self.stuffing.polarity.applies_to(bit) {
+            Some(bit ^ u1::new(1))
+        } else {
+            None
+        }
+    }
+}
+
+/// A writer which inserts stuff bits into the bits written to an inner writer.
+#[derive(Debug)]
+pub struct Stuffer<W> {
+    inner: W,
+    state: RunState,
+}
+
+impl<W> Stuffer<W> {
+    pub fn new(inner: W, stuffing: Stuffing) -> Self {
+        Stuffer {
+            inner,
+            state: RunState::new(stuffing),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: BitWrite> BitWrite for Stuffer<W> {
+    fn write(&mut self, buf: &[u1]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u1]) -> io::Result<()> {
+        // Each input bit becomes at most two output bits
+        let mut stuffed = [u1::new(0); 128];
+        for chunk in buf.chunks(stuffed.len() / 2) {
+            let mut n = 0;
+            for &bit in chunk {
+                stuffed[n] = bit;
+                n += 1;
+                if let Some(stuff) = self.state.push(bit) {
+                    self.state.push(stuff);
+                    stuffed[n] = stuff;
+                    n += 1;
+                }
+            }
+            self.inner.write_all(&stuffed[..n])?;
+        }
+        Ok(())
+    }
+}
+
+/// A reader which removes the stuff bits from the bits read from an inner reader.
+///
+/// If a bit which should have been a stuff bit has the wrong value, reads fail with
+/// [`io::ErrorKind::InvalidData`], and [`Destuffer::error_position`] gives the position of that
+/// bit.  (HDLC flags and aborts are such violations, so frames should be delimited before
+/// destuffing them, e.g. with [`BitRead::take`].)
+#[derive(Debug)]
+pub struct Destuffer<R> {
+    inner: R,
+    state: RunState,
+    pending_stuff: Option<u1>,
+    position: usize,
+    error_position: Option<usize>,
+}
+
+impl<R> Destuffer<R> {
+    pub fn new(inner: R, stuffing: Stuffing) -> Self {
+        Destuffer {
+            inner,
+            state: RunState::new(stuffing),
+            pending_stuff: None,
+            position: 0,
+            error_position: None,
+        }
+    }
+
+    /// The number of (stuffed) bits which have been read from the inner reader.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The position in the inner reader's bits of the stuffing violation, if there was one.
+    pub fn error_position(&self) -> Option<usize> {
+        self.error_position
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead> Destuffer<R> {
+    fn stuffing_error() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, "bit stuffing violation")
+    }
+
+    /// Read the next data bit, or None at the end of the inner reader
+    fn read_bit(&mut self) -> io::Result<Option<u1>> {
+        if self.error_position.is_some() {
+            return Err(Self::stuffing_error());
+        }
+        let mut bit = [u1::new(0)];
+        loop {
+            if self.inner.read(&mut bit)? == 0 {
+                return Ok(None);
+            }
+            let position = self.position;
+            self.position += 1;
+            match self.pending_stuff.take() {
+                Some(stuff) if bit[0] == stuff => {
+                    self.state.push(stuff);
+                }
+                Some(_) => {
+                    self.error_position = Some(position);
+                    return Err(Self::stuffing_error());
+                }
+                None => {
+                    self.pending_stuff = self.state.push(bit[0]);
+                    return Ok(Some(bit[0]));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BitRead> BitRead for Destuffer<R> {
+    fn read(&mut self, buf: &mut [u1]) -> io::Result<usize> {
+        for (i, dst) in buf.iter_mut().enumerate() {
+            match self.read_bit()? {
+                Some(bit) => *dst = bit,
+                None => return Ok(i),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u1]) -> io::Result<()> {
+        if self.read(buf)? < buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_vec::BitVec,
+        bit_write_exts::BitWriteExts, bitarray, bitvec,
+    };
+
+    fn stuff(stuffing: Stuffing, bits: &[u1]) -> BitVec {
+        let mut stuffer = Stuffer::new(BitVec::new(), stuffing);
+        stuffer.write_all(bits).unwrap();
+        stuffer.into_inner()
+    }
+
+    #[test]
+    fn test_hdlc() {
+        // A flag's worth of data, 0x7E, can't be mistaken for a flag once stuffed
+        let mut stuffer = Stuffer::new(BitVec::new(), HDLC);
+        stuffer.write_u8(0x7E).unwrap();
+        stuffer.write_u8(0xFF).unwrap();
+        assert_eq!(
+            stuffer.into_inner(),
+            bitvec!(0, 1, 1, 1, 1, 1, 0, 1, 0, 1, 1, 1, 1, 1, 0, 1, 1, 1)
+        );
+
+        let stuffed = bitvec!(0, 1, 1, 1, 1, 1, 0, 1, 0, 1, 1, 1, 1, 1, 0, 1, 1, 1);
+        let mut destuffer = Destuffer::new(BitCursor::new(stuffed), HDLC);
+        assert_eq!(destuffer.read_u8().unwrap(), 0x7E);
+        assert_eq!(destuffer.read_u8().unwrap(), 0xFF);
+        assert_eq!(destuffer.position(), 18);
+        assert_eq!(destuffer.read(&mut [u1::new(0); 1]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_can() {
+        // Stuff bits count towards the next run
+        let bits = bitarray!(0, 0, 0, 0, 0, 1, 1, 1, 1, 0);
+        let stuffed = stuff(CAN, &bits);
+        assert_eq!(stuffed, bitvec!(0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 0, 0));
+
+        let mut destuffer = Destuffer::new(BitCursor::new(stuffed), CAN);
+        let mut buf = [u1::new(0); 10];
+        destuffer.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, bits);
+    }
+
+    #[test]
+    fn test_usb() {
+        let bits = bitarray!(1, 1, 1, 1, 1, 1, 1, 1);
+        assert_eq!(stuff(USB, &bits), bitvec!(1, 1, 1, 1, 1, 1, 0, 1, 1));
+        assert_eq!(stuff(HDLC, &bits), bitvec!(1, 1, 1, 1, 1, 0, 1, 1, 1));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bits: [u1; 64] =
+            core::array::from_fn(|i| u1::new((0x0FFF_07C0_3FF8_E0F0u64 >> i) as u8 & 1));
+        for stuffing in [HDLC, CAN, USB, Stuffing::new(3, StuffPolarity::Zeros)] {
+            let stuffed = stuff(stuffing, &bits);
+            let mut destuffer = Destuffer::new(BitCursor::new(stuffed), stuffing);
+            let mut buf = [u1::new(0); 64];
+            destuffer.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, bits);
+        }
+    }
+
+    #[test]
+    fn test_violation() {
+        // An HDLC flag in the middle of a frame
+        let raw = bitvec!(1, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1);
+        let mut destuffer = Destuffer::new(BitCursor::new(raw), HDLC);
+        let mut buf = [u1::new(0); 9];
+        let err = destuffer.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(destuffer.error_position(), Some(8));
+        assert!(destuffer.read_u1().is_err());
+    }
+}
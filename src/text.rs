@@ -0,0 +1,590 @@
+//! Reading and writing text fields made up of fixed-width character codes.
+//!
+//! A [`Charset`] maps between strings and sequences of codes, and a [`Framing`] describes how
+//! the end of the field is found.  Codes are read and written MSB first, like the other
+//! fixed-width fields.
+//!
+//! # Example
+//! ```
+//! use b3::{
+//!     bit_cursor::BitCursor,
+//!     bit_read_exts::BitReadExts,
+//!     bit_write_exts::BitWriteExts,
+//!     bitvec,
+//!     text::{Framing, AIS_6BIT},
+//! };
+//!
+//! let mut cursor = BitCursor::new(bitvec!(0; 48));
+//! let framing = Framing::Fixed { count: 8, pad: 0 };
+//! cursor.write_string(&AIS_6BIT, framing, "VESSEL").unwrap();
+//!
+//! let mut cursor = BitCursor::new(cursor.into_inner());
+//! // Trailing padding ('@' in AIS) is stripped
+//! assert_eq!(cursor.read_string(&AIS_6BIT, framing).unwrap(), "VESSEL");
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    bit_read::BitRead,
+    bit_write::BitWrite,
+    error::{B3Error, B3Result},
+    util::{read_bits_msb_first, write_bits_msb_first},
+};
+
+/// A mapping between strings and sequences of fixed-width character codes.
+///
+/// Charsets see the whole sequence at once, so they can be stateful (e.g. shift codes in
+/// Baudot or escape codes in GSM 7-bit).  Errors should be reported as
+/// [`B3Error::InvalidCode`] with the index of the offending code, or
+/// [`B3Error::UnencodableChar`] with the index of the offending char.
+pub trait Charset {
+    /// The width of each code, in bits.  Must be between 1 and 32, or reading and writing
+    /// strings will fail with [`B3Error::InvalidWidth`].
+    fn code_bits(&self) -> usize;
+
+    fn decode(&self, codes: &[u32]) -> B3Result<String>;
+
+    fn encode(&self, s: &str) -> B3Result<Vec<u32>>;
+}
+
+impl<C: Charset + ?Sized> Charset for &C {
+    fn code_bits(&self) -> usize {
+        (**self).code_bits()
+    }
+
+    fn decode(&self, codes: &[u32]) -> B3Result<String> {
+        (**self).decode(codes)
+    }
+
+    fn encode(&self, s: &str) -> B3Result<Vec<u32>> {
+        (**self).encode(s)
+    }
+}
+
+/// How the length of a text field is determined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// Exactly `count` codes.  When writing, shorter strings are padded with `pad` codes, and
+    /// when reading, trailing `pad` codes are stripped.
+    Fixed { count: usize, pad: u32 },
+    /// The number of codes, as a `bits` wide (1 to 32) unsigned integer, followed by the
+    /// codes.  Any other width fails with [`B3Error::InvalidWidth`].
+    LengthPrefixed { bits: usize },
+    /// The codes followed by the given terminator code.  The terminator is consumed but not
+    /// included in the string, and can't appear in a string being written.
+    Terminated(u32),
+}
+
+/// A charset with a single code per character, given by a table indexed by code.
+///
+/// For example, a charset for 4-bit hex digits:
+/// ```
+/// use b3::text::{CharTable, Charset};
+///
+/// const HEX: CharTable = CharTable::new(4, &[
+///     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F',
+/// ]);
+/// assert_eq!(HEX.encode("C0DE").unwrap(), vec![0xC, 0x0, 0xD, 0xE]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CharTable<'a> {
+    bits: usize,
+    chars: &'a [char],
+}
+
+impl<'a> CharTable<'a> {
+    pub const fn new(bits: usize, chars: &'a [char]) -> Self {
+        assert!(bits > 0 && bits <= 32, "Code width must be 1 to 32 bits");
+        CharTable { bits, chars }
+    }
+}
+
+impl Charset for CharTable<'_> {
+    fn code_bits(&self) -> usize {
+        self.bits
+    }
+
+    fn decode(&self, codes: &[u32]) -> B3Result<String> {
+        codes
+            .iter()
+            .enumerate()
+            .map(|(index, &code)| {
+                self.chars
+                    .get(code as usize)
+                    .copied()
+                    .ok_or(B3Error::InvalidCode { code, index })
+            })
+            .collect()
+    }
+
+    fn encode(&self, s: &str) -> B3Result<Vec<u32>> {
+        s.chars()
+            .enumerate()
+            .map(|(index, ch)| {
+                self.chars
+                    .iter()
+                    .position(|&c| c == ch)
+                    .map(|code| code as u32)
+                    .ok_or(B3Error::UnencodableChar { ch, index })
+            })
+            .collect()
+    }
+}
+
+/// The 6-bit ASCII used by AIS (ITU-R M.1371) for names and call signs: codes 0 to 31 are
+/// '@' to '_', and codes 32 to 63 are ' ' to '?'.
+#[rustfmt::skip]
+pub const AIS_6BIT: CharTable<'static> = CharTable::new(
+    6,
+    &[
+        '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+        'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '^', '_',
+        ' ', '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
+        '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    ],
+);
+
+const GSM_ESCAPE: u32 = 0x1B;
+
+#[rustfmt::skip]
+const GSM_BASIC: [char; 128] = [
+    '@', '£', '$', '¥', 'è', 'é', 'ù', 'ì', 'ò', 'Ç', '\n', 'Ø', 'ø', '\r', 'Å', 'å',
+    'Δ', '_', 'Φ', 'Γ', 'Λ', 'Ω', 'Π', 'Ψ', 'Σ', 'Θ', 'Ξ', '\u{1b}', 'Æ', 'æ', 'ß', 'É',
+    ' ', '!', '"', '#', '¤', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    '¡', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'Ä', 'Ö', 'Ñ', 'Ü', '§',
+    '¿', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'ä', 'ö', 'ñ', 'ü', 'à',
+];
+
+/// The codes which follow an escape code in the GSM extension table
+const GSM_EXTENSION: [(u32, char); 10] = [
+    (0x0A, '\u{c}'),
+    (0x14, '^'),
+    (0x28, '{'),
+    (0x29, '}'),
+    (0x2F, '\\'),
+    (0x3C, '['),
+    (0x3D, '~'),
+    (0x3E, ']'),
+    (0x40, '|'),
+    (0x65, '€'),
+];
+
+/// The GSM 03.38 7-bit default alphabet, including the characters in its extension table
+/// (which are encoded as an escape code followed by a second code).
+///
+/// Note that SMS user data packs septets LSB first into octets, which this doesn't undo: the
+/// codes are read MSB first like any other field.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Gsm7;
+
+impl Charset for Gsm7 {
+    fn code_bits(&self) -> usize {
+        7
+    }
+
+    fn decode(&self, codes: &[u32]) -> B3Result<String> {
+        let mut s = String::with_capacity(codes.len());
+        let mut iter = codes.iter().copied().enumerate();
+        while let Some((index, code)) = iter.next() {
+            if code == GSM_ESCAPE {
+                let (index, code) = iter.next().ok_or(B3Error::InvalidCode { code, index })?;
+                let ch = GSM_EXTENSION
+                    .iter()
+                    .find(|(ext, _)| *ext == code)
+                    .map(|(_, ch)| *ch)
+                    .ok_or(B3Error::InvalidCode { code, index })?;
+                s.push(ch);
+            } else {
+                let ch = GSM_BASIC
+                    .get(code as usize)
+                    .ok_or(B3Error::InvalidCode { code, index })?;
+                s.push(*ch);
+            }
+        }
+        Ok(s)
+    }
+
+    fn encode(&self, s: &str) -> B3Result<Vec<u32>> {
+        let mut codes = Vec::with_capacity(s.len());
+        for (index, ch) in s.chars().enumerate() {
+            if let Some(code) = GSM_BASIC.iter().position(|&c| c == ch && c != '\u{1b}') {
+                codes.push(code as u32);
+            } else if let Some((code, _)) = GSM_EXTENSION.iter().find(|(_, c)| *c == ch) {
+                codes.push(GSM_ESCAPE);
+                codes.push(*code);
+            } else {
+                return Err(B3Error::UnencodableChar { ch, index });
+            }
+        }
+        Ok(codes)
+    }
+}
+
+const BAUDOT_FIGS: u32 = 0x1B;
+const BAUDOT_LTRS: u32 = 0x1F;
+
+#[rustfmt::skip]
+const BAUDOT_LETTERS: [Option<char>; 32] = [
+    Some('\0'), Some('E'), Some('\n'), Some('A'), Some(' '), Some('S'), Some('I'), Some('U'),
+    Some('\r'), Some('D'), Some('R'), Some('J'), Some('N'), Some('F'), Some('C'), Some('K'),
+    Some('T'), Some('Z'), Some('L'), Some('W'), Some('H'), Some('Y'), Some('P'), Some('Q'),
+    Some('O'), Some('B'), Some('G'), None, Some('M'), Some('X'), Some('V'), None,
+];
+
+#[rustfmt::skip]
+const BAUDOT_FIGURES: [Option<char>; 32] = [
+    Some('\0'), Some('3'), Some('\n'), Some('-'), Some(' '), Some('\''), Some('8'), Some('7'),
+    Some('\r'), Some('\u{5}'), Some('4'), Some('\u{7}'), Some(','), None, Some(':'), Some('('),
+    Some('5'), Some('+'), Some(')'), Some('2'), None, Some('6'), Some('0'), Some('1'),
+    Some('9'), Some('?'), None, None, Some('.'), Some('/'), Some('='), None,
+];
+
+/// The 5-bit ITA2 (Baudot-Murray) code.  Decoding starts in the letters shift, and encoding
+/// inserts the FIGS and LTRS shift codes as needed.  The figures positions which ITA2 leaves
+/// for national use are treated as invalid.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Baudot;
+
+impl Charset for Baudot {
+    fn code_bits(&self) -> usize {
+        5
+    }
+
+    fn decode(&self, codes: &[u32]) -> B3Result<String> {
+        let mut s = String::with_capacity(codes.len());
+        let mut table = &BAUDOT_LETTERS;
+        for (index, &code) in codes.iter().enumerate() {
+            match code {
+                BAUDOT_FIGS => table = &BAUDOT_FIGURES,
+                BAUDOT_LTRS => table = &BAUDOT_LETTERS,
+                _ => {
+                    let ch = table
+                        .get(code as usize)
+                        .copied()
+                        .flatten()
+                        .ok_or(B3Error::InvalidCode { code, index })?;
+                    s.push(ch);
+                }
+            }
+        }
+        Ok(s)
+    }
+
+    fn encode(&self, s: &str) -> B3Result<Vec<u32>> {
+        let find = |table: &[Option<char>; 32], ch| {
+            table
+                .iter()
+                .position(|&c| c == Some(ch))
+                .map(|code| code as u32)
+        };
+        let mut codes = Vec::with_capacity(s.len());
+        let mut figures = false;
+        for (index, ch) in s.chars().enumerate() {
+            let current = if figures {
+                &BAUDOT_FIGURES
+            } else {
+                &BAUDOT_LETTERS
+            };
+            if let Some(code) = find(current, ch) {
+                codes.push(code);
+            } else if let Some(code) = find(&BAUDOT_FIGURES, ch) {
+                codes.push(BAUDOT_FIGS);
+                codes.push(code);
+                figures = true;
+            } else if let Some(code) = find(&BAUDOT_LETTERS, ch) {
+                codes.push(BAUDOT_LTRS);
+                codes.push(code);
+                figures = false;
+            } else {
+                return Err(B3Error::UnencodableChar { ch, index });
+            }
+        }
+        Ok(codes)
+    }
+}
+
+/// UTF-8, with one code per byte.  Lengths (for [`Framing::Fixed`] and
+/// [`Framing::LengthPrefixed`]) are therefore in bytes rather than characters.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Utf8;
+
+impl Charset for Utf8 {
+    fn code_bits(&self) -> usize {
+        8
+    }
+
+    fn decode(&self, codes: &[u32]) -> B3Result<String> {
+        // Every code was read from 8 bits, so this can't truncate
+        let bytes: Vec<u8> = codes.iter().map(|&code| code as u8).collect();
+        String::from_utf8(bytes).map_err(|err| {
+            let index = err.utf8_error().valid_up_to();
+            B3Error::InvalidCode {
+                code: codes[index],
+                index,
+            }
+        })
+    }
+
+    fn encode(&self, s: &str) -> B3Result<Vec<u32>> {
+        Ok(s.bytes().map(u32::from).collect())
+    }
+}
+
+fn read_code<R: BitRead + ?Sized>(reader: &mut R, bits: usize) -> B3Result<u32> {
+    let code = read_bits_msb_first(reader, bits)?;
+    u32::try_from(code).map_err(|_| B3Error::ValueOutOfRange {
+        value: code.into(),
+        bits: MAX_CODE_BITS,
+    })
+}
+
+fn write_code<W: BitWrite + ?Sized>(writer: &mut W, bits: usize, code: u32) -> B3Result<()> {
+    Ok(write_bits_msb_first(writer, bits, code.into())?)
+}
+
+/// The widest code a [`Charset`] or [`Framing::LengthPrefixed`] length can have
+const MAX_CODE_BITS: usize = 32;
+
+/// Check that `bits` is a valid code width, before anything is read or written
+fn check_code_bits(bits: usize) -> B3Result<()> {
+    if bits > 0 && bits <= MAX_CODE_BITS {
+        Ok(())
+    } else {
+        Err(B3Error::InvalidWidth {
+            bits,
+            max: MAX_CODE_BITS,
+        })
+    }
+}
+
+/// The largest value which fits in `bits` (at most [`MAX_CODE_BITS`]) bits
+fn max_code(bits: usize) -> u64 {
+    (1u64 << bits) - 1
+}
+
+pub(crate) fn read_string<R: BitRead + ?Sized, C: Charset + ?Sized>(
+    reader: &mut R,
+    charset: &C,
+    framing: Framing,
+) -> B3Result<String> {
+    let bits = charset.code_bits();
+    check_code_bits(bits)?;
+    if let Framing::LengthPrefixed { bits: prefix_bits } = framing {
+        check_code_bits(prefix_bits)?;
+    }
+    let mut codes = Vec::new();
+    match framing {
+        Framing::Fixed { count, pad } => {
+            for _ in 0..count {
+                codes.push(read_code(reader, bits)?);
+            }
+            while codes.last() == Some(&pad) {
+                codes.pop();
+            }
+        }
+        Framing::LengthPrefixed { bits: prefix_bits } => {
+            let count = read_code(reader, prefix_bits)?;
+            for _ in 0..count {
+                codes.push(read_code(reader, bits)?);
+            }
+        }
+        Framing::Terminated(terminator) => loop {
+            let code = read_code(reader, bits)?;
+            if code == terminator {
+                break;
+            }
+            codes.push(code);
+        },
+    }
+    charset.decode(&codes)
+}
+
+pub(crate) fn write_string<W: BitWrite + ?Sized, C: Charset + ?Sized>(
+    writer: &mut W,
+    charset: &C,
+    framing: Framing,
+    s: &str,
+) -> B3Result<()> {
+    let bits = charset.code_bits();
+    check_code_bits(bits)?;
+    if let Framing::LengthPrefixed { bits: prefix_bits } = framing {
+        check_code_bits(prefix_bits)?;
+    }
+    let mut codes = charset.encode(s)?;
+    match framing {
+        Framing::Fixed { count, pad } => {
+            if codes.len() > count {
+                return Err(B3Error::FieldTooLong {
+                    len: codes.len(),
+                    max: count,
+                });
+            }
+            codes.resize(count, pad);
+        }
+        Framing::LengthPrefixed { bits: prefix_bits } => {
+            let max = max_code(prefix_bits);
+            if codes.len() as u64 > max {
+                return Err(B3Error::FieldTooLong {
+                    len: codes.len(),
+                    max: max as usize,
+                });
+            }
+            write_code(writer, prefix_bits, codes.len() as u32)?;
+        }
+        Framing::Terminated(terminator) => {
+            if let Some(index) = codes.iter().position(|&code| code == terminator) {
+                return Err(B3Error::InvalidCode {
+                    code: terminator,
+                    index,
+                });
+            }
+            codes.push(terminator);
+        }
+    }
+    for code in codes {
+        write_code(writer, bits, code)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bit_cursor::BitCursor, bit_read_exts::BitReadExts, bit_write_exts::BitWriteExts, bitvec, io,
+    };
+
+    use super::*;
+
+    fn round_trip<C: Charset>(charset: C, framing: Framing, s: &str, nbits: usize) {
+        let mut cursor = BitCursor::new(bitvec!(0; nbits));
+        cursor.write_string(&charset, framing, s).unwrap();
+        assert_eq!(cursor.bits_remaining(), 0);
+        let mut cursor = BitCursor::new(cursor.into_inner());
+        assert_eq!(cursor.read_string(&charset, framing).unwrap(), s);
+    }
+
+    #[test]
+    fn test_charsets() {
+        round_trip(AIS_6BIT, Framing::Fixed { count: 7, pad: 0 }, "CALL 1", 42);
+        // "a{" is 'a', ESC, '(' plus a 4 bit length
+        round_trip(Gsm7, Framing::LengthPrefixed { bits: 4 }, "a{€", 4 + 5 * 7);
+        // "A1B" is A, FIGS, 1, LTRS, B plus the terminator (NUL)
+        round_trip(Baudot, Framing::Terminated(0), "A1B", 6 * 5);
+        round_trip(
+            Utf8,
+            Framing::LengthPrefixed { bits: 8 },
+            "héllo",
+            8 + 6 * 8,
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        // FIGS, '3', LTRS, 'E'
+        assert_eq!(Baudot.decode(&[0x1B, 0x01, 0x1F, 0x01]).unwrap(), "3E");
+        assert_eq!(Gsm7.decode(&[0x00, 0x1B, 0x65]).unwrap(), "@€");
+        assert_eq!(AIS_6BIT.decode(&[0x20, 0x30]).unwrap(), " 0");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Gsm7.decode(&[0x41, 0x1B, 0x41]),
+            Err(B3Error::InvalidCode {
+                code: 0x41,
+                index: 2
+            })
+        );
+        assert_eq!(
+            Gsm7.decode(&[0x41, 0x1B]),
+            Err(B3Error::InvalidCode {
+                code: 0x1B,
+                index: 1
+            })
+        );
+        // FIGS, then the unassigned 'F' position
+        assert_eq!(
+            Baudot.decode(&[0x1B, 0x0D]),
+            Err(B3Error::InvalidCode {
+                code: 0x0D,
+                index: 1
+            })
+        );
+        assert_eq!(
+            AIS_6BIT.encode("ab"),
+            Err(B3Error::UnencodableChar { ch: 'a', index: 0 })
+        );
+        assert_eq!(
+            Utf8.decode(&[0x61, 0xFF]),
+            Err(B3Error::InvalidCode {
+                code: 0xFF,
+                index: 1
+            })
+        );
+
+        let mut cursor = BitCursor::new(bitvec!(0; 64));
+        assert_eq!(
+            cursor.write_string(&Utf8, Framing::Fixed { count: 2, pad: 0 }, "abc"),
+            Err(B3Error::FieldTooLong { len: 3, max: 2 })
+        );
+        assert_eq!(
+            cursor.write_string(&Utf8, Framing::LengthPrefixed { bits: 1 }, "ab"),
+            Err(B3Error::FieldTooLong { len: 2, max: 1 })
+        );
+        assert_eq!(
+            cursor.write_string(&Utf8, Framing::Terminated(b'b' as u32), "ab"),
+            Err(B3Error::InvalidCode {
+                code: b'b' as u32,
+                index: 1
+            })
+        );
+        // No terminator before the end of the buffer
+        assert_eq!(
+            cursor.read_string(&Utf8, Framing::Terminated(0xFF)),
+            Err(B3Error::Io(io::ErrorKind::UnexpectedEof))
+        );
+    }
+
+    /// A charset with codes wider than 32 bits
+    struct Wide;
+
+    impl Charset for Wide {
+        fn code_bits(&self) -> usize {
+            40
+        }
+
+        fn decode(&self, codes: &[u32]) -> B3Result<String> {
+            Utf8.decode(codes)
+        }
+
+        fn encode(&self, s: &str) -> B3Result<Vec<u32>> {
+            Utf8.encode(s)
+        }
+    }
+
+    #[test]
+    fn test_invalid_widths() {
+        let mut cursor = BitCursor::new(bitvec!(0; 128));
+        let invalid = |bits| B3Error::InvalidWidth { bits, max: 32 };
+        assert_eq!(
+            cursor.write_string(&Wide, Framing::Fixed { count: 1, pad: 0 }, "a"),
+            Err(invalid(40))
+        );
+        assert_eq!(
+            cursor.read_string(&Wide, Framing::Fixed { count: 1, pad: 0 }),
+            Err(invalid(40))
+        );
+        for bits in [0, 33, 64] {
+            let framing = Framing::LengthPrefixed { bits };
+            assert_eq!(cursor.write_string(&Utf8, framing, "a"), Err(invalid(bits)));
+            assert_eq!(cursor.read_string(&Utf8, framing), Err(invalid(bits)));
+        }
+        // Nothing was read or written
+        assert_eq!(cursor.bits_remaining(), 128);
+        assert_eq!(cursor.into_inner(), bitvec!(0; 128));
+    }
+}
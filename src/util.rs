@@ -1,7 +1,9 @@
-use std::ops::RangeBounds;
+use core::{hash::Hasher, ops::RangeBounds};
 
 use ux::u1;
 
+use crate::{bit_read::BitRead, bit_write::BitWrite, io};
+
 /// Set the |bit_index| bit of |byte| to |value|
 pub(crate) fn set_bit(byte: &mut u8, bit_index: usize, value: u1) {
     // Mask out bit_index
@@ -41,6 +43,47 @@ pub(crate) fn get_bit(byte: u8, bit_index: usize) -> u1 {
     u1::new(result >> (7 - bit_index))
 }
 
+/// Panic if a buffer of |len| bytes can't hold |nbits| bits starting at bit |bit_offset|.
+pub(crate) fn assert_bits_fit(len: usize, bit_offset: usize, nbits: usize) {
+    assert!(
+        bit_offset + nbits <= len * 8,
+        "{} bits at bit offset {} don't fit in a buffer of {} bytes",
+        nbits,
+        bit_offset,
+        len
+    );
+}
+
+/// Read |nbits| (at most 64) bits, MSB first, as an unsigned integer.  Panics, before reading
+/// anything, if |nbits| is greater than 64; callers must check widths which come from users.
+pub(crate) fn read_bits_msb_first<R: BitRead + ?Sized>(
+    reader: &mut R,
+    nbits: usize,
+) -> io::Result<u64> {
+    assert!(nbits <= 64, "Can't read {} bits into a u64", nbits);
+    let mut buf = [u1::new(0); 64];
+    let buf = &mut buf[..nbits];
+    reader.read_exact(buf)?;
+    Ok(buf
+        .iter()
+        .fold(0u64, |value, bit| (value << 1) | u64::from(*bit)))
+}
+
+/// Write the low |nbits| (at most 64) bits of |value|, MSB first.  Panics, before writing
+/// anything, if |nbits| is greater than 64; callers must check widths which come from users.
+pub(crate) fn write_bits_msb_first<W: BitWrite + ?Sized>(
+    writer: &mut W,
+    nbits: usize,
+    value: u64,
+) -> io::Result<()> {
+    assert!(nbits <= 64, "Can't write {} bits from a u64", nbits);
+    let mut buf = [u1::new(0); 64];
+    for (i, bit) in buf[..nbits].iter_mut().enumerate() {
+        *bit = u1::new(((value >> (nbits - 1 - i)) & 1) as u8);
+    }
+    writer.write_all(&buf[..nbits])
+}
+
 /// Get the start and end bit indices from the given |range|, where |len| represents the length of
 /// the item being indexed.  The returned start_bit_index is inclusive and end_bit_index is
 /// exclusive.
@@ -49,16 +92,36 @@ pub(crate) fn get_start_end_bit_index_from_range<T: RangeBounds<usize>>(
     len: usize,
 ) -> (usize, usize) {
     let start_bit_index = match range.start_bound() {
-        std::ops::Bound::Included(&s) => s,
-        std::ops::Bound::Excluded(s) => s + 1,
-        std::ops::Bound::Unbounded => 0,
+        core::ops::Bound::Included(&s) => s,
+        core::ops::Bound::Excluded(s) => s + 1,
+        core::ops::Bound::Unbounded => 0,
     };
     let end_bit_index = match range.end_bound() {
-        std::ops::Bound::Included(s) => s + 1,
-        std::ops::Bound::Excluded(&s) => s,
-        // The end bit index is exclusive, so to handle the case where the length is 0 we make sure
-        // it's always at least '1'.
-        std::ops::Bound::Unbounded => std::cmp::max(len, 1),
+        core::ops::Bound::Included(s) => s + 1,
+        core::ops::Bound::Excluded(&s) => s,
+        core::ops::Bound::Unbounded => len,
     };
     (start_bit_index, end_bit_index)
 }
+
+/// Feed the canonical representation of a bit container into |state|: its length in bits
+/// followed by its bits packed MSB-first into bytes (with any unused bits in the last byte set to
+/// 0).  All bit containers hash this way so that equal containers have equal hashes, regardless of
+/// their type or storage.
+pub(crate) fn hash_bits<H: Hasher, I: Iterator<Item = u8>>(len: usize, bytes: I, state: &mut H) {
+    state.write_usize(len);
+    // Write in fixed-size chunks, so the sequence of writes only depends on the bytes
+    let mut chunk = [0u8; 32];
+    let mut chunk_len = 0;
+    for byte in bytes {
+        chunk[chunk_len] = byte;
+        chunk_len += 1;
+        if chunk_len == chunk.len() {
+            state.write(&chunk);
+            chunk_len = 0;
+        }
+    }
+    if chunk_len > 0 {
+        state.write(&chunk[..chunk_len]);
+    }
+}
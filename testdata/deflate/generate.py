@@ -0,0 +1,72 @@
+#!/usr/bin/env python3
+"""Regenerate the DEFLATE test corpus.
+
+Each input NAME.bin is compressed by zlib into raw DEFLATE streams (no zlib header or
+checksum) with several settings:
+
+    NAME.stored.deflate   level 0, so stored blocks only
+    NAME.fixed.deflate    level 6 with the Z_FIXED strategy, so fixed Huffman blocks only
+    NAME.default.deflate  level 6, which mostly gives dynamic Huffman blocks
+    NAME.best.deflate     level 9
+"""
+
+import os
+import zlib
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+WORDS = (
+    "the of and to in is that for it as was with be by on not he this are or his from at "
+    "which but have an they you were her she there been one all we their has would when "
+    "bit byte stream block code length distance literal huffman window match buffer"
+).split()
+
+
+def lcg(seed):
+    while True:
+        seed = (seed * 1103515245 + 12345) & 0x7FFFFFFF
+        yield seed >> 16
+
+
+def text():
+    rng = lcg(1)
+    words = []
+    while sum(len(w) + 1 for w in words) < 12000:
+        words.append(WORDS[next(rng) % len(WORDS)])
+        if next(rng) % 12 == 0:
+            words[-1] += ".\n"
+    return " ".join(words).encode()
+
+
+def random():
+    rng = lcg(2)
+    return bytes(next(rng) & 0xFF for _ in range(4096))
+
+
+def runs():
+    return b"a" * 10000 + b"ab" * 2000 + b"abc" * 1000 + bytes(3000)
+
+
+INPUTS = {
+    "empty": b"",
+    "short": b"hello, world\n",
+    "text": text(),
+    "random": random(),
+    "runs": runs(),
+    "allbytes": bytes(range(256)) * 4,
+}
+
+SETTINGS = {
+    "stored": (0, zlib.Z_DEFAULT_STRATEGY),
+    "fixed": (6, zlib.Z_FIXED),
+    "default": (6, zlib.Z_DEFAULT_STRATEGY),
+    "best": (9, zlib.Z_DEFAULT_STRATEGY),
+}
+
+for name, data in INPUTS.items():
+    with open(os.path.join(HERE, name + ".bin"), "wb") as f:
+        f.write(data)
+    for setting, (level, strategy) in SETTINGS.items():
+        compressor = zlib.compressobj(level, zlib.DEFLATED, -15, 9, strategy)
+        with open(os.path.join(HERE, "%s.%s.deflate" % (name, setting)), "wb") as f:
+            f.write(compressor.compress(data) + compressor.flush())